@@ -0,0 +1,180 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Error, LitStr};
+
+/// Embeds a directory of templates into the binary.
+///
+/// The path is resolved relative to the root of the crate that invokes the macro (the
+/// directory containing its `Cargo.toml`). Every file beneath it is embedded with
+/// `include_bytes!`, along with its path relative to the directory and its Unix permission
+/// bits, and every directory is recorded so that empty ones survive too. Symbolic links
+/// are not followed: a directory that contains one fails to compile.
+///
+/// The macro expands to an `anvil::embed::EmbeddedDir`, which can be stored in a `static`.
+///
+/// # Example
+///
+/// ```ignore
+/// use anvil::{embed::EmbeddedDir, embed_dir};
+///
+/// static TEMPLATES: EmbeddedDir = embed_dir!("templates");
+/// ```
+///
+/// This expands to code equivalent to:
+///
+/// ```ignore
+/// // Note: This is pseudocode showing what the macro generates
+/// static TEMPLATES: EmbeddedDir = ::anvil::embed::EmbeddedDir::new(
+///     &["controllers"],
+///     &[
+///         ::anvil::embed::EmbeddedFile::new(
+///             "controllers/{{name}}.rs",
+///             include_bytes!("/path/to/crate/templates/controllers/{{name}}.rs"),
+///             0o644,
+///         ),
+///     ],
+/// );
+/// ```
+///
+/// # Rebuilding
+///
+/// Editing an embedded file triggers a rebuild, but adding, removing or renaming one does
+/// not: procedural macros have no stable way to tell Cargo to watch a directory. Crates
+/// that embed a directory must list it in their build script, or the binary keeps
+/// embedding the files that existed when it was last built:
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     // Cargo watches a directory, and everything beneath it, for changes
+///     println!("cargo:rerun-if-changed=templates");
+/// }
+/// ```
+#[proc_macro]
+pub fn embed_dir(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+
+    let root = match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => Path::new(&dir).join(path.value()),
+        Err(_) => PathBuf::from(path.value()),
+    };
+
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    if let Err(e) = walk(&root, Path::new(""), &mut dirs, &mut files) {
+        return Error::new(
+            path.span(),
+            format!("failed to embed {}: {e}", root.display()),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let dirs = dirs.iter().map(|dir| relative(dir));
+    let files = files.iter().map(|(file, mode)| {
+        let name = relative(file);
+        let absolute = root.join(file).to_string_lossy().into_owned();
+        quote! {
+            ::anvil::embed::EmbeddedFile::new(#name, include_bytes!(#absolute), #mode)
+        }
+    });
+
+    let expanded = quote! {
+        ::anvil::embed::EmbeddedDir::new(&[#(#dirs),*], &[#(#files),*])
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Collects every directory and file beneath `root.join(relative)`, in sorted order.
+///
+/// Fails on symbolic links, which are neither followed nor embedded.
+fn walk(
+    root: &Path,
+    relative: &Path,
+    dirs: &mut Vec<PathBuf>,
+    files: &mut Vec<(PathBuf, u32)>,
+) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(root.join(relative))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for name in entries {
+        let path = relative.join(name);
+        // Links are not followed, so a link back up the tree can't make the walk endless
+        let metadata = fs::symlink_metadata(root.join(&path))?;
+        if metadata.is_symlink() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "symbolic links can't be embedded: {}",
+                    root.join(&path).display()
+                ),
+            ));
+        }
+        if metadata.is_dir() {
+            dirs.push(path.clone());
+            walk(root, &path, dirs, files)?;
+        } else {
+            files.push((path, mode(&metadata)));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the permission bits of a file, or `0o644` where they are not available.
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+/// Returns the permission bits of a file, or `0o644` where they are not available.
+#[cfg(not(unix))]
+fn mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// Formats a relative path with `/` separators on every platform.
+fn relative(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_walk_rejects_symbolic_links() {
+        // A link back to the template directory would otherwise be walked forever
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("templates/nested")).unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("templates/nested/up")).unwrap();
+
+        let (mut dirs, mut files) = (Vec::new(), Vec::new());
+        let error = walk(
+            &dir.path().join("templates"),
+            Path::new(""),
+            &mut dirs,
+            &mut files,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("nested/up"));
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use anvil::{
+    embed::EmbeddedDir,
+    embed_dir,
+    fs::{FileSystem, Memory},
+    generate_dir::GenerateDir,
+    Forge,
+};
+
+static TEMPLATES: EmbeddedDir = embed_dir!("tests/templates");
+
+#[test]
+fn test_embed_dir_embeds_files_paths_and_permissions() {
+    let paths: Vec<&Path> = TEMPLATES.files().iter().map(|file| file.path()).collect();
+    assert_eq!(
+        paths,
+        vec![Path::new("run.sh"), Path::new("{{name}}/greeting.txt")]
+    );
+    assert!(TEMPLATES.metadata(Path::new("{{name}}")).unwrap().is_dir());
+
+    let greeting = TEMPLATES.get("{{name}}/greeting.txt").unwrap();
+    assert_eq!(greeting.contents_str(), Some("Hello, {{name}}!\n"));
+
+    #[cfg(unix)]
+    assert_eq!(TEMPLATES.get("run.sh").unwrap().mode(), 0o755);
+}
+
+#[test]
+fn test_generate_dir_from_embedded_templates() {
+    let fs = Memory::new();
+    let renderer = |source: &str| -> Result<String, std::io::Error> {
+        Ok(source.replace("{{name}}", "world"))
+    };
+
+    GenerateDir::new(&TEMPLATES, renderer)
+        .forge_in(&fs, "out")
+        .unwrap();
+
+    assert_eq!(
+        fs.read_to_string(Path::new("out/world/greeting.txt"))
+            .unwrap(),
+        "Hello, world!\n"
+    );
+    assert_eq!(
+        fs.read_to_string(Path::new("out/run.sh")).unwrap(),
+        "#!/bin/sh\necho world\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_generated_files_keep_embedded_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let renderer = |source: &str| -> Result<String, std::io::Error> { Ok(source.to_string()) };
+
+    GenerateDir::new(&TEMPLATES, renderer)
+        .forge(temp.path())
+        .unwrap();
+
+    let mode = std::fs::metadata(temp.path().join("run.sh"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o755);
+}
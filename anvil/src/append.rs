@@ -1,7 +1,10 @@
-use std::{io::BufWriter, path::Path};
+use std::path::Path;
 use thiserror::Error;
 
-use crate::{Anvil, Forge};
+use crate::{
+    fs::{FileSystem, Kept},
+    render, Anvil, Forge,
+};
 
 /// Errors that can occur during file append operations.
 ///
@@ -15,7 +18,7 @@ pub enum AppendError {
 
     /// Error that occurred during template rendering.
     #[error("failed to render template during append operation: {0}")]
-    Template(#[from] Box<dyn std::error::Error>),
+    Template(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// A struct that appends template content to an existing file.
@@ -24,6 +27,14 @@ pub enum AppendError {
 /// to an existing file. The file must already exist; if it doesn't, the operation will fail
 /// with a "file not found" error.
 ///
+/// The template is rendered in full before the file is opened, so a template that fails
+/// to render never leaves half-appended content behind.
+///
+/// Because generators are often run more than once, `Append` can be made idempotent with
+/// [`once`](Append::once) or [`once_per`](Append::once_per), in which case content that is
+/// already present is not appended again. [`append_in`](Append::append_in) reports which
+/// [`Outcome`] occurred.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -60,6 +71,29 @@ pub enum AppendError {
 pub struct Append<A: Anvil> {
     /// The template to render and append
     template: A,
+    /// When to skip appending because the content is already present
+    presence: Presence,
+}
+
+/// How [`Append`] decides whether its content is already present in the file.
+#[derive(Debug, Clone, Default)]
+enum Presence {
+    /// Never skip; always append
+    #[default]
+    Ignore,
+    /// Skip if the file already contains the rendered content
+    Content,
+    /// Skip if the file already contains a line equal to the key
+    Key(String),
+}
+
+/// What [`Append`] did to the file it was asked to append to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The rendered content was appended to the file.
+    Appended,
+    /// The content was already present, so the file was left untouched.
+    Skipped,
 }
 
 impl<A: Anvil> Forge for Append<A> {
@@ -67,13 +101,16 @@ impl<A: Anvil> Forge for Append<A> {
 
     /// Appends the template content to an existing file.
     ///
-    /// This method:
-    /// 1. Opens the file in append mode
-    /// 2. Renders the template
-    /// 3. Appends the rendered content to the file
+    /// This method performs all file access through the given [`FileSystem`]:
+    /// 1. Renders the template into memory
+    /// 2. Appends the rendered content to the file in a single write, unless the append
+    ///    is idempotent and the content is already present
+    ///
+    /// Use [`append_in`](Append::append_in) to find out which [`Outcome`] occurred.
     ///
     /// # Parameters
     ///
+    /// * `fs` - The filesystem containing the file
     /// * `into` - Path to the file where content will be appended
     ///
     /// # Returns
@@ -87,20 +124,8 @@ impl<A: Anvil> Forge for Append<A> {
     ///
     /// Returns `AppendError::StdIo` if there's an IO error (like file not found),
     /// or `AppendError::Template` if there's an error during template rendering.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
-        let path = into.as_ref();
-        let file = std::fs::OpenOptions::new()
-            .append(true)
-            .open(path)
-            .map_err(AppendError::StdIo)?;
-
-        let mut writer = BufWriter::new(file);
-
-        self.template
-            .anvil(&mut writer)
-            .map_err(|e| AppendError::Template(Box::new(e)))?;
-
-        Ok(())
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.append_in(fs, into).map(|_| ())
     }
 }
 
@@ -116,13 +141,138 @@ impl<A: Anvil> Append<A> {
     ///
     /// A new `Append` instance configured with the provided template.
     pub fn new(template: A) -> Self {
-        Self { template }
+        Self {
+            template,
+            presence: Presence::default(),
+        }
+    }
+
+    /// Makes the append idempotent by skipping it when the file already contains the
+    /// rendered content.
+    ///
+    /// # Returns
+    ///
+    /// The `Append` instance, which now only appends content that is not yet present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use anvil::{Forge, append::Append};
+    /// # use anvil::Anvil;
+    /// # use std::io::Write;
+    /// # struct ModuleLine;
+    /// # impl Anvil for ModuleLine {
+    /// #     type Error = std::io::Error;
+    /// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+    /// #         writer.write_all(b"pub mod users;\n")
+    /// #     }
+    /// # }
+    ///
+    /// // Running this twice only adds the module line once
+    /// let append = Append::new(ModuleLine).once();
+    /// append.forge("./src/controllers/mod.rs").unwrap();
+    /// append.forge("./src/controllers/mod.rs").unwrap();
+    /// ```
+    pub fn once(mut self) -> Self {
+        self.presence = Presence::Content;
+        self
+    }
+
+    /// Makes the append idempotent by skipping it when the file already contains a line
+    /// equal to `key`, ignoring surrounding whitespace.
+    ///
+    /// This is useful when the rendered content may vary between runs, but a single line
+    /// (such as a `pub mod` declaration) identifies whether it was appended before.
+    ///
+    /// # Parameters
+    ///
+    /// * `key` - The line that marks the content as already present
+    ///
+    /// # Returns
+    ///
+    /// The `Append` instance, which now only appends when `key` is not yet present.
+    pub fn once_per(mut self, key: impl Into<String>) -> Self {
+        self.presence = Presence::Key(key.into());
+        self
+    }
+
+    /// Appends to the file on the real disk and reports what happened.
+    ///
+    /// This is shorthand for calling [`append_in`](Append::append_in) with
+    /// [`Disk`](crate::fs::Disk).
+    ///
+    /// # Parameters
+    ///
+    /// * `into` - Path to the file where content will be appended
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Outcome, AppendError>` - The [`Outcome`] if successful, or an error
+    ///   under the same conditions as [`append_in`](Append::append_in).
+    pub fn append(&self, into: impl AsRef<Path>) -> Result<Outcome, AppendError> {
+        self.append_in(&crate::fs::Disk, into)
+    }
+
+    /// Appends to the file against the given filesystem and reports what happened.
+    ///
+    /// This method:
+    /// 1. Renders the template into memory
+    /// 2. If the append is idempotent, reads the file and checks whether the content is
+    ///    already present
+    /// 3. Otherwise appends the rendered content to the file in a single write
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem containing the file
+    /// * `into` - Path to the file where content will be appended
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Outcome, AppendError>` - The [`Outcome`] if successful, or an error if:
+    ///   - The file doesn't exist
+    ///   - File permissions don't allow writing
+    ///   - Template rendering fails
+    pub fn append_in(
+        &self,
+        fs: &dyn FileSystem,
+        into: impl AsRef<Path>,
+    ) -> Result<Outcome, AppendError> {
+        let path = into.as_ref();
+
+        let content = render(&self.template).map_err(|e| AppendError::Template(Box::new(e)))?;
+
+        let present = match &self.presence {
+            Presence::Ignore => false,
+            Presence::Content => {
+                let existing = fs.read(path).map_err(AppendError::StdIo)?;
+                content.is_empty()
+                    || existing
+                        .windows(content.len())
+                        .any(|window| window == content.as_slice())
+            }
+            Presence::Key(key) => {
+                let existing = fs.read(path).map_err(AppendError::StdIo)?;
+                String::from_utf8_lossy(&existing)
+                    .lines()
+                    .any(|line| line.trim() == key.trim())
+            }
+        };
+        if present {
+            fs.keep(path, Kept::Identical);
+            return Ok(Outcome::Skipped);
+        }
+
+        fs.append(path, &content).map_err(AppendError::StdIo)?;
+
+        Ok(Outcome::Appended)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
     use std::io::Write;
     use tempfile::{tempdir, NamedTempFile};
 
@@ -185,4 +335,70 @@ mod tests {
             other => unreachable!("Expected AppendError::StdIo but got: {:?}", other),
         }
     }
+
+    #[test]
+    fn test_append_leaves_file_untouched_on_template_error() {
+        // A template that writes some content before failing
+        struct PartialAnvil;
+
+        impl Anvil for PartialAnvil {
+            type Error = std::io::Error;
+
+            fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+                writer.write_all(b"partial")?;
+                Err(std::io::Error::other("Template error"))
+            }
+        }
+
+        // Create a temporary file with some initial content
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"Initial content\n").unwrap();
+
+        let result = Append::new(PartialAnvil).forge(temp_file.path());
+        assert!(matches!(result, Err(AppendError::Template(_))));
+
+        // The partial output should never reach the file
+        let content = std::fs::read_to_string(temp_file.path()).unwrap();
+        assert_eq!(content, "Initial content\n");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_append_once_skips_content_already_present() {
+        let fs = Memory::new().with_file("mod.rs", "pub mod posts;\n");
+
+        let append = Append::new(MockAnvil {
+            content: "pub mod users;\n".to_string(),
+        })
+        .once();
+
+        // Running the same append twice only adds the content once
+        assert_eq!(append.append_in(&fs, "mod.rs").unwrap(), Outcome::Appended);
+        assert_eq!(append.append_in(&fs, "mod.rs").unwrap(), Outcome::Skipped);
+
+        let content = fs.read_to_string(Path::new("mod.rs")).unwrap();
+        assert_eq!(content, "pub mod posts;\npub mod users;\n");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_append_once_per_key_line() {
+        let fs = Memory::new().with_file("mod.rs", "    pub mod users;\n");
+
+        // The rendered content differs, but the key line is already present
+        let append = Append::new(MockAnvil {
+            content: "// Users controller\npub mod users;\n".to_string(),
+        })
+        .once_per("pub mod users;");
+        assert_eq!(append.append_in(&fs, "mod.rs").unwrap(), Outcome::Skipped);
+
+        let append = Append::new(MockAnvil {
+            content: "pub mod posts;\n".to_string(),
+        })
+        .once_per("pub mod posts;");
+        assert_eq!(append.append_in(&fs, "mod.rs").unwrap(), Outcome::Appended);
+
+        let content = fs.read_to_string(Path::new("mod.rs")).unwrap();
+        assert_eq!(content, "    pub mod users;\npub mod posts;\n");
+    }
 }
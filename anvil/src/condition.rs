@@ -0,0 +1,531 @@
+use std::{convert::Infallible, error::Error, path::Path};
+
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{fs::FileSystem, Forge};
+
+/// A condition that decides whether a conditional operation runs.
+///
+/// A predicate is checked against the filesystem and the path the operation is forged
+/// into. It is implemented for `bool`, for conditions known up front such as command line
+/// flags, and for closures taking the filesystem and path. The functions in this module
+/// build predicates for common checks on the target: [`exists`], [`is_dir`], [`is_file`]
+/// and [`contains`], which can be inverted with [`not`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "memory")] {
+/// use anvil::{condition::Predicate, fs::{FileSystem, Memory}};
+/// use std::path::Path;
+///
+/// let fs = Memory::new().with_file("Cargo.toml", "[package]");
+/// let missing = |fs: &dyn FileSystem, path: &Path| !fs.exists(path);
+///
+/// assert!(!missing.test(&fs, Path::new("Cargo.toml")));
+/// assert!(true.test(&fs, Path::new("Cargo.toml")));
+/// # }
+/// ```
+pub trait Predicate {
+    /// Returns `true` if the condition holds for `path`.
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool;
+}
+
+impl Predicate for bool {
+    fn test(&self, _fs: &dyn FileSystem, _path: &Path) -> bool {
+        *self
+    }
+}
+
+impl<F> Predicate for F
+where
+    F: Fn(&dyn FileSystem, &Path) -> bool,
+{
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        self(fs, path)
+    }
+}
+
+/// A predicate that holds if something exists at the target path.
+#[derive(Debug, Clone, Copy)]
+pub struct Exists;
+
+impl Predicate for Exists {
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        fs.exists(path)
+    }
+}
+
+/// Returns a predicate that holds if something exists at the target path.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, condition::exists, touch::Touch};
+///
+/// // Only mark directories that are already there
+/// Touch::new().at(".gitkeep").when(exists()).forge("./storage").unwrap();
+/// ```
+pub fn exists() -> Exists {
+    Exists
+}
+
+/// A predicate that holds if the target path is a directory.
+#[derive(Debug, Clone, Copy)]
+pub struct IsDir;
+
+impl Predicate for IsDir {
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        fs.metadata(path).is_ok_and(|metadata| metadata.is_dir())
+    }
+}
+
+/// Returns a predicate that holds if the target path is a directory.
+pub fn is_dir() -> IsDir {
+    IsDir
+}
+
+/// A predicate that holds if the target path is a file.
+#[derive(Debug, Clone, Copy)]
+pub struct IsFile;
+
+impl Predicate for IsFile {
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        fs.metadata(path).is_ok_and(|metadata| metadata.is_file())
+    }
+}
+
+/// Returns a predicate that holds if the target path is a file.
+pub fn is_file() -> IsFile {
+    IsFile
+}
+
+/// A predicate that holds if the target file's content matches a regular expression.
+#[derive(Debug, Clone)]
+pub struct Contains {
+    /// The pattern to look for
+    regex: Regex,
+}
+
+impl Predicate for Contains {
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        fs.read_to_string(path)
+            .is_ok_and(|content| self.regex.is_match(&content))
+    }
+}
+
+/// Returns a predicate that holds if the target file's content matches `regex`.
+///
+/// The predicate doesn't hold if the file doesn't exist or isn't valid UTF-8.
+///
+/// # Parameters
+///
+/// * `regex` - The pattern to look for
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, condition::contains, inject::Regex, transform::Transform};
+///
+/// // Only add the dependency if Cargo.toml doesn't have it yet
+/// let add_serde = Transform::new(|content| {
+///     Ok(content.replace("[dependencies]\n", "[dependencies]\nserde = \"1\"\n"))
+/// });
+///
+/// add_serde
+///     .unless(contains(Regex::new(r"(?m)^serde\s*=").unwrap()))
+///     .forge("./Cargo.toml")
+///     .unwrap();
+/// ```
+pub fn contains(regex: Regex) -> Contains {
+    Contains { regex }
+}
+
+/// A predicate that holds if another predicate doesn't.
+#[derive(Debug, Clone, Copy)]
+pub struct Not<P: Predicate>(P);
+
+impl<P: Predicate> Predicate for Not<P> {
+    fn test(&self, fs: &dyn FileSystem, path: &Path) -> bool {
+        !self.0.test(fs, path)
+    }
+}
+
+/// Returns a predicate that holds if `predicate` doesn't.
+///
+/// # Parameters
+///
+/// * `predicate` - The predicate to invert
+pub fn not<P: Predicate>(predicate: P) -> Not<P> {
+    Not(predicate)
+}
+
+/// Returns a closure unchanged as a predicate.
+///
+/// Closures passed straight to [`when`](crate::ForgeExt::when) need their parameter types
+/// spelled out. Passing them through `custom` lets the compiler infer them instead.
+///
+/// # Parameters
+///
+/// * `predicate` - The closure checking the filesystem and target path
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, condition::custom, mkdir::Mkdir};
+///
+/// // Only create the directory inside a Rust project
+/// Mkdir::new()
+///     .when(custom(|fs, _path| fs.exists("Cargo.toml".as_ref())))
+///     .forge("./src/bin")
+///     .unwrap();
+/// ```
+pub fn custom<F>(predicate: F) -> F
+where
+    F: Fn(&dyn FileSystem, &Path) -> bool,
+{
+    predicate
+}
+
+/// A struct that only runs an operation if a [`Predicate`] holds.
+///
+/// When the predicate doesn't hold, nothing happens and the operation succeeds.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, mkdir::Mkdir};
+///
+/// let with_migrations = std::env::args().any(|arg| arg == "--migrations");
+///
+/// Mkdir::new()
+///     .when(with_migrations)
+///     .forge("./migrations")
+///     .unwrap();
+/// ```
+pub struct When<F: Forge, P: Predicate> {
+    /// The operation to run
+    forge: F,
+    /// The condition it runs under
+    predicate: P,
+}
+
+impl<F: Forge, P: Predicate> When<F, P> {
+    /// Creates a new `When` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `predicate` - The condition it runs under
+    ///
+    /// # Returns
+    ///
+    /// A new `When` instance.
+    pub fn new(forge: F, predicate: P) -> Self {
+        Self { forge, predicate }
+    }
+
+    /// Runs `alternative` instead when the predicate doesn't hold.
+    ///
+    /// # Parameters
+    ///
+    /// * `alternative` - The operation to run if the predicate doesn't hold
+    ///
+    /// # Returns
+    ///
+    /// A [`Branch`] choosing between the two operations.
+    pub fn otherwise<O: Forge>(self, alternative: O) -> Branch<F, O, P> {
+        Branch::new(self.predicate, self.forge, alternative)
+    }
+}
+
+impl<F: Forge, P: Predicate> Forge for When<F, P> {
+    type Error = F::Error;
+
+    /// Runs the wrapped operation if the predicate holds for the given path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the predicate is checked and the operation runs against
+    /// * `into` - Path where the operation will be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), F::Error>` - Ok if the predicate doesn't hold, otherwise the result of
+    ///   the wrapped operation.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        if !self.predicate.test(fs, into) {
+            return Ok(());
+        }
+        self.forge.forge_in(fs, into)
+    }
+}
+
+/// A struct that only runs an operation if a [`Predicate`] doesn't hold.
+///
+/// `Unless` is the inverse of [`When`], for the common case of leaving existing files alone
+/// without relying on the operation failing.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, condition::exists, generate::Generate, inject::{Inject, Location}};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Template(&'static str);
+/// # impl Anvil for Template {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(self.0.as_bytes())
+/// #     }
+/// # }
+///
+/// // Generate the routes file unless it exists, otherwise add the route to it
+/// Generate::new(Template("mod routes {\n    // anvil:routes\n    get!(\"/users\");\n}\n"))
+///     .unless(exists())
+///     .otherwise(Inject::new(
+///         Template("    get!(\"/users\");\n"),
+///         Location::Anchor("routes".to_string()),
+///     ))
+///     .forge("./config/routes.rb")
+///     .unwrap();
+/// ```
+pub struct Unless<F: Forge, P: Predicate> {
+    /// The operation to run
+    forge: F,
+    /// The condition that prevents it from running
+    predicate: P,
+}
+
+impl<F: Forge, P: Predicate> Unless<F, P> {
+    /// Creates a new `Unless` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `predicate` - The condition that prevents it from running
+    ///
+    /// # Returns
+    ///
+    /// A new `Unless` instance.
+    pub fn new(forge: F, predicate: P) -> Self {
+        Self { forge, predicate }
+    }
+
+    /// Runs `alternative` instead when the predicate holds.
+    ///
+    /// # Parameters
+    ///
+    /// * `alternative` - The operation to run if the predicate holds
+    ///
+    /// # Returns
+    ///
+    /// A [`Branch`] choosing between the two operations.
+    pub fn otherwise<O: Forge>(self, alternative: O) -> Branch<O, F, P> {
+        Branch::new(self.predicate, alternative, self.forge)
+    }
+}
+
+impl<F: Forge, P: Predicate> Forge for Unless<F, P> {
+    type Error = F::Error;
+
+    /// Runs the wrapped operation unless the predicate holds for the given path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the predicate is checked and the operation runs against
+    /// * `into` - Path where the operation will be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), F::Error>` - Ok if the predicate holds, otherwise the result of the
+    ///   wrapped operation.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        if self.predicate.test(fs, into) {
+            return Ok(());
+        }
+        self.forge.forge_in(fs, into)
+    }
+}
+
+/// A struct that runs one of two operations depending on a [`Predicate`].
+///
+/// A `Branch` is usually built with [`When::otherwise`] or [`Unless::otherwise`]. Unlike
+/// [`Either`](crate::either::Either), the choice is made before anything runs, so a failure
+/// of the chosen operation is reported rather than hidden by the other one.
+pub struct Branch<T: Forge, O: Forge, P: Predicate> {
+    /// The condition deciding which operation runs
+    predicate: P,
+    /// The operation to run if the predicate holds
+    then: T,
+    /// The operation to run if it doesn't
+    otherwise: O,
+}
+
+/// Errors that can occur when running a [`Branch`] operation.
+#[derive(Error, Debug)]
+pub enum BranchError<T: Error + 'static, O: Error + 'static> {
+    /// The predicate held, and the operation chosen for that case failed.
+    #[error("conditional operation failed: {0}")]
+    Then(#[source] T),
+
+    /// The predicate didn't hold, and the alternative operation failed.
+    #[error("alternative operation failed: {0}")]
+    Otherwise(#[source] O),
+}
+
+impl<T: Forge, O: Forge, P: Predicate> Branch<T, O, P> {
+    /// Creates a new `Branch` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `predicate` - The condition deciding which operation runs
+    /// * `then` - The operation to run if the predicate holds
+    /// * `otherwise` - The operation to run if it doesn't
+    ///
+    /// # Returns
+    ///
+    /// A new `Branch` instance.
+    pub fn new(predicate: P, then: T, otherwise: O) -> Self {
+        Self {
+            predicate,
+            then,
+            otherwise,
+        }
+    }
+}
+
+impl<T: Forge, O: Forge, P: Predicate> Forge for Branch<T, O, P>
+where
+    T::Error: 'static,
+    O::Error: 'static,
+{
+    type Error = BranchError<T::Error, O::Error>;
+
+    /// Checks the predicate for the given path and runs the operation it selects.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the predicate is checked and the operation runs against
+    /// * `into` - Path where the operation will be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), BranchError<T::Error, O::Error>>` - The result of the selected
+    ///   operation.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        if self.predicate.test(fs, into) {
+            self.then.forge_in(fs, into).map_err(BranchError::Then)
+        } else {
+            self.otherwise
+                .forge_in(fs, into)
+                .map_err(BranchError::Otherwise)
+        }
+    }
+}
+
+/// An operation that does nothing.
+///
+/// `Skip` fills the place of an operation that isn't needed, such as the alternative of a
+/// [`Branch`] or a step of a [`Sequence`](crate::sequence::Sequence) that is switched off.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "memory")] {
+/// use anvil::{Forge, condition::Skip, fs::{FileSystem, Memory}};
+/// use std::path::Path;
+///
+/// let fs = Memory::new();
+/// Skip.forge_in(&fs, "anything.txt").unwrap();
+///
+/// assert!(!fs.exists(Path::new("anything.txt")));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Skip;
+
+impl Forge for Skip {
+    type Error = Infallible;
+
+    /// Does nothing.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Infallible>` - Always Ok.
+    fn forge_in(&self, _fs: &dyn FileSystem, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{fs::Memory, mkdir::Mkdir, ForgeExt};
+
+    #[test]
+    fn test_when_runs_only_if_predicate_holds() {
+        let fs = Memory::new().with_dir("a");
+
+        Mkdir::new().when(false).forge_in(&fs, "skipped").unwrap();
+        assert!(!fs.exists(Path::new("skipped")));
+
+        let beside_a = |fs: &dyn FileSystem, _path: &Path| fs.exists(Path::new("a"));
+        Mkdir::new().when(beside_a).forge_in(&fs, "b").unwrap();
+        assert!(fs.exists(Path::new("b")));
+    }
+
+    #[test]
+    fn test_target_predicates() {
+        let fs = Memory::new()
+            .with_file("Cargo.toml", "[dependencies]\nserde = \"1\"\n")
+            .with_dir("src");
+        let cargo = Path::new("Cargo.toml");
+        let src = Path::new("src");
+
+        assert!(exists().test(&fs, cargo));
+        assert!(!exists().test(&fs, Path::new("missing")));
+        assert!(is_file().test(&fs, cargo) && !is_file().test(&fs, src));
+        assert!(is_dir().test(&fs, src) && !is_dir().test(&fs, cargo));
+
+        let serde = contains(Regex::new(r"(?m)^serde\s*=").unwrap());
+        assert!(serde.test(&fs, cargo));
+        // Directories and missing files never match
+        assert!(!serde.test(&fs, src));
+        assert!(not(serde).test(&fs, Path::new("missing")));
+
+        assert!(custom(|_fs, path| path.starts_with("src")).test(&fs, src));
+    }
+
+    #[test]
+    fn test_unless_and_otherwise_choose_one_operation() {
+        let fs = Memory::new()
+            .with_file("notes.txt", "old")
+            .with_dir("existing");
+
+        Mkdir::new()
+            .unless(exists())
+            .forge_in(&fs, "notes.txt")
+            .unwrap();
+        assert!(is_file().test(&fs, Path::new("notes.txt")));
+
+        // The existing file is kept and only the alternative runs
+        let marker = Mkdir::new().at("touched");
+        let branch = Mkdir::new().unless(exists()).otherwise(marker);
+        branch.forge_in(&fs, "existing").unwrap();
+        assert!(fs.exists(Path::new("existing/touched")));
+
+        branch.forge_in(&fs, "new").unwrap();
+        assert!(is_dir().test(&fs, Path::new("new")));
+
+        // A failure of the chosen operation is reported, not swallowed
+        let failing = Skip.when(is_dir()).otherwise(Mkdir::new().at("nested"));
+        let error = failing.forge_in(&fs, "notes.txt").unwrap_err();
+        assert!(matches!(error, BranchError::Otherwise(_)));
+        failing.forge_in(&fs, "new").unwrap();
+    }
+}
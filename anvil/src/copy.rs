@@ -0,0 +1,300 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::{
+    embed::Source,
+    fs::{normalize, FileSystem},
+    generate::{GenerateError, Outcome, Overwrite},
+    Forge,
+};
+
+/// A struct that copies a file or a directory tree.
+///
+/// `Copy` copies the file at its source path to the path it is forged into, creating any
+/// missing parent directories. If the source is a directory, its whole tree is copied,
+/// and the destination directory is created if needed.
+///
+/// The source can also be a directory that was embedded into the binary with
+/// [`embed_dir!`](crate::embed_dir), in which case copied files keep the permissions they
+/// were embedded with.
+///
+/// Symbolic links to files are copied as regular files with the linked contents. Links to
+/// directories are not followed, so a link that points back into the tree can't make the
+/// copy run forever; the copy fails on them instead.
+///
+/// Files that already exist at the destination are handled by an [`Overwrite`] policy,
+/// exactly as [`Generate`](crate::generate::Generate) handles them. By default the copy
+/// fails rather than replacing anything.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, copy::Copy, generate::Overwrite};
+///
+/// // Copy a static asset into a new project
+/// Copy::new("./templates/favicon.ico")
+///     .forge("./my-app/static/favicon.ico")
+///     .unwrap();
+///
+/// // Copy a whole directory, keeping files the user already has
+/// Copy::new("./templates/migrations")
+///     .policy(Overwrite::Skip)
+///     .forge("./my-app/migrations")
+///     .unwrap();
+/// ```
+pub struct Copy {
+    /// The file or directory to copy
+    from: Source,
+    /// What to do when a destination file already exists
+    policy: Overwrite,
+}
+
+/// Errors that can occur during copy operations.
+#[derive(Error, Debug)]
+pub enum CopyError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while copying: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// Error that occurred because a file could not be merged without a recorded base.
+    #[error("cannot merge {} because no previously copied version was recorded", .0.display())]
+    MissingBase(PathBuf),
+}
+
+impl From<GenerateError> for CopyError {
+    fn from(error: GenerateError) -> Self {
+        match error {
+            GenerateError::StdIo(e) => CopyError::StdIo(e),
+            GenerateError::MissingBase(path) => CopyError::MissingBase(path),
+            // Copying never renders a template
+            GenerateError::Template(e) => CopyError::StdIo(std::io::Error::other(e.to_string())),
+        }
+    }
+}
+
+impl Copy {
+    /// Creates a new `Copy` operation with the given source path.
+    ///
+    /// # Parameters
+    ///
+    /// * `from` - The file or directory to copy, either a path or an embedded [`Source`]
+    ///
+    /// # Returns
+    ///
+    /// A new `Copy` instance that fails if a destination file already exists.
+    pub fn new(from: impl Into<Source>) -> Self {
+        Self {
+            from: from.into(),
+            policy: Overwrite::default(),
+        }
+    }
+
+    /// Sets what happens when a destination file already exists.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy` - The [`Overwrite`] policy to apply to each copied file
+    ///
+    /// # Returns
+    ///
+    /// The `Copy` instance with the policy applied.
+    pub fn policy(mut self, policy: Overwrite) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Copies the file or directory at `from`, read from `source`, to `to` in `fs`.
+    fn copy(
+        &self,
+        source: &dyn FileSystem,
+        fs: &dyn FileSystem,
+        from: &Path,
+        to: &Path,
+    ) -> Result<(), CopyError> {
+        let metadata = source.symlink_metadata(from)?;
+        // Following links to directories could copy the same tree forever
+        if metadata.is_symlink() && source.metadata(from)?.is_dir() {
+            return Err(CopyError::StdIo(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot copy symbolic link to a directory: {}",
+                    from.display()
+                ),
+            )));
+        }
+        if metadata.is_dir() {
+            fs.create_dir_all(to)?;
+            for entry in source.read_dir(from)? {
+                let name = entry.file_name().expect("directory entries have names");
+                self.copy(source, fs, &entry, &to.join(name))?;
+            }
+            return Ok(());
+        }
+
+        let contents = source.read(from)?;
+        if let Some(parent) = to.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        let outcome = self.policy.apply(fs, to, &contents)?;
+        if let Some(mode) = self.from.mode(from) {
+            if !matches!(outcome, Outcome::Skipped | Outcome::Unchanged) {
+                fs.set_mode(to, mode)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Forge for Copy {
+    type Error = CopyError;
+
+    /// Copies the file or directory tree to the destination path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem in which the files are copied
+    /// * `into` - Destination path of the copy
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), CopyError>` - Ok if successful, or an error if:
+    ///   - The source doesn't exist
+    ///   - The source is a directory and the destination is inside it
+    ///   - A destination file exists and the policy does not allow replacing it
+    ///   - File permissions don't allow the operation
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let to = into.as_ref();
+        let from = self.from.path();
+        if let Source::Path(_) = self.from {
+            if normalize(to).starts_with(normalize(from)) && fs.metadata(from)?.is_dir() {
+                return Err(CopyError::StdIo(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "cannot copy {} into itself at {}",
+                        from.display(),
+                        to.display()
+                    ),
+                )));
+            }
+        }
+        self.copy(self.from.filesystem(fs), fs, from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_copy_file_creates_parent_directories() {
+        let fs = Memory::new().with_file("templates/a.txt", "content");
+
+        Copy::new("templates/a.txt")
+            .forge_in(&fs, "out/nested/a.txt")
+            .unwrap();
+
+        // The source is left in place
+        assert_eq!(fs.read(Path::new("templates/a.txt")).unwrap(), b"content");
+        assert_eq!(fs.read(Path::new("out/nested/a.txt")).unwrap(), b"content");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_copy_directory_recursively() {
+        let fs = Memory::new()
+            .with_file("templates/a.txt", "a")
+            .with_file("templates/sub/b.txt", "b")
+            .with_dir("templates/empty");
+
+        Copy::new("templates").forge_in(&fs, "out").unwrap();
+
+        assert_eq!(fs.read(Path::new("out/a.txt")).unwrap(), b"a");
+        assert_eq!(fs.read(Path::new("out/sub/b.txt")).unwrap(), b"b");
+        assert!(fs.metadata(Path::new("out/empty")).unwrap().is_dir());
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_copy_applies_overwrite_policy() {
+        let fs = Memory::new()
+            .with_file("templates/a.txt", "new")
+            .with_file("out/a.txt", "existing");
+
+        // Fails by default
+        let result = Copy::new("templates").forge_in(&fs, "out");
+        assert!(
+            matches!(result, Err(CopyError::StdIo(e)) if e.kind() == std::io::ErrorKind::AlreadyExists)
+        );
+
+        Copy::new("templates")
+            .policy(Overwrite::Skip)
+            .forge_in(&fs, "out")
+            .unwrap();
+        assert_eq!(fs.read(Path::new("out/a.txt")).unwrap(), b"existing");
+
+        Copy::new("templates")
+            .policy(Overwrite::Force)
+            .forge_in(&fs, "out")
+            .unwrap();
+        assert_eq!(fs.read(Path::new("out/a.txt")).unwrap(), b"new");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_copy_from_embedded_directory() {
+        use crate::embed::{EmbeddedDir, EmbeddedFile};
+
+        static TEMPLATES: EmbeddedDir = EmbeddedDir::new(
+            &["static"],
+            &[
+                EmbeddedFile::new("README.md", b"readme", 0o644),
+                EmbeddedFile::new("static/app.css", b"body {}", 0o644),
+            ],
+        );
+        let fs = Memory::new();
+
+        Copy::new(&TEMPLATES).forge_in(&fs, "app").unwrap();
+        assert_eq!(fs.read(Path::new("app/README.md")).unwrap(), b"readme");
+        assert_eq!(
+            fs.read(Path::new("app/static/app.css")).unwrap(),
+            b"body {}"
+        );
+
+        // A path inside the embedded directory can be copied on its own
+        Copy::new(TEMPLATES.at("static/app.css"))
+            .forge_in(&fs, "other/app.css")
+            .unwrap();
+        assert_eq!(fs.read(Path::new("other/app.css")).unwrap(), b"body {}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_does_not_follow_links_to_directories() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let templates = temp_dir.path().join("templates");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(temp_dir.path().join("shared.txt"), "shared").unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("shared.txt"), templates.join("a.txt"))
+            .unwrap();
+
+        // Links to files are copied as regular files
+        let out = temp_dir.path().join("out");
+        Copy::new(&templates).forge(&out).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(out.join("a.txt")).unwrap(),
+            "shared"
+        );
+        assert!(!out.join("a.txt").is_symlink());
+
+        // A link back to its own directory fails instead of copying forever
+        std::os::unix::fs::symlink(&templates, templates.join("loop")).unwrap();
+        let result = Copy::new(&templates).forge(temp_dir.path().join("again"));
+        assert!(
+            matches!(result, Err(CopyError::StdIo(e)) if e.kind() == std::io::ErrorKind::InvalidInput)
+        );
+    }
+}
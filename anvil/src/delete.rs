@@ -0,0 +1,267 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+#[cfg(feature = "manifest")]
+use crate::manifest::Manifest;
+use crate::{fs::FileSystem, Forge};
+
+/// A struct that deletes a file or a directory tree.
+///
+/// `Delete` removes the file at the path it is forged into. If the path is a directory,
+/// the directory and everything inside it are removed. Symbolic links are removed
+/// themselves; whatever they point to is left alone, even if it is a directory.
+///
+/// With the `manifest` feature enabled, [`unless_modified`](Delete::unless_modified) makes
+/// the deletion conditional: every file is checked against a [`Manifest`] first, and
+/// nothing is deleted if any file was edited since it was generated or was never generated
+/// at all.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, delete::Delete};
+///
+/// // Remove a generated file
+/// match Delete::new().forge("./src/controllers/usr.rs") {
+///     Ok(_) => println!("File deleted"),
+///     Err(e) => eprintln!("Failed to delete file: {}", e),
+/// }
+/// ```
+#[derive(Default)]
+pub struct Delete {
+    /// The manifest that files must match before they are deleted
+    #[cfg(feature = "manifest")]
+    manifest: Option<Manifest>,
+}
+
+/// Errors that can occur during delete operations.
+#[derive(Error, Debug)]
+pub enum DeleteError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while deleting: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// A file was changed after it was generated, so it was not deleted.
+    #[error("{} was modified after it was generated", path.display())]
+    Modified {
+        /// The file that was modified
+        path: PathBuf,
+    },
+
+    /// A file was not recorded in the manifest, so it was not deleted.
+    #[error("{} was not generated", path.display())]
+    Untracked {
+        /// The file that is missing from the manifest
+        path: PathBuf,
+    },
+}
+
+impl Delete {
+    /// Creates a new `Delete` operation.
+    ///
+    /// # Returns
+    ///
+    /// A new `Delete` instance that removes whatever it is forged into.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only deletes files that are unchanged since they were generated.
+    ///
+    /// Every file that would be deleted is looked up in `manifest`. If any file is missing
+    /// from it or no longer has the recorded contents, the operation fails with
+    /// [`DeleteError::Untracked`] or [`DeleteError::Modified`] before anything is deleted.
+    ///
+    /// This method is only available with the `manifest` feature enabled.
+    ///
+    /// # Parameters
+    ///
+    /// * `manifest` - The record of generated files to check against
+    ///
+    /// # Returns
+    ///
+    /// The `Delete` instance with the check applied.
+    #[cfg(feature = "manifest")]
+    pub fn unless_modified(mut self, manifest: Manifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Checks every file that would be deleted at `path`.
+    fn check_tree(&self, fs: &dyn FileSystem, path: &Path) -> Result<(), DeleteError> {
+        if fs.symlink_metadata(path)?.is_dir() {
+            for entry in fs.read_dir(path)? {
+                self.check_tree(fs, &entry)?;
+            }
+            return Ok(());
+        }
+        self.check(fs, path)
+    }
+
+    #[cfg(feature = "manifest")]
+    fn check(&self, fs: &dyn FileSystem, path: &Path) -> Result<(), DeleteError> {
+        let Some(manifest) = &self.manifest else {
+            return Ok(());
+        };
+        if manifest.get(path).is_none() {
+            return Err(DeleteError::Untracked {
+                path: path.to_path_buf(),
+            });
+        }
+        if manifest.is_modified(fs, path) {
+            return Err(DeleteError::Modified {
+                path: path.to_path_buf(),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "manifest"))]
+    fn check(&self, _fs: &dyn FileSystem, _path: &Path) -> Result<(), DeleteError> {
+        Ok(())
+    }
+
+    /// Removes the file or directory tree at `path`.
+    ///
+    /// Symbolic links are removed themselves, never what they point to.
+    fn remove(fs: &dyn FileSystem, path: &Path) -> Result<(), DeleteError> {
+        if fs.symlink_metadata(path)?.is_dir() {
+            for entry in fs.read_dir(path)? {
+                Self::remove(fs, &entry)?;
+            }
+            fs.remove_dir(path)?;
+        } else {
+            fs.remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Forge for Delete {
+    type Error = DeleteError;
+
+    /// Deletes the file or directory tree at the given path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem in which the files are deleted
+    /// * `into` - The file or directory to delete
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), DeleteError>` - Ok if successful, or an error if:
+    ///   - Nothing exists at the path
+    ///   - A file was modified or never generated, when checking against a manifest
+    ///   - File permissions don't allow the operation
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref();
+        // Check every file before deleting any of them
+        self.check_tree(fs, path)?;
+        Self::remove(fs, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+    #[cfg(all(feature = "memory", feature = "manifest"))]
+    use crate::{generate::Generate, Anvil};
+
+    // Mock implementation for Anvil
+    #[cfg(all(feature = "memory", feature = "manifest"))]
+    struct MockAnvil {
+        content: String,
+    }
+
+    #[cfg(all(feature = "memory", feature = "manifest"))]
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_delete_removes_file_and_directory_tree() {
+        let fs = Memory::new()
+            .with_file("a.txt", "a")
+            .with_file("dir/b.txt", "b")
+            .with_file("dir/sub/c.txt", "c")
+            .with_dir("dir/empty");
+
+        Delete::new().forge_in(&fs, "a.txt").unwrap();
+        Delete::new().forge_in(&fs, "dir").unwrap();
+
+        assert!(!fs.exists(Path::new("a.txt")));
+        assert!(!fs.exists(Path::new("dir")));
+
+        // Deleting something that doesn't exist fails
+        let result = Delete::new().forge_in(&fs, "a.txt");
+        assert!(
+            matches!(result, Err(DeleteError::StdIo(e)) if e.kind() == std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[cfg(all(feature = "memory", feature = "manifest"))]
+    #[test]
+    fn test_delete_unless_modified_checks_every_file_first() {
+        let fs = Memory::new().with_file("src/hand_written.rs", "fn main() {}");
+        let recorder = Manifest::new().recorder(&fs, "scaffold");
+        for (path, content) in [("src/a.rs", "a"), ("src/b.rs", "b")] {
+            Generate::new(MockAnvil {
+                content: content.to_string(),
+            })
+            .forge_in(&recorder, path)
+            .unwrap();
+        }
+        let manifest = recorder.finish();
+
+        // A file that was never generated stops the whole deletion
+        let result = Delete::new()
+            .unless_modified(manifest.clone())
+            .forge_in(&fs, "src");
+        assert!(matches!(result, Err(DeleteError::Untracked { .. })));
+        assert!(fs.exists(Path::new("src/a.rs")));
+
+        // So does a generated file that was edited
+        fs.write(Path::new("src/b.rs"), b"edited").unwrap();
+        let result = Delete::new()
+            .unless_modified(manifest.clone())
+            .forge_in(&fs, "src/b.rs");
+        assert!(matches!(result, Err(DeleteError::Modified { .. })));
+
+        Delete::new()
+            .unless_modified(manifest)
+            .forge_in(&fs, "src/a.rs")
+            .unwrap();
+        assert!(!fs.exists(Path::new("src/a.rs")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_removes_links_without_following_them() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let outside = temp_dir.path().join("outside");
+        let tree = temp_dir.path().join("tree");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("keep.txt"), "keep").unwrap();
+        std::fs::create_dir_all(&tree).unwrap();
+        std::fs::write(tree.join("a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink(&outside, tree.join("linked")).unwrap();
+
+        Delete::new().forge(&tree).unwrap();
+
+        // The tree is gone, but the directory the link pointed to is untouched
+        assert!(!tree.exists());
+        assert_eq!(
+            std::fs::read_to_string(outside.join("keep.txt")).unwrap(),
+            "keep"
+        );
+    }
+}
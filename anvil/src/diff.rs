@@ -0,0 +1,418 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use similar::{ChangeTag, TextDiff};
+
+use crate::{
+    fs::{Disk, FileSystem},
+    plan::{Plan, Step},
+    Forge,
+};
+
+/// The number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// The change an operation would make to a single file.
+///
+/// A missing `before` means the file would be created, and a missing `after` means it would
+/// be removed. Moves show up as the removal of the source and the creation of the destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// The file that would change
+    path: PathBuf,
+    /// The current contents of the file, if it exists
+    before: Option<String>,
+    /// The contents of the file after the operation, if it still exists
+    after: Option<String>,
+}
+
+impl FileDiff {
+    /// Returns the path of the file that would change.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the current contents of the file, or `None` if it would be created.
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Returns the contents of the file after the operation, or `None` if it would be removed.
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+
+    /// Returns the `---` and `+++` header names for the diff.
+    fn headers(&self) -> (String, String) {
+        let path = self.path.display();
+        let old = match self.before {
+            Some(_) => format!("a/{path}"),
+            None => "/dev/null".to_string(),
+        };
+        let new = match self.after {
+            Some(_) => format!("b/{path}"),
+            None => "/dev/null".to_string(),
+        };
+        (old, new)
+    }
+
+    /// Writes the diff in unified format, with ANSI colours if `colored` is set.
+    fn write(&self, writer: &mut dyn Write, colored: bool) -> io::Result<()> {
+        let paint = |code: &str, text: &str| {
+            if colored {
+                format!("\x1b[{code}m{text}\x1b[0m")
+            } else {
+                text.to_string()
+            }
+        };
+
+        let before = self.before.as_deref().unwrap_or_default();
+        let after = self.after.as_deref().unwrap_or_default();
+        let diff = TextDiff::from_lines(before, after);
+        let (old, new) = self.headers();
+
+        writeln!(writer, "{}", paint("1", &format!("--- {old}")))?;
+        writeln!(writer, "{}", paint("1", &format!("+++ {new}")))?;
+        for hunk in diff.unified_diff().context_radius(CONTEXT).iter_hunks() {
+            writeln!(writer, "{}", paint("36", &hunk.header().to_string()))?;
+            for change in hunk.iter_changes() {
+                let (sign, code) = match change.tag() {
+                    ChangeTag::Delete => ("-", "31"),
+                    ChangeTag::Insert => ("+", "32"),
+                    ChangeTag::Equal => (" ", ""),
+                };
+                let line = format!("{sign}{}", change.value().trim_end_matches('\n'));
+                if code.is_empty() {
+                    writeln!(writer, "{line}")?;
+                } else {
+                    writeln!(writer, "{}", paint(code, &line))?;
+                }
+                if change.missing_newline() {
+                    writeln!(writer, "\\ No newline at end of file")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = Vec::new();
+        self.write(&mut buffer, false).map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buffer))
+    }
+}
+
+/// The changes an operation would make to every file it touches.
+///
+/// A `Diff` is computed by running the operation against a [`Plan`], so nothing is written,
+/// and comparing each file the plan touched with its current contents. Any [`Forge`] can be
+/// diffed, including compositions of several operations.
+///
+/// Displaying a `Diff` prints it in unified format; [`write_colored`](Diff::write_colored)
+/// prints it with terminal colours.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{diff, generate::Generate};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content\n")
+/// #     }
+/// # }
+///
+/// let diff = diff::diff(&Generate::new(SimpleTemplate), "./src/new_file.rs").unwrap();
+///
+/// // Print the diff for review before applying it
+/// diff.write_colored(&mut std::io::stdout()).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// The changed files, ordered by path
+    files: Vec<FileDiff>,
+}
+
+impl Diff {
+    /// Computes the changes recorded by a plan, relative to the filesystem it reads from.
+    ///
+    /// # Parameters
+    ///
+    /// * `plan` - A plan that one or more operations have been run against
+    ///
+    /// # Returns
+    ///
+    /// A `Diff` with an entry for every file whose contents would change.
+    pub fn from_plan(plan: &Plan<'_>) -> Self {
+        let mut paths = BTreeSet::new();
+        for step in plan.steps() {
+            match step {
+                Step::Create { path, .. }
+                | Step::Append { path, .. }
+                | Step::Overwrite { path, .. }
+                | Step::Remove { path } => {
+                    paths.insert(path);
+                }
+                Step::Rename { from, to } => {
+                    // A moved directory changes the path of every file beneath it, which
+                    // may only exist before or only after the plan runs
+                    let mut moved = Vec::new();
+                    files_under(plan.base(), &from, Path::new(""), &mut moved);
+                    files_under(plan, &to, Path::new(""), &mut moved);
+                    for relative in moved {
+                        paths.insert(from.join(&relative));
+                        paths.insert(to.join(relative));
+                    }
+                    paths.insert(from);
+                    paths.insert(to);
+                }
+                Step::CreateDir { .. } | Step::RemoveDir { .. } | Step::Fallback { .. } => {}
+            }
+        }
+
+        let read = |fs: &dyn FileSystem, path: &Path| {
+            fs.metadata(path)
+                .is_ok_and(|m| m.is_file())
+                .then(|| fs.read(path).ok())
+                .flatten()
+                .map(|contents| String::from_utf8_lossy(&contents).into_owned())
+        };
+
+        let files = paths
+            .into_iter()
+            .filter_map(|path| {
+                let before = read(plan.base(), &path);
+                let after = read(plan, &path);
+                (before != after).then_some(FileDiff {
+                    path,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    /// Returns the changed files, ordered by path.
+    pub fn files(&self) -> &[FileDiff] {
+        &self.files
+    }
+
+    /// Returns `true` if no file would change.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Writes the diff in unified format, coloured with ANSI escape codes for terminals.
+    ///
+    /// # Parameters
+    ///
+    /// * `writer` - Where to write the diff, such as standard output
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<()>` - Ok if the diff was written, or the error from the writer.
+    pub fn write_colored(&self, writer: &mut impl Write) -> io::Result<()> {
+        for file in &self.files {
+            file.write(writer, true)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            write!(f, "{file}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the paths of the files beneath the directory `dir`, relative to it and
+/// prefixed with `relative`. Symbolic links are listed as files and never followed.
+fn files_under(fs: &dyn FileSystem, dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    let is_dir = |path: &Path| fs.symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    if !is_dir(dir) {
+        return;
+    }
+    for entry in fs.read_dir(dir).unwrap_or_default() {
+        let Some(name) = entry.file_name() else {
+            continue;
+        };
+        let relative = relative.join(name);
+        if is_dir(&entry) {
+            files_under(fs, &entry, &relative, files);
+        } else {
+            files.push(relative);
+        }
+    }
+}
+
+/// Computes the changes an operation would make on the real disk, without making them.
+///
+/// # Parameters
+///
+/// * `forge` - The operation to diff
+/// * `into` - The path the operation would be performed on
+///
+/// # Returns
+///
+/// * `Result<Diff, F::Error>` - The changes the operation would make, or the error it
+///   would fail with.
+pub fn diff<F: Forge>(forge: &F, into: impl AsRef<Path>) -> Result<Diff, F::Error> {
+    diff_in(forge, &Disk, into)
+}
+
+/// Computes the changes an operation would make to the given filesystem, without making them.
+///
+/// # Parameters
+///
+/// * `forge` - The operation to diff
+/// * `fs` - The filesystem whose current state the diff is relative to
+/// * `into` - The path the operation would be performed on
+///
+/// # Returns
+///
+/// * `Result<Diff, F::Error>` - The changes the operation would make, or the error it
+///   would fail with.
+pub fn diff_in<F: Forge>(
+    forge: &F,
+    fs: &dyn FileSystem,
+    into: impl AsRef<Path>,
+) -> Result<Diff, F::Error> {
+    let plan = Plan::over(fs);
+    forge.forge_in(&plan, into)?;
+    Ok(Diff::from_plan(&plan))
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append, fs::Memory, generate::Generate, mover::Move, transform::Transform, Anvil,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_of_generated_file() {
+        let fs = Memory::new();
+
+        let diff = diff_in(&Generate::new(mock("one\ntwo\n")), &fs, "src/lib.rs").unwrap();
+
+        assert_eq!(
+            diff.to_string(),
+            "--- /dev/null\n+++ b/src/lib.rs\n@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+        // Nothing was written
+        assert!(fs.files().is_empty());
+    }
+
+    #[test]
+    fn test_diff_of_append_and_transform() {
+        let fs = Memory::new().with_file("mod.rs", "pub mod a;\n");
+
+        let diff = diff_in(&Append::new(mock("pub mod b;\n")), &fs, "mod.rs").unwrap();
+        assert_eq!(
+            diff.to_string(),
+            "--- a/mod.rs\n+++ b/mod.rs\n@@ -1 +1,2 @@\n pub mod a;\n+pub mod b;\n"
+        );
+
+        let transform = Transform::new(|content| Ok(content.replace("a;", "alpha;")));
+        let diff = diff_in(&transform, &fs, "mod.rs").unwrap();
+        assert_eq!(
+            diff.to_string(),
+            "--- a/mod.rs\n+++ b/mod.rs\n@@ -1 +1 @@\n-pub mod a;\n+pub mod alpha;\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_of_move_shows_removal_and_creation() {
+        let fs = Memory::new().with_file("old.txt", "content\n");
+
+        let diff = diff_in(&Move::new("old.txt"), &fs, "new.txt").unwrap();
+
+        let paths: Vec<_> = diff.files().iter().map(|file| file.path()).collect();
+        assert_eq!(paths, vec![Path::new("new.txt"), Path::new("old.txt")]);
+        assert_eq!(diff.files()[0].before(), None);
+        assert_eq!(diff.files()[1].after(), None);
+    }
+
+    #[test]
+    fn test_diff_of_moved_directory_lists_every_file() {
+        let fs = Memory::new()
+            .with_file("old/a.txt", "a\n")
+            .with_file("old/nested/b.txt", "b\n");
+
+        let diff = diff_in(&Move::new("old"), &fs, "new").unwrap();
+
+        let paths: Vec<_> = diff.files().iter().map(|file| file.path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("new/a.txt"),
+                Path::new("new/nested/b.txt"),
+                Path::new("old/a.txt"),
+                Path::new("old/nested/b.txt"),
+            ]
+        );
+        assert_eq!(diff.files()[1].after(), Some("b\n"));
+        assert_eq!(diff.files()[3].before(), Some("b\n"));
+    }
+
+    #[test]
+    fn test_diff_write_colored_highlights_changes() {
+        let fs = Memory::new().with_file("file.txt", "same\nold");
+        let transform = Transform::new(|content| Ok(content.replace("old", "new\n")));
+
+        let diff = diff_in(&transform, &fs, "file.txt").unwrap();
+        let mut output = Vec::new();
+        diff.write_colored(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("\x1b[1m--- a/file.txt\x1b[0m"));
+        assert!(output.contains("\x1b[31m-old\x1b[0m\n\\ No newline at end of file\n"));
+        assert!(output.contains("\x1b[32m+new\x1b[0m"));
+        assert!(output.contains("\n same\n"));
+    }
+
+    #[test]
+    fn test_diff_is_empty_when_nothing_changes() {
+        let fs = Memory::new().with_file("file.txt", "content");
+        let transform = Transform::new(Ok);
+
+        let diff = diff_in(&transform, &fs, "file.txt").unwrap();
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+}
@@ -0,0 +1,244 @@
+use std::{error::Error, fmt, io::Write, path::Path};
+
+use crate::{fs::FileSystem, Anvil, Forge};
+
+/// An error from an operation or template whose type was erased.
+///
+/// `DynError` is the error type of [`DynForge`] and [`DynAnvil`]. It displays as, and has
+/// the same [`source`](Error::source) as, the error it wraps, which can be recovered with
+/// [`downcast_ref`](DynError::downcast_ref). The wrapped error is `Send + Sync`, so a
+/// `DynError` can be returned from another thread or boxed into any error type.
+#[derive(Debug)]
+pub struct DynError(Box<dyn Error + Send + Sync>);
+
+impl DynError {
+    /// Wraps an error of any type.
+    ///
+    /// # Parameters
+    ///
+    /// * `error` - The error to wrap
+    ///
+    /// # Returns
+    ///
+    /// A new `DynError` instance.
+    pub fn new(error: impl Error + Send + Sync + 'static) -> Self {
+        Self(Box::new(error))
+    }
+
+    /// Returns the wrapped error as `E`, if it is one.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+
+    /// Returns the wrapped error.
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync> {
+        self.0
+    }
+}
+
+impl fmt::Display for DynError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for DynError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for DynError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        Self(error)
+    }
+}
+
+/// An object-safe version of [`Forge`].
+///
+/// [`Forge`] can't be used as a trait object, because its methods are generic over the
+/// path and its error type differs between operations. `DynForge` takes a plain [`Path`]
+/// and returns a [`DynError`] instead, and is implemented for every [`Forge`], so
+/// operations of different types can be collected in a `Vec<Box<dyn DynForge>>`. Boxed
+/// `DynForge`s implement [`Forge`] themselves and work with every combinator, such as
+/// [`Sequence`](crate::sequence::Sequence).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, dynamic::DynForge, mkdir::Mkdir, sequence::Sequence, touch::Touch};
+///
+/// let with_docs = std::env::args().any(|arg| arg == "--docs");
+///
+/// // Choose the steps at runtime
+/// let mut steps: Vec<Box<dyn DynForge>> = vec![Mkdir::new().at("./src").boxed()];
+/// if with_docs {
+///     steps.push(Mkdir::new().at("./docs").boxed());
+///     steps.push(Touch::new().at("./docs/index.md").boxed());
+/// }
+///
+/// steps.into_iter().collect::<Sequence<_>>().forge(".").unwrap();
+/// ```
+pub trait DynForge {
+    /// Performs the operation against the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The [`FileSystem`] that all file access should go through
+    /// * `into` - Path where the operation should be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), DynError>` - Ok if the operation was successful, or its error.
+    fn dyn_forge_in(&self, fs: &dyn FileSystem, into: &Path) -> Result<(), DynError>;
+}
+
+impl<F: Forge> DynForge for F
+where
+    F::Error: Send + Sync + 'static,
+{
+    fn dyn_forge_in(&self, fs: &dyn FileSystem, into: &Path) -> Result<(), DynError> {
+        self.forge_in(fs, into).map_err(DynError::new)
+    }
+}
+
+impl Forge for dyn DynForge + '_ {
+    type Error = DynError;
+
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.dyn_forge_in(fs, into.as_ref())
+    }
+}
+
+impl Forge for Box<dyn DynForge + '_> {
+    type Error = DynError;
+
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        // The box is itself a `DynForge`, so dispatch on its contents to avoid recursing
+        (**self).dyn_forge_in(fs, into.as_ref())
+    }
+}
+
+/// An object-safe version of [`Anvil`].
+///
+/// [`Anvil`] can't be used as a trait object, because it is generic over the writer.
+/// `DynAnvil` writes to a `dyn Write` and returns a [`DynError`] instead, and is
+/// implemented for every [`Anvil`]. Boxed `DynAnvil`s implement [`Anvil`] themselves, so a
+/// template chosen at runtime can be passed to [`Generate`](crate::generate::Generate) or
+/// any other operation that takes one.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, dynamic::DynAnvil, generate::Generate};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Template(&'static str);
+/// # impl Anvil for Template {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(self.0.as_bytes())
+/// #     }
+/// # }
+///
+/// let license: Box<dyn DynAnvil> = match std::env::args().nth(1).as_deref() {
+///     Some("apache") => Box::new(Template("Apache License 2.0")),
+///     _ => Box::new(Template("MIT License")),
+/// };
+///
+/// Generate::new(license).forge("./LICENSE").unwrap();
+/// ```
+pub trait DynAnvil {
+    /// Renders the template to the provided writer.
+    ///
+    /// # Parameters
+    ///
+    /// * `writer` - The destination for the rendered content
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), DynError>` - Ok if rendering was successful, or its error.
+    fn dyn_anvil(&self, writer: &mut dyn Write) -> Result<(), DynError>;
+}
+
+impl<A: Anvil> DynAnvil for A {
+    fn dyn_anvil(&self, mut writer: &mut dyn Write) -> Result<(), DynError> {
+        self.anvil(&mut writer).map_err(DynError::new)
+    }
+}
+
+impl Anvil for dyn DynAnvil + '_ {
+    type Error = DynError;
+
+    fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+        self.dyn_anvil(writer)
+    }
+}
+
+impl Anvil for Box<dyn DynAnvil + '_> {
+    type Error = DynError;
+
+    fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+        // The box is itself a `DynAnvil`, so dispatch on its contents to avoid recursing
+        (**self).dyn_anvil(writer)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{append::Append, fs::Memory, generate::Generate, sequence::Sequence, ForgeExt};
+
+    // Mock implementation for Anvil
+    struct MockAnvil(&'static str);
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.0.as_bytes())
+        }
+    }
+
+    #[test]
+    fn test_boxed_operations_run_as_a_sequence() {
+        let fs = Memory::new();
+        let header: Box<dyn DynAnvil> = Box::new(MockAnvil("# Notes\n"));
+
+        let steps: Vec<Box<dyn DynForge>> = vec![
+            Generate::new(header).boxed(),
+            Append::new(MockAnvil("- first\n")).boxed(),
+        ];
+        let plan: Sequence<_> = steps.into_iter().collect();
+        plan.forge_in(&fs, "notes.md").unwrap();
+        assert_eq!(
+            fs.read_to_string(Path::new("notes.md")).unwrap(),
+            "# Notes\n- first\n"
+        );
+
+        // The original error can still be inspected
+        let error = Generate::new(MockAnvil(""))
+            .boxed()
+            .forge_in(&fs, "notes.md")
+            .unwrap_err();
+        let error = error.downcast_ref::<crate::generate::GenerateError>();
+        assert!(matches!(
+            error,
+            Some(crate::generate::GenerateError::StdIo(_))
+        ));
+    }
+
+    #[test]
+    fn test_dyn_errors_can_be_sent_across_threads() {
+        let fs = Memory::new();
+        fs.write(Path::new("notes.md"), b"# Notes\n").unwrap();
+        let error = Generate::new(MockAnvil(""))
+            .boxed()
+            .forge_in(&fs, "notes.md")
+            .unwrap_err();
+
+        let error: Box<dyn Error + Send + Sync> =
+            std::thread::spawn(move || error.into()).join().unwrap();
+        assert!(error.to_string().contains("notes.md"));
+    }
+}
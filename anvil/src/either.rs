@@ -1,5 +1,6 @@
-use crate::Forge;
-use std::path::Path;
+use crate::{fs::FileSystem, Forge};
+use std::{error::Error, io::ErrorKind, path::Path};
+use thiserror::Error;
 
 /// A struct that implements a fallback mechanism between two operations.
 ///
@@ -8,6 +9,9 @@ use std::path::Path;
 /// the second operation. This provides a resilient way to handle operations
 /// that might fail, giving an alternative approach.
 ///
+/// The error of the first operation is discarded. Use [`Fallback`] to keep it, or to only
+/// fall back on the errors that are expected.
+///
 /// # Type Parameters
 ///
 /// * `L` - The primary (left) operation type that implements [`Forge`]
@@ -16,7 +20,7 @@ use std::path::Path;
 /// # Examples
 ///
 /// ```
-/// use anvil::{Forge, either::{either, Either}};
+/// use anvil::{Forge, either::{either, Either}, fs::FileSystem};
 /// use std::path::Path;
 ///
 /// // Assuming we have two forge operations:
@@ -25,7 +29,7 @@ use std::path::Path;
 ///
 /// impl Forge for PrimaryOperation {
 ///     type Error = std::io::Error;
-///     fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+///     fn forge_in(&self, _fs: &dyn FileSystem, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
 ///         // Try primary operation...
 ///         Err(std::io::Error::new(std::io::ErrorKind::Other, "Primary operation failed"))
 ///     }
@@ -33,7 +37,7 @@ use std::path::Path;
 ///
 /// impl Forge for FallbackOperation {
 ///     type Error = std::io::Error;
-///     fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+///     fn forge_in(&self, _fs: &dyn FileSystem, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
 ///         // Fallback operation succeeds
 ///         Ok(())
 ///     }
@@ -60,20 +64,26 @@ impl<L: Forge, R: Forge> Forge for Either<L, R> {
     /// Attempts the left operation first, then falls back to the right operation if needed.
     ///
     /// This method:
-    /// 1. Tries to execute the left operation
-    /// 2. If the left operation fails, tries the right operation
+    /// 1. Takes a [`Savepoint`](crate::fs::Savepoint) and tries to execute the left operation
+    /// 2. If the left operation fails, rolls the filesystem back to the savepoint and tries
+    ///    the right operation
     /// 3. Returns the result of the successful operation, or the error from the right operation
     ///
     /// # Parameters
     ///
+    /// * `fs` - The filesystem both operations run against
     /// * `into` - Path where the operation will be performed
     ///
     /// # Returns
     ///
     /// * `Result<(), R::Error>` - Ok if either operation succeeds, or the error from the right operation
     ///   if both fail. Note that the error type is the one from the right operation.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
-        self.left.forge(&into).or_else(|_| self.right.forge(&into))
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let savepoint = fs.savepoint();
+        self.left.forge_in(fs, &into).or_else(|error| {
+            fs.rollback_to(savepoint, &error);
+            self.right.forge_in(fs, &into)
+        })
     }
 }
 
@@ -109,7 +119,7 @@ impl<L: Forge, R: Forge> Either<L, R> {
 /// # Examples
 ///
 /// ```
-/// use anvil::{Forge, either::either};
+/// use anvil::{Forge, either::either, fs::FileSystem};
 /// use std::path::Path;
 ///
 /// // Two hypothetical operations
@@ -119,14 +129,14 @@ impl<L: Forge, R: Forge> Either<L, R> {
 /// // Implement Forge for the operations
 /// impl Forge for Operation1 {
 ///     type Error = std::io::Error;
-///     fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+///     fn forge_in(&self, _fs: &dyn FileSystem, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
 ///         Ok(())
 ///     }
 /// }
 ///
 /// impl Forge for Operation2 {
 ///     type Error = std::io::Error;
-///     fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+///     fn forge_in(&self, _fs: &dyn FileSystem, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
 ///         Ok(())
 ///     }
 /// }
@@ -139,13 +149,407 @@ pub fn either<L: Forge, R: Forge>(left: L, right: R) -> Either<L, R> {
     Either::new(left, right)
 }
 
+/// A check deciding whether an error should lead to the next alternative being tried.
+type Filter = Box<dyn Fn(&(dyn Error + 'static)) -> bool>;
+
+/// Returns `true` if `filter` is unset or accepts `error`.
+fn falls_back(filter: &Option<Filter>, error: &(dyn Error + 'static)) -> bool {
+    filter.as_ref().is_none_or(|filter| filter(error))
+}
+
+/// Builds a filter accepting errors caused by an I/O error of one of the given kinds.
+fn kinds_filter(kinds: impl IntoIterator<Item = ErrorKind>) -> Filter {
+    let kinds: Vec<ErrorKind> = kinds.into_iter().collect();
+    Box::new(move |error| io_error_kind(error).is_some_and(|kind| kinds.contains(&kind)))
+}
+
+/// Finds the kind of the I/O error that caused an error.
+///
+/// The error itself and each of its [`source`](Error::source)s are checked in turn, so the
+/// kind is found through wrappers such as [`GenerateError`](crate::generate::GenerateError).
+///
+/// # Parameters
+///
+/// * `error` - The error to inspect
+///
+/// # Returns
+///
+/// The kind of the first [`std::io::Error`] in the chain, or `None` if there is none.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::{either::io_error_kind, generate::GenerateError};
+/// use std::io::{Error, ErrorKind};
+///
+/// let error = GenerateError::from(Error::from(ErrorKind::AlreadyExists));
+/// assert_eq!(io_error_kind(&error), Some(ErrorKind::AlreadyExists));
+/// ```
+pub fn io_error_kind(error: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            return Some(io.kind());
+        }
+        current = error.source();
+    }
+    None
+}
+
+/// A struct that falls back to a second operation and keeps the errors of both.
+///
+/// `Fallback` works like [`Either`], with two differences:
+///
+/// * If both operations fail, the error says why each of them did.
+/// * The fallback can be limited to selected errors with [`on`](Fallback::on) or
+///   [`on_kinds`](Fallback::on_kinds). Any other error of the first operation, such as a
+///   permission problem or a broken template, is returned without trying the second.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, generate::Generate, inject::{Inject, Location}};
+/// use std::io::ErrorKind;
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Template(&'static str);
+/// # impl Anvil for Template {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(self.0.as_bytes())
+/// #     }
+/// # }
+///
+/// // Add to the file if it already exists, but report any other failure of `Generate`
+/// Generate::new(Template("// anvil:routes\n"))
+///     .fallback(Inject::new(Template("get!(\"/users\");\n"), Location::Anchor("routes".to_string())))
+///     .on_kinds([ErrorKind::AlreadyExists])
+///     .forge("./src/routes.rs")
+///     .unwrap();
+/// ```
+pub struct Fallback<L: Forge, R: Forge> {
+    /// The primary operation to try first
+    left: L,
+    /// The fallback operation to try if the first fails
+    right: R,
+    /// Which errors of the primary operation lead to the fallback, if not all of them
+    filter: Option<Filter>,
+}
+
+/// Errors that can occur when running a [`Fallback`] operation.
+#[derive(Error, Debug)]
+pub enum FallbackError<L: Error + 'static, R: Error + 'static> {
+    /// The primary operation failed with an error that doesn't lead to the fallback.
+    #[error("primary operation failed: {0}")]
+    Primary(#[source] L),
+
+    /// The primary operation failed, and so did the fallback.
+    #[error("primary operation failed: {primary}; fallback failed: {fallback}")]
+    Both {
+        /// The error of the primary operation
+        primary: L,
+        /// The error of the fallback operation
+        #[source]
+        fallback: R,
+    },
+}
+
+impl<L: Forge, R: Forge> Fallback<L, R> {
+    /// Creates a new `Fallback` operation that falls back on any error.
+    ///
+    /// # Parameters
+    ///
+    /// * `left` - The primary operation to try first
+    /// * `right` - The fallback operation to try if the first fails
+    ///
+    /// # Returns
+    ///
+    /// A new `Fallback` instance.
+    pub fn new(left: L, right: R) -> Self {
+        Self {
+            left,
+            right,
+            filter: None,
+        }
+    }
+
+    /// Only falls back on errors for which `filter` returns `true`.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - The check applied to the error of the primary operation
+    ///
+    /// # Returns
+    ///
+    /// The `Fallback` instance with the filter set.
+    pub fn on(mut self, filter: impl Fn(&(dyn Error + 'static)) -> bool + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only falls back on errors caused by an I/O error of one of the given kinds.
+    ///
+    /// See [`io_error_kind`] for how the kind of an error is found.
+    ///
+    /// # Parameters
+    ///
+    /// * `kinds` - The kinds of I/O error that lead to the fallback
+    ///
+    /// # Returns
+    ///
+    /// The `Fallback` instance with the filter set.
+    pub fn on_kinds(mut self, kinds: impl IntoIterator<Item = ErrorKind>) -> Self {
+        self.filter = Some(kinds_filter(kinds));
+        self
+    }
+}
+
+impl<L: Forge, R: Forge> Forge for Fallback<L, R>
+where
+    L::Error: 'static,
+    R::Error: 'static,
+{
+    type Error = FallbackError<L::Error, R::Error>;
+
+    /// Attempts the primary operation, then the fallback if the error allows it.
+    ///
+    /// As with [`Either`], the filesystem is rolled back to a
+    /// [`Savepoint`](crate::fs::Savepoint) before the fallback runs.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem both operations run against
+    /// * `into` - Path where the operation will be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), FallbackError<L::Error, R::Error>>` - Ok if either operation succeeds,
+    ///   or an error carrying the primary error and, if it ran, the fallback error.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        let savepoint = fs.savepoint();
+        let primary = match self.left.forge_in(fs, into) {
+            Ok(()) => return Ok(()),
+            Err(primary) => primary,
+        };
+        if !falls_back(&self.filter, &primary) {
+            return Err(FallbackError::Primary(primary));
+        }
+        fs.rollback_to(savepoint, &primary);
+        self.right
+            .forge_in(fs, into)
+            .map_err(|fallback| FallbackError::Both { primary, fallback })
+    }
+}
+
+/// A struct that tries a list of alternative operations in order until one succeeds.
+///
+/// `FirstOf` generalises [`Fallback`] to any number of operations of the same type. Like
+/// `Fallback`, it can be limited to selected errors with [`on`](FirstOf::on) or
+/// [`on_kinds`](FirstOf::on_kinds), and it keeps the error of every alternative it tried.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, either::first_of, mover::Move};
+///
+/// // Use whichever of the old config files the project has
+/// first_of([
+///     Move::new("./config.yml"),
+///     Move::new("./config.yaml"),
+///     Move::new("./.config.yml"),
+/// ])
+/// .on_kinds([std::io::ErrorKind::NotFound])
+/// .forge("./config/app.yml")
+/// .unwrap();
+/// ```
+pub struct FirstOf<F: Forge> {
+    /// The operations to try, in order
+    alternatives: Vec<F>,
+    /// Which errors lead to the next alternative, if not all of them
+    filter: Option<Filter>,
+}
+
+/// Errors that can occur when running a [`FirstOf`] operation.
+#[derive(Error, Debug)]
+pub enum FirstOfError<E: Error + 'static> {
+    /// There were no alternatives to try.
+    #[error("no alternatives to try")]
+    Empty,
+
+    /// An alternative failed with an error that doesn't lead to the next one. The last
+    /// error is the one that stopped the search.
+    #[error("alternative {} failed: {}", errors.len(), errors[errors.len() - 1])]
+    Stopped {
+        /// The errors of the alternatives tried, in order
+        errors: Vec<E>,
+    },
+
+    /// Every alternative failed.
+    #[error("all {} alternatives failed, the last with: {}", errors.len(), errors[errors.len() - 1])]
+    Exhausted {
+        /// The errors of all alternatives, in order
+        errors: Vec<E>,
+    },
+}
+
+impl<E: Error + 'static> FirstOfError<E> {
+    /// Returns the errors of the alternatives that were tried, in order.
+    pub fn errors(&self) -> &[E] {
+        match self {
+            FirstOfError::Empty => &[],
+            FirstOfError::Stopped { errors } | FirstOfError::Exhausted { errors } => errors,
+        }
+    }
+}
+
+impl<F: Forge> FirstOf<F> {
+    /// Creates a new `FirstOf` operation that moves on after any error.
+    ///
+    /// # Parameters
+    ///
+    /// * `alternatives` - The operations to try, in order
+    ///
+    /// # Returns
+    ///
+    /// A new `FirstOf` instance.
+    pub fn new(alternatives: impl IntoIterator<Item = F>) -> Self {
+        Self {
+            alternatives: alternatives.into_iter().collect(),
+            filter: None,
+        }
+    }
+
+    /// Only moves on to the next alternative on errors for which `filter` returns `true`.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - The check applied to the error of each alternative
+    ///
+    /// # Returns
+    ///
+    /// The `FirstOf` instance with the filter set.
+    pub fn on(mut self, filter: impl Fn(&(dyn Error + 'static)) -> bool + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only moves on to the next alternative on errors caused by an I/O error of one of the
+    /// given kinds.
+    ///
+    /// # Parameters
+    ///
+    /// * `kinds` - The kinds of I/O error that lead to the next alternative
+    ///
+    /// # Returns
+    ///
+    /// The `FirstOf` instance with the filter set.
+    pub fn on_kinds(mut self, kinds: impl IntoIterator<Item = ErrorKind>) -> Self {
+        self.filter = Some(kinds_filter(kinds));
+        self
+    }
+
+    /// Returns the number of alternatives.
+    pub fn len(&self) -> usize {
+        self.alternatives.len()
+    }
+
+    /// Returns `true` if there are no alternatives.
+    pub fn is_empty(&self) -> bool {
+        self.alternatives.is_empty()
+    }
+}
+
+impl<F: Forge> FromIterator<F> for FirstOf<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<F: Forge> Forge for FirstOf<F>
+where
+    F::Error: 'static,
+{
+    type Error = FirstOfError<F::Error>;
+
+    /// Tries each alternative in order until one succeeds.
+    ///
+    /// The filesystem is rolled back to a [`Savepoint`](crate::fs::Savepoint) after each
+    /// alternative that fails and is followed by another.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the alternatives run against
+    /// * `into` - Path where the operation will be performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), FirstOfError<F::Error>>` - Ok as soon as an alternative succeeds, or an
+    ///   error carrying the errors of every alternative that was tried.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        let mut errors = Vec::new();
+        for (index, alternative) in self.alternatives.iter().enumerate() {
+            let savepoint = fs.savepoint();
+            let error = match alternative.forge_in(fs, into) {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            if !falls_back(&self.filter, &error) {
+                errors.push(error);
+                return Err(FirstOfError::Stopped { errors });
+            }
+            if index + 1 < self.alternatives.len() {
+                fs.rollback_to(savepoint, &error);
+            }
+            errors.push(error);
+        }
+        if errors.is_empty() {
+            Err(FirstOfError::Empty)
+        } else {
+            Err(FirstOfError::Exhausted { errors })
+        }
+    }
+}
+
+/// Convenience function to create a `FirstOf` operation.
+///
+/// This is a shorthand for `FirstOf::new()`.
+///
+/// # Parameters
+///
+/// * `alternatives` - The operations to try, in order
+///
+/// # Returns
+///
+/// A `FirstOf` instance trying the given operations.
+#[inline]
+pub fn first_of<F: Forge>(alternatives: impl IntoIterator<Item = F>) -> FirstOf<F> {
+    FirstOf::new(alternatives)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "memory")]
+    use crate::{append::Append, fs::Memory, generate::Generate, mover::Move, Anvil, ForgeExt};
     use std::io;
     use std::path::PathBuf;
     use tempfile::tempdir;
 
+    // Mock implementation for Anvil
+    #[cfg(feature = "memory")]
+    struct MockAnvil(&'static str);
+
+    #[cfg(feature = "memory")]
+    impl Anvil for MockAnvil {
+        type Error = io::Error;
+
+        fn anvil(&self, writer: &mut (impl io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.0.as_bytes())
+        }
+    }
+
     // Mock Forge implementations for testing
     struct MockForge<F>
     where
@@ -160,7 +564,11 @@ mod tests {
     {
         type Error = io::Error;
 
-        fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        fn forge_in(
+            &self,
+            _fs: &dyn FileSystem,
+            _into: impl AsRef<Path>,
+        ) -> Result<(), Self::Error> {
             (self.action)()
         }
     }
@@ -255,4 +663,73 @@ mod tests {
         let result = either_forge.forge(test_path);
         assert!(result.is_ok());
     }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_fallback_keeps_both_errors_and_filters_kinds() {
+        let fs = Memory::new();
+
+        // Both errors are reported when both operations fail
+        let left = MockForge {
+            action: || Err(io::Error::from(io::ErrorKind::AlreadyExists)),
+        };
+        let right = MockForge {
+            action: || Err(io::Error::other("Right fails")),
+        };
+        let error = Fallback::new(left, right).forge_in(&fs, "a").unwrap_err();
+        match error {
+            FallbackError::Both { primary, fallback } => {
+                assert_eq!(primary.kind(), io::ErrorKind::AlreadyExists);
+                assert_eq!(fallback.to_string(), "Right fails");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        // An unexpected error is returned without trying the fallback
+        let left = MockForge {
+            action: || Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+        };
+        let right = MockForge {
+            action: || panic!("fallback should not run"),
+        };
+        let error = Fallback::new(left, right)
+            .on_kinds([io::ErrorKind::AlreadyExists, io::ErrorKind::NotFound])
+            .forge_in(&fs, "a")
+            .unwrap_err();
+        assert!(matches!(error, FallbackError::Primary(_)));
+
+        // The kind is found through wrapping errors
+        let existing = Memory::new().with_file("a.txt", "old");
+        Generate::new(MockAnvil("new"))
+            .fallback(Append::new(MockAnvil(" appended")))
+            .on_kinds([io::ErrorKind::AlreadyExists])
+            .forge_in(&existing, "a.txt")
+            .unwrap();
+        assert_eq!(
+            existing.read_to_string(Path::new("a.txt")).unwrap(),
+            "old appended"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_first_of_tries_alternatives_in_order() {
+        let fs = Memory::new().with_file("b.txt", "b");
+
+        let moves = first_of([Move::new("a.txt"), Move::new("b.txt"), Move::new("c.txt")])
+            .on_kinds([io::ErrorKind::NotFound]);
+        moves.forge_in(&fs, "moved.txt").unwrap();
+        assert_eq!(fs.read_to_string(Path::new("moved.txt")).unwrap(), "b");
+
+        // Now none of the sources exist
+        let error = moves.forge_in(&fs, "moved.txt").unwrap_err();
+        assert!(matches!(error, FirstOfError::Exhausted { .. }));
+        assert_eq!(error.errors().len(), 3);
+
+        let none: FirstOf<Move> = first_of([]);
+        assert!(matches!(
+            none.forge_in(&fs, "moved.txt"),
+            Err(FirstOfError::Empty)
+        ));
+    }
 }
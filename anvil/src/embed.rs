@@ -0,0 +1,419 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fs::{is_root, normalize, FileSystem, Metadata},
+    Anvil,
+};
+
+/// A file that was embedded into the binary by [`embed_dir!`](crate::embed_dir).
+///
+/// An `EmbeddedFile` implements [`Anvil`] by writing its contents verbatim, so it can be
+/// handed straight to [`Generate`](crate::generate::Generate). Text templates can also be
+/// passed to a template engine through [`contents_str`](EmbeddedFile::contents_str).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, embed::EmbeddedDir, generate::Generate};
+///
+/// # static TEMPLATES: EmbeddedDir = EmbeddedDir::new(&[], &[]);
+/// // With `static TEMPLATES: EmbeddedDir = anvil::embed_dir!("templates");`
+/// let gitignore = TEMPLATES.get(".gitignore").unwrap();
+/// Generate::new(gitignore).forge("./my-app/.gitignore").unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedFile {
+    /// The path of the file relative to the embedded directory, with `/` separators
+    path: &'static str,
+    /// The contents of the file
+    contents: &'static [u8],
+    /// The Unix permission bits of the file
+    mode: u32,
+}
+
+impl EmbeddedFile {
+    /// Creates an embedded file. This is called by the code that
+    /// [`embed_dir!`](crate::embed_dir) generates.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - The path of the file relative to the embedded directory
+    /// * `contents` - The contents of the file
+    /// * `mode` - The Unix permission bits of the file, such as `0o644`
+    pub const fn new(path: &'static str, contents: &'static [u8], mode: u32) -> Self {
+        Self {
+            path,
+            contents,
+            mode,
+        }
+    }
+
+    /// Returns the path of the file relative to the embedded directory.
+    pub fn path(&self) -> &'static Path {
+        Path::new(self.path)
+    }
+
+    /// Returns the contents of the file.
+    pub fn contents(&self) -> &'static [u8] {
+        self.contents
+    }
+
+    /// Returns the contents of the file as text, or `None` if they aren't valid UTF-8.
+    pub fn contents_str(&self) -> Option<&'static str> {
+        std::str::from_utf8(self.contents).ok()
+    }
+
+    /// Returns the Unix permission bits the file had when it was embedded.
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+impl Anvil for &EmbeddedFile {
+    type Error = io::Error;
+
+    fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+        writer.write_all(self.contents)
+    }
+}
+
+/// A directory of templates that was embedded into the binary by
+/// [`embed_dir!`](crate::embed_dir).
+///
+/// Generators that are distributed as binaries can't rely on their templates being on
+/// disk, so `embed_dir!` bakes the whole tree into the binary at compile time, keeping the
+/// relative path and permissions of every file.
+///
+/// An `EmbeddedDir` is a read-only [`FileSystem`] whose root is the embedded directory. It
+/// converts into a [`Source`], which is what [`GenerateDir`](crate::generate_dir::GenerateDir)
+/// and [`Copy`](crate::copy::Copy) read from, and its files can be looked up with
+/// [`get`](EmbeddedDir::get) for use with [`Generate`](crate::generate::Generate).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, copy::Copy, embed::EmbeddedDir};
+///
+/// # static TEMPLATES: EmbeddedDir = EmbeddedDir::new(&[], &[]);
+/// // With `static TEMPLATES: EmbeddedDir = anvil::embed_dir!("templates");`
+///
+/// // Copy the whole embedded tree into a new project
+/// Copy::new(&TEMPLATES).forge("./my-app").unwrap();
+///
+/// // Or just one directory of it
+/// Copy::new(TEMPLATES.at("static")).forge("./my-app/static").unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedDir {
+    /// Every directory beneath the root, relative to it with `/` separators
+    dirs: &'static [&'static str],
+    /// Every file beneath the root
+    files: &'static [EmbeddedFile],
+}
+
+impl EmbeddedDir {
+    /// Creates an embedded directory. This is called by the code that
+    /// [`embed_dir!`](crate::embed_dir) generates.
+    ///
+    /// # Parameters
+    ///
+    /// * `dirs` - Every directory beneath the root, relative to it
+    /// * `files` - Every file beneath the root
+    pub const fn new(dirs: &'static [&'static str], files: &'static [EmbeddedFile]) -> Self {
+        Self { dirs, files }
+    }
+
+    /// Returns every embedded file, sorted by path.
+    pub fn files(&self) -> &'static [EmbeddedFile] {
+        self.files
+    }
+
+    /// Looks up the file at `path`, relative to the embedded directory.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - The path of the file to look up
+    ///
+    /// # Returns
+    ///
+    /// The embedded file, or `None` if there is no file at `path`.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&'static EmbeddedFile> {
+        let path = normalize(path.as_ref());
+        self.files.iter().find(|file| file.path() == path)
+    }
+
+    /// Returns a [`Source`] for the file or directory at `path` inside the embedded
+    /// directory.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - The path inside the embedded directory
+    ///
+    /// # Returns
+    ///
+    /// A source that reads from this embedded directory.
+    pub fn at(&'static self, path: impl AsRef<Path>) -> Source {
+        Source::Embedded(self, path.as_ref().to_path_buf())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        is_root(path) || self.dirs.iter().any(|dir| Path::new(dir) == path)
+    }
+
+    fn read_only(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::ReadOnlyFilesystem,
+            format!("cannot modify embedded file {}", path.display()),
+        )
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no embedded file or directory at {}", path.display()),
+        )
+    }
+}
+
+impl FileSystem for EmbeddedDir {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.get(path) {
+            Some(file) => Ok(file.contents.to_vec()),
+            None => Err(Self::not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, _contents: &[u8]) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn create_new(&self, path: &Path, _contents: &[u8]) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn append(&self, path: &Path, _contents: &[u8]) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn rename(&self, from: &Path, _to: &Path) -> io::Result<()> {
+        Err(Self::read_only(from))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        Err(Self::read_only(path))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let normalized = normalize(path);
+        if !self.is_dir(&normalized) {
+            if self.get(&normalized).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotADirectory,
+                    format!("not a directory: {}", normalized.display()),
+                ));
+            }
+            return Err(Self::not_found(&normalized));
+        }
+
+        let children = self
+            .dirs
+            .iter()
+            .copied()
+            .chain(self.files.iter().map(|file| file.path))
+            .map(Path::new)
+            .filter(|child| child.parent() == Some(normalized.as_path()));
+        let mut entries: Vec<PathBuf> = children
+            .filter_map(|child| child.file_name())
+            .map(|name| path.join(name))
+            .collect();
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let normalized = normalize(path);
+        if self.is_dir(&normalized) {
+            return Ok(Metadata::dir());
+        }
+        match self.get(&normalized) {
+            Some(file) => Ok(Metadata::file(file.contents.len() as u64)),
+            None => Err(Self::not_found(&normalized)),
+        }
+    }
+}
+
+/// Where [`GenerateDir`](crate::generate_dir::GenerateDir) and [`Copy`](crate::copy::Copy)
+/// read their files from.
+///
+/// A plain path converts into a `Source` that is read from the same [`FileSystem`] the
+/// operation writes to, so `Copy::new("./templates")` keeps working as it always has. An
+/// [`EmbeddedDir`] converts into a `Source` that is read from the binary instead, with
+/// [`EmbeddedDir::at`] selecting a path inside it.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::embed::{EmbeddedDir, Source};
+/// use std::path::Path;
+///
+/// static TEMPLATES: EmbeddedDir = EmbeddedDir::new(&[], &[]);
+///
+/// let on_disk = Source::from("./templates");
+/// let embedded = Source::from(&TEMPLATES);
+/// assert_eq!(on_disk.path(), Path::new("./templates"));
+/// assert_eq!(embedded.path(), Path::new(""));
+/// ```
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// A path on the filesystem that the operation writes to.
+    Path(PathBuf),
+    /// A path inside a directory that was embedded into the binary.
+    Embedded(&'static EmbeddedDir, PathBuf),
+}
+
+impl Source {
+    /// Returns the path of the source, relative to the filesystem it is read from.
+    pub fn path(&self) -> &Path {
+        match self {
+            Source::Path(path) | Source::Embedded(_, path) => path,
+        }
+    }
+
+    /// Returns the filesystem to read the source from, given the one being written to.
+    pub(crate) fn filesystem<'a>(&self, fs: &'a dyn FileSystem) -> &'a dyn FileSystem {
+        match self {
+            Source::Path(_) => fs,
+            Source::Embedded(dir, _) => *dir,
+        }
+    }
+
+    /// Returns the permission bits to give a copy of the file at `path`, if the source
+    /// records them.
+    pub(crate) fn mode(&self, path: &Path) -> Option<u32> {
+        match self {
+            Source::Path(_) => None,
+            Source::Embedded(dir, _) => dir.get(path).map(EmbeddedFile::mode),
+        }
+    }
+}
+
+impl From<&str> for Source {
+    fn from(path: &str) -> Self {
+        Source::Path(PathBuf::from(path))
+    }
+}
+
+impl From<&String> for Source {
+    fn from(path: &String) -> Self {
+        Source::Path(PathBuf::from(path))
+    }
+}
+
+impl From<String> for Source {
+    fn from(path: String) -> Self {
+        Source::Path(PathBuf::from(path))
+    }
+}
+
+impl From<&Path> for Source {
+    fn from(path: &Path) -> Self {
+        Source::Path(path.to_path_buf())
+    }
+}
+
+impl From<&PathBuf> for Source {
+    fn from(path: &PathBuf) -> Self {
+        Source::Path(path.clone())
+    }
+}
+
+impl From<PathBuf> for Source {
+    fn from(path: PathBuf) -> Self {
+        Source::Path(path)
+    }
+}
+
+impl From<&'static EmbeddedDir> for Source {
+    fn from(dir: &'static EmbeddedDir) -> Self {
+        Source::Embedded(dir, PathBuf::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::{fs::Memory, generate::Generate, Forge};
+
+    static TEMPLATES: EmbeddedDir = EmbeddedDir::new(
+        &["empty", "src", "src/bin"],
+        &[
+            EmbeddedFile::new("README.md", b"# Project\n", 0o644),
+            EmbeddedFile::new("src/bin/run.sh", b"#!/bin/sh\n", 0o755),
+            EmbeddedFile::new("src/main.rs", b"fn main() {}\n", 0o644),
+        ],
+    );
+
+    #[test]
+    fn test_embedded_dir_is_a_read_only_filesystem() {
+        let root = TEMPLATES.read_dir(Path::new("")).unwrap();
+        assert_eq!(
+            root,
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("empty"),
+                PathBuf::from("src")
+            ]
+        );
+        assert_eq!(
+            TEMPLATES.read_dir(Path::new("src")).unwrap(),
+            vec![PathBuf::from("src/bin"), PathBuf::from("src/main.rs")]
+        );
+        assert!(TEMPLATES.read_dir(Path::new("empty")).unwrap().is_empty());
+
+        assert!(TEMPLATES.metadata(Path::new("src/bin")).unwrap().is_dir());
+        assert_eq!(
+            TEMPLATES.metadata(Path::new("README.md")).unwrap().len(),
+            10
+        );
+        assert_eq!(
+            TEMPLATES.read(Path::new("./src/main.rs")).unwrap(),
+            b"fn main() {}\n"
+        );
+        assert!(!TEMPLATES.exists(Path::new("missing")));
+
+        // Nothing can be written
+        let result = TEMPLATES.write(Path::new("README.md"), b"changed");
+        assert_eq!(
+            result.unwrap_err().kind(),
+            io::ErrorKind::ReadOnlyFilesystem
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_embedded_file_generates_its_contents() {
+        let fs = Memory::new();
+        let readme = TEMPLATES.get("README.md").unwrap();
+        assert_eq!(readme.contents_str(), Some("# Project\n"));
+        assert_eq!(TEMPLATES.get("src/bin/run.sh").unwrap().mode(), 0o755);
+
+        Generate::new(readme).forge_in(&fs, "README.md").unwrap();
+        assert_eq!(fs.read(Path::new("README.md")).unwrap(), b"# Project\n");
+    }
+}
@@ -0,0 +1,894 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "memory")]
+mod memory;
+
+#[cfg(feature = "memory")]
+pub use memory::Memory;
+
+/// Metadata about an entry in a [`FileSystem`].
+///
+/// This is a filesystem-agnostic counterpart to [`std::fs::Metadata`], which cannot be
+/// constructed outside of the standard library. It carries only the information that
+/// Anvil's operations need.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::fs::Metadata;
+///
+/// let metadata = Metadata::file(42);
+/// assert!(metadata.is_file());
+/// assert_eq!(metadata.len(), 42);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    /// Whether the entry is a directory
+    is_dir: bool,
+    /// Whether the entry is a symbolic link, which [`FileSystem::metadata`] never reports
+    is_symlink: bool,
+    /// The size of the entry in bytes
+    len: u64,
+    /// The Unix permission bits of the entry, where the filesystem models them
+    mode: Option<u32>,
+}
+
+impl Metadata {
+    /// Creates metadata describing a regular file of `len` bytes.
+    pub fn file(len: u64) -> Self {
+        Self {
+            is_dir: false,
+            is_symlink: false,
+            len,
+            mode: None,
+        }
+    }
+
+    /// Creates metadata describing a directory.
+    pub fn dir() -> Self {
+        Self {
+            is_dir: true,
+            is_symlink: false,
+            len: 0,
+            mode: None,
+        }
+    }
+
+    /// Creates metadata describing a symbolic link itself, as returned by
+    /// [`FileSystem::symlink_metadata`].
+    pub fn symlink() -> Self {
+        Self {
+            is_dir: false,
+            is_symlink: true,
+            len: 0,
+            mode: None,
+        }
+    }
+
+    /// Returns `true` if this metadata is for a regular file.
+    pub fn is_file(&self) -> bool {
+        !self.is_dir && !self.is_symlink
+    }
+
+    /// Returns `true` if this metadata is for a directory.
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Returns `true` if this metadata is for a symbolic link.
+    pub fn is_symlink(&self) -> bool {
+        self.is_symlink
+    }
+
+    /// Returns the size of the file in bytes. Directories report a length of zero.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the metadata with the given Unix permission bits, such as `0o755`.
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Returns the Unix permission bits of the entry, or `None` if the filesystem
+    /// doesn't model permissions.
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+}
+
+impl From<std::fs::Metadata> for Metadata {
+    fn from(metadata: std::fs::Metadata) -> Self {
+        let converted = if metadata.is_symlink() {
+            Self::symlink()
+        } else if metadata.is_dir() {
+            Self::dir()
+        } else {
+            Self::file(metadata.len())
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            converted.with_mode(metadata.permissions().mode() & 0o7777)
+        }
+        #[cfg(not(unix))]
+        converted
+    }
+}
+
+/// A marker for a point in a [`FileSystem`]'s history that can later be rolled back to.
+///
+/// Savepoints are handed out by [`FileSystem::savepoint`] and consumed by
+/// [`FileSystem::rollback_to`]. Their meaning is entirely up to the filesystem that
+/// created them; filesystems that do not support rollback ignore them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Savepoint(usize);
+
+impl Savepoint {
+    /// Creates a savepoint at the given position in a filesystem's history.
+    pub fn new(position: usize) -> Self {
+        Self(position)
+    }
+
+    /// Returns the position in the filesystem's history that this savepoint marks.
+    pub fn position(&self) -> usize {
+        self.0
+    }
+}
+
+/// Why an operation left a path as it was, as passed to [`FileSystem::keep`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kept {
+    /// The path already was what the operation would have made it, such as a file with the
+    /// rendered contents or an existing directory.
+    Identical,
+    /// The file differed from what the operation would have written, but the operation was
+    /// told to leave it alone.
+    Skipped,
+}
+
+/// The storage backend that every [`Forge`](crate::Forge) operation runs against.
+///
+/// `FileSystem` abstracts the handful of file operations that Anvil's built-in forges
+/// perform. The default implementation, [`Disk`], forwards directly to [`std::fs`], but
+/// alternative implementations can redirect, record or sandbox those operations without
+/// the forges themselves having to change.
+///
+/// The methods intentionally mirror their `std::fs` namesakes, including the error kinds
+/// they report (for example `NotFound` and `AlreadyExists`), so that callers can rely on
+/// the same behaviour regardless of which filesystem they run against.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::fs::{Disk, FileSystem};
+/// use std::path::Path;
+///
+/// fn describe(fs: &dyn FileSystem, path: &Path) -> std::io::Result<String> {
+///     let content = fs.read_to_string(path)?;
+///     Ok(format!("{} is {} bytes long", path.display(), content.len()))
+/// }
+///
+/// let summary = describe(&Disk, Path::new("./Cargo.toml"));
+/// ```
+pub trait FileSystem {
+    /// Reads the entire contents of a file into a byte vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the file does not exist.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Reads the entire contents of a file into a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the file does not exist, or `InvalidData`
+    /// if the contents are not valid UTF-8.
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Writes `contents` to a file, creating it if it does not exist and replacing it
+    /// if it does.
+    ///
+    /// Implementations should replace the file atomically, so that readers (and users,
+    /// after a crash) see either the old contents or the new ones, never a mix.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates a new file containing `contents`.
+    ///
+    /// The file should only become visible once all of `contents` has been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `AlreadyExists` if a file already exists at `path`.
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Adds `contents` to the end of an existing file.
+    ///
+    /// Implementations should apply the whole of `contents` atomically, so that a
+    /// failure never leaves a partial append behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the file does not exist.
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Renames a file or directory from `from` to `to`.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Recursively creates a directory and all of its missing parents.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the file does not exist.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes an empty directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the directory does not exist, or
+    /// `DirectoryNotEmpty` if it still has entries.
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns the paths of the entries directly inside a directory, sorted by name.
+    ///
+    /// Like [`std::fs::read_dir`], each returned path is `path` joined with the name of
+    /// the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if the directory does not exist, or
+    /// `NotADirectory` if `path` is a file.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Returns `true` if a file or directory exists at `path`.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Returns the canonical form of `path`, with every symbolic link resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if nothing exists at `path`. Filesystems
+    /// without symbolic links may instead return the path normalised lexically, which is
+    /// what the default implementation does.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(normalize(path))
+    }
+
+    /// Returns the path that the symbolic link at `path` points to.
+    ///
+    /// The default implementation is for filesystems without symbolic links.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if nothing exists at `path`, or
+    /// `InvalidInput` if it isn't a symbolic link.
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.symlink_metadata(path)?;
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a symbolic link: {}", path.display()),
+        ))
+    }
+
+    /// Creates a symbolic link at `link` that points to `target`.
+    ///
+    /// `target` is stored as given, so a relative target is resolved against the
+    /// directory containing `link`. The default implementation is for filesystems without
+    /// symbolic links, and fails with kind `Unsupported`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `AlreadyExists` if something already exists at `link`.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let _ = target;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("symbolic links are not supported: {}", link.display()),
+        ))
+    }
+
+    /// Sets the Unix permission bits of the file at `path`, such as `0o755`.
+    ///
+    /// Filesystems that don't model permissions ignore the call, which is what the default
+    /// implementation does.
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        let _ = (path, mode);
+        Ok(())
+    }
+
+    /// Notes that an operation chose to leave `path` as it was.
+    ///
+    /// Nothing is changed. Wrappers that report what operations did use this to tell a file
+    /// that was skipped or already up to date from one that was never looked at. The
+    /// default implementation does nothing.
+    fn keep(&self, path: &Path, kept: Kept) {
+        let _ = (path, kept);
+    }
+
+    /// Returns the [`Metadata`] for the entry at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if nothing exists at `path`.
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// Returns the [`Metadata`] for the entry at `path` without following symbolic links.
+    ///
+    /// Operations that walk a directory tree, such as [`Delete`](crate::delete::Delete) and
+    /// [`Copy`](crate::copy::Copy), use this so that they never descend into a linked
+    /// directory. Filesystems that don't support symbolic links return the same as
+    /// [`metadata`](FileSystem::metadata), which is what the default implementation does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind `NotFound` if nothing exists at `path`.
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.metadata(path)
+    }
+
+    /// Marks the current point in the filesystem's history.
+    ///
+    /// Combinators such as [`Either`](crate::either::Either) take a savepoint before
+    /// running an operation that might fail, so that its partial effects can be
+    /// discarded with [`rollback_to`](FileSystem::rollback_to) before trying an
+    /// alternative. The default implementation does not track history.
+    fn savepoint(&self) -> Savepoint {
+        Savepoint::default()
+    }
+
+    /// Discards the effects of every operation performed since `savepoint` was taken.
+    ///
+    /// `cause` is the error that made the operations fail, which filesystems may record
+    /// for reporting. The default implementation does nothing, which means effects on
+    /// filesystems without history (such as [`Disk`]) are kept.
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let _ = (savepoint, cause);
+    }
+}
+
+/// The default [`FileSystem`], backed by the real disk through [`std::fs`].
+///
+/// This is what [`Forge::forge`](crate::Forge::forge) uses when no other filesystem
+/// is provided.
+///
+/// Every write goes to a temporary file in the target's directory first, which is then
+/// renamed (or, for new files, linked) into place. A crash or error part-way through a
+/// write therefore never leaves a truncated or half-written file behind. Appends are
+/// written the same way, as the old contents followed by the new ones. Writes through a
+/// symbolic link replace the file it points to and keep the link. On filesystems without
+/// hard links, such as FAT or many network mounts, new files are created in place instead,
+/// so a failure there can leave a partial new file behind.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{fs::Disk, Forge, mover::Move};
+///
+/// // Equivalent to `Move::new("./old.txt").forge("./new.txt")`
+/// Move::new("./old.txt").forge_in(&Disk, "./new.txt").unwrap();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Disk;
+
+impl FileSystem for Disk {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        // Renaming over a symbolic link would replace the link itself, so write to the
+        // file it points to instead
+        replace_atomically(&resolve_links(path)?, contents)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        write_atomically(path, contents, |temp| {
+            // Linking fails if the target exists, so a file that appears in the meantime
+            // is never replaced
+            match std::fs::hard_link(temp, path) {
+                Ok(()) => {}
+                // Some filesystems (FAT, many network mounts) don't support hard links,
+                // so fall back to creating the file in place
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        io::ErrorKind::Unsupported | io::ErrorKind::PermissionDenied
+                    ) =>
+                {
+                    create_in_place(path, contents)?
+                }
+                Err(error) => return Err(error),
+            }
+            let _ = std::fs::remove_file(temp);
+            Ok(())
+        })
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = &resolve_links(path)?;
+        let mut combined = std::fs::read(path)?;
+        combined.extend_from_slice(contents);
+        replace_atomically(path, &combined)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+
+    #[cfg(unix)]
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        std::fs::metadata(path).map(Metadata::from)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        std::fs::symlink_metadata(path).map(Metadata::from)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::read_link(path)
+    }
+
+    #[cfg(unix)]
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    #[cfg(windows)]
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let resolved = match link.parent() {
+            Some(parent) => parent.join(target),
+            None => target.to_path_buf(),
+        };
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        }
+    }
+}
+
+/// Writes `contents` to a temporary file beside `path` and hands it to `commit` to move
+/// into place. The temporary file is removed again if writing or committing fails.
+fn write_atomically(
+    path: &Path,
+    contents: &[u8],
+    commit: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a file path: {}", path.display()),
+        )
+    })?;
+    let temp = path.with_file_name(format!(
+        ".{}.{}-{}.anvil-tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = std::fs::File::create_new(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| commit(&temp));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Replaces the file at `path` with `contents` through a temporary file, keeping the
+/// permissions of the file being replaced.
+fn replace_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let permissions = std::fs::metadata(path).ok().map(|m| m.permissions());
+    write_atomically(path, contents, |temp| {
+        if let Some(permissions) = permissions {
+            std::fs::set_permissions(temp, permissions)?;
+        }
+        std::fs::rename(temp, path)
+    })
+}
+
+/// Creates `path` and writes `contents` to it, failing if it already exists. The file is
+/// removed again if writing fails.
+fn create_in_place(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    let result = file.write_all(contents).and_then(|()| file.sync_all());
+    if result.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+/// Follows `path` while it is a symbolic link, returning the path of the file it
+/// finally points to. The target doesn't need to exist.
+fn resolve_links(path: &Path) -> io::Result<PathBuf> {
+    // The same limit Linux applies before failing with `ELOOP`
+    const MAX_LINKS: usize = 40;
+
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = std::fs::read_link(&path)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("too many levels of symbolic links: {}", path.display()),
+    ))
+}
+
+/// Lexically normalises a path by dropping `.` components and resolving `..` components.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // `..` at the root is the root itself
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Returns `true` for paths that always exist as directories, such as `/` or the empty path.
+pub(crate) fn is_root(path: &Path) -> bool {
+    path.parent().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_disk_create_new_fails_if_file_exists() {
+        // Create a temporary directory with an existing file
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("existing.txt");
+        std::fs::write(&file_path, "existing").unwrap();
+
+        // Attempting to create it again should fail
+        let result = Disk.create_new(&file_path, b"new");
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(io::ErrorKind::AlreadyExists)
+        );
+
+        // The existing file is untouched and no temporary file is left behind
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(content, "existing");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_disk_append_writes_to_end_of_file() {
+        // Create a temporary file with some initial content
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("append.txt");
+        std::fs::write(&file_path, "Initial\n").unwrap();
+
+        // Append through the filesystem abstraction
+        Disk.append(&file_path, b"Appended").unwrap();
+
+        let content = Disk.read_to_string(&file_path).unwrap();
+        assert_eq!(content, "Initial\nAppended");
+    }
+
+    #[test]
+    fn test_disk_metadata_reports_files_and_directories() {
+        // Create a temporary directory containing a file
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("file.txt");
+        Disk.write(&file_path, b"12345").unwrap();
+
+        let file_metadata = Disk.metadata(&file_path).unwrap();
+        assert!(file_metadata.is_file());
+        assert_eq!(file_metadata.len(), 5);
+
+        let dir_metadata = Disk.metadata(temp_dir.path()).unwrap();
+        assert!(dir_metadata.is_dir());
+
+        // Missing entries report NotFound
+        let missing = Disk.metadata(&temp_dir.path().join("missing.txt"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_disk_write_replaces_file_without_leaving_temporary_files() {
+        // Create a temporary directory with an existing file
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("config.toml");
+        std::fs::write(&file_path, "old = true").unwrap();
+
+        Disk.write(&file_path, b"new = true").unwrap();
+
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(content, "new = true");
+
+        // Only the target file remains in the directory
+        let names: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["config.toml"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_write_through_link_keeps_the_link() {
+        // Create a file and a symbolic link pointing to it
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("target.txt");
+        let link = temp_dir.path().join("link.txt");
+        std::fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink("target.txt", &link).unwrap();
+
+        Disk.write(&link, b"new").unwrap();
+
+        // The link still points to the target, which holds the new contents
+        assert!(Disk.symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_append_keeps_links_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Create an executable script and a symbolic link pointing to it
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("run.sh");
+        let link = temp_dir.path().join("link.sh");
+        std::fs::write(&target, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("run.sh", &link).unwrap();
+
+        Disk.append(&link, b"echo hi\n").unwrap();
+
+        assert!(Disk.symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            "#!/bin/sh\necho hi\n"
+        );
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        // Only the script and the link remain
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    // Stacks every wrapper over a probe, to check that the hooks they must forward reach
+    // the innermost filesystem
+    #[cfg(all(feature = "memory", feature = "manifest"))]
+    mod stacked {
+        use super::*;
+        use crate::{
+            manifest::Manifest, plan::Plan, report::Reporter, revert::Revertible, sandbox::Sandbox,
+            transaction::Transaction, Forge,
+        };
+
+        // A filesystem that logs the hooks wrappers must forward, and passes every call on
+        struct Probe<'a> {
+            inner: &'a dyn FileSystem,
+            calls: std::cell::RefCell<Vec<String>>,
+        }
+
+        impl Probe<'_> {
+            fn log(&self, call: String) {
+                self.calls.borrow_mut().push(call);
+            }
+        }
+
+        impl FileSystem for Probe<'_> {
+            fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+                self.inner.read(path)
+            }
+
+            fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+                self.inner.write(path, contents)
+            }
+
+            fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+                self.inner.create_new(path, contents)
+            }
+
+            fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+                self.inner.append(path, contents)
+            }
+
+            fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+                self.inner.rename(from, to)
+            }
+
+            fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+                self.inner.create_dir_all(path)
+            }
+
+            fn remove_file(&self, path: &Path) -> io::Result<()> {
+                self.inner.remove_file(path)
+            }
+
+            fn remove_dir(&self, path: &Path) -> io::Result<()> {
+                self.inner.remove_dir(path)
+            }
+
+            fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+                self.inner.read_dir(path)
+            }
+
+            fn exists(&self, path: &Path) -> bool {
+                self.inner.exists(path)
+            }
+
+            fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+                self.inner.metadata(path)
+            }
+
+            fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+                self.log(format!("symlink_metadata {}", path.display()));
+                self.inner.symlink_metadata(path)
+            }
+
+            fn keep(&self, path: &Path, kept: Kept) {
+                self.log(format!("keep {}", path.display()));
+                self.inner.keep(path, kept)
+            }
+
+            fn savepoint(&self) -> Savepoint {
+                self.log("savepoint".to_string());
+                self.inner.savepoint()
+            }
+
+            fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+                self.log("rollback_to".to_string());
+                self.inner.rollback_to(savepoint, cause)
+            }
+        }
+
+        // Runs a closure against the filesystem it is forged in, to reach the wrappers that
+        // only exist while an operation runs
+        struct Run<F: Fn(&dyn FileSystem, &Path) -> io::Result<()>>(F);
+
+        impl<F: Fn(&dyn FileSystem, &Path) -> io::Result<()>> crate::Forge for Run<F> {
+            type Error = io::Error;
+
+            fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> io::Result<()> {
+                (self.0)(fs, into.as_ref())
+            }
+        }
+
+        #[test]
+        fn test_stacked_wrappers_forward_hooks_to_the_innermost_filesystem() {
+            let memory = Memory::new().with_file("project/kept.txt", "kept");
+            let plan = Plan::over(&memory);
+            let probe = Probe {
+                inner: &plan,
+                calls: Default::default(),
+            };
+
+            // Transaction journal > revert recorder > manifest recorder > reporter > sandbox
+            let recorded = Run(|fs: &dyn FileSystem, into: &Path| {
+                let revertible = Revertible::record_in(
+                    &Run(|fs: &dyn FileSystem, into: &Path| {
+                        let recorder = Manifest::new().recorder(fs, "test");
+                        let reporter = Reporter::new(&recorder);
+                        let sandbox = Sandbox::new(&reporter, into);
+                        let kept = into.join("kept.txt");
+                        let discarded = into.join("discarded.txt");
+
+                        assert!(sandbox.symlink_metadata(&kept)?.is_file());
+                        sandbox.keep(&kept, Kept::Identical);
+
+                        let savepoint = sandbox.savepoint();
+                        sandbox.create_new(&discarded, b"discarded")?;
+                        let cause = io::Error::other("branch failed");
+                        sandbox.rollback_to(savepoint, &cause);
+
+                        // Every wrapper dropped what it recorded for the rolled back branch
+                        let report = reporter.finish();
+                        assert_eq!(report.entries().len(), 1);
+                        assert_eq!(report.entries()[0].path(), kept);
+                        assert!(recorder.finish().get(&discarded).is_none());
+                        Ok(())
+                    }),
+                    fs,
+                    into,
+                )?;
+                assert!(revertible.is_empty());
+                Ok(())
+            });
+            Transaction::new()
+                .step(recorded, "")
+                .forge_in(&probe, "project")
+                .unwrap();
+
+            assert_eq!(
+                *probe.calls.borrow(),
+                vec![
+                    // Taken by the transaction before its first step
+                    "savepoint",
+                    "symlink_metadata project/kept.txt",
+                    "keep project/kept.txt",
+                    "savepoint",
+                    "rollback_to",
+                ]
+            );
+            // The plan at the bottom discarded the branch too, and the base was never touched
+            assert!(!plan.exists(Path::new("project/discarded.txt")));
+            assert_eq!(memory.files().len(), 1);
+        }
+    }
+}
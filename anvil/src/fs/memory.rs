@@ -0,0 +1,524 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use super::{is_root, normalize, FileSystem, Metadata};
+
+/// A single entry stored in a [`Memory`] filesystem.
+#[derive(Debug, Clone)]
+enum Entry {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// An in-memory [`FileSystem`] for fast, isolated tests.
+///
+/// `Memory` keeps every file and directory in a map instead of touching the disk, so
+/// generator tests can run without tempdirs and in parallel. It follows the same rules
+/// as the real disk where they matter to Anvil's operations: files can only be created
+/// inside existing directories, `create_new` fails on existing files, `append` fails on
+/// missing ones, and so on.
+///
+/// Paths are normalised lexically (`.` components are dropped and `..` components pop
+/// their parent) but are otherwise used as given, so relative and absolute paths name
+/// different entries.
+///
+/// This type is only available with the `memory` feature enabled.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::{Anvil, Forge, append::Append, fs::Memory};
+/// use std::{io::Write, path::Path};
+///
+/// struct Line(&'static str);
+///
+/// impl Anvil for Line {
+///     type Error = std::io::Error;
+///
+///     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+///         writer.write_all(self.0.as_bytes())
+///     }
+/// }
+///
+/// // Seed the filesystem with an existing module file
+/// let fs = Memory::new().with_file("src/lib.rs", "pub mod a;\n");
+///
+/// Append::new(Line("pub mod b;\n")).forge_in(&fs, "src/lib.rs").unwrap();
+///
+/// // Snapshot the resulting tree
+/// let snapshot = fs.snapshot();
+/// assert_eq!(snapshot[Path::new("src/lib.rs")], "pub mod a;\npub mod b;\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct Memory {
+    /// Every file and directory, keyed by normalised path
+    entries: Mutex<BTreeMap<PathBuf, Entry>>,
+}
+
+impl Memory {
+    /// Creates a new, empty in-memory filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the filesystem with a file, creating any missing parent directories.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - Path of the file to create
+    /// * `contents` - Initial contents of the file
+    ///
+    /// # Returns
+    ///
+    /// The filesystem, so that several files can be seeded in a chain.
+    pub fn with_file(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        let path = normalize(path.as_ref());
+        {
+            let mut entries = self.lock();
+            if let Some(parent) = path.parent() {
+                insert_dirs(&mut entries, parent);
+            }
+            entries.insert(path, Entry::File(contents.as_ref().to_vec()));
+        }
+        self
+    }
+
+    /// Seeds the filesystem with a directory, creating any missing parent directories.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - Path of the directory to create
+    ///
+    /// # Returns
+    ///
+    /// The filesystem, so that several entries can be seeded in a chain.
+    pub fn with_dir(self, path: impl AsRef<Path>) -> Self {
+        insert_dirs(&mut self.lock(), &normalize(path.as_ref()));
+        self
+    }
+
+    /// Returns a snapshot of every file in the filesystem and its contents.
+    ///
+    /// Directories are not included. Contents that are not valid UTF-8 are converted
+    /// lossily; use [`files`](Memory::files) when exact bytes are needed.
+    pub fn snapshot(&self) -> BTreeMap<PathBuf, String> {
+        self.files()
+            .into_iter()
+            .map(|(path, contents)| (path, String::from_utf8_lossy(&contents).into_owned()))
+            .collect()
+    }
+
+    /// Returns every file in the filesystem along with its raw contents.
+    pub fn files(&self) -> BTreeMap<PathBuf, Vec<u8>> {
+        self.lock()
+            .iter()
+            .filter_map(|(path, entry)| match entry {
+                Entry::File(contents) => Some((path.clone(), contents.clone())),
+                Entry::Dir => None,
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Entry>> {
+        // A poisoned lock only means another test panicked mid-operation; the map itself
+        // is always left in a consistent state, so it is safe to keep using it.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8], mode: WriteMode) -> io::Result<()> {
+        let mut entries = self.lock();
+        check_parent(&entries, path)?;
+        match (entries.get_mut(path), mode) {
+            (Some(Entry::Dir), _) => Err(is_a_directory(path)),
+            (Some(Entry::File(_)), WriteMode::CreateNew) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("file already exists: {}", path.display()),
+            )),
+            (Some(Entry::File(existing)), WriteMode::Append) => {
+                existing.extend_from_slice(contents);
+                Ok(())
+            }
+            (None, WriteMode::Append) => Err(not_found(path)),
+            (_, _) => {
+                entries.insert(path.to_path_buf(), Entry::File(contents.to_vec()));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Clone for Memory {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Mutex::new(self.lock().clone()),
+        }
+    }
+}
+
+/// How [`Memory::write_file`] treats an existing file.
+#[derive(Clone, Copy)]
+enum WriteMode {
+    Truncate,
+    CreateNew,
+    Append,
+}
+
+impl FileSystem for Memory {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.lock().get(&path) {
+            Some(Entry::File(contents)) => Ok(contents.clone()),
+            Some(Entry::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.write_file(&normalize(path), contents, WriteMode::Truncate)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.write_file(&normalize(path), contents, WriteMode::CreateNew)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.write_file(&normalize(path), contents, WriteMode::Append)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut entries = self.lock();
+        let source_is_dir = match entries.get(&from) {
+            Some(entry) => matches!(entry, Entry::Dir),
+            None => return Err(not_found(&from)),
+        };
+        check_parent(&entries, &to)?;
+        if from == to {
+            return Ok(());
+        }
+        if to.starts_with(&from) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot move {} into itself: {}",
+                    from.display(),
+                    to.display()
+                ),
+            ));
+        }
+
+        // Only files replace files, and only empty directories are replaced by directories
+        match (source_is_dir, entries.get(&to)) {
+            (false, Some(Entry::Dir)) => return Err(is_a_directory(&to)),
+            (true, Some(Entry::File(_))) => return Err(not_a_directory(&to)),
+            (true, Some(Entry::Dir)) => {
+                if has_children(&entries, &to) {
+                    return Err(directory_not_empty(&to));
+                }
+                entries.remove(&to);
+            }
+            _ => {}
+        }
+
+        // Move the entry itself along with everything nested beneath it
+        let moved: Vec<PathBuf> = entries
+            .keys()
+            .filter(|path| path.starts_with(&from))
+            .cloned()
+            .collect();
+        for path in moved {
+            let entry = entries.remove(&path).expect("entry was just listed");
+            let suffix = path
+                .strip_prefix(&from)
+                .expect("entry is nested under source");
+            entries.insert(to.join(suffix), entry);
+        }
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.lock();
+        if let Some(existing) = path
+            .ancestors()
+            .find(|ancestor| matches!(entries.get(*ancestor), Some(Entry::File(_))))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("a file exists at {}", existing.display()),
+            ));
+        }
+        insert_dirs(&mut entries, &path);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.lock();
+        match entries.get(&path) {
+            Some(Entry::File(_)) => {
+                entries.remove(&path);
+                Ok(())
+            }
+            Some(Entry::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.lock();
+        match entries.get(&path) {
+            Some(Entry::Dir) => {
+                if has_children(&entries, &path) {
+                    return Err(directory_not_empty(&path));
+                }
+                entries.remove(&path);
+                Ok(())
+            }
+            Some(Entry::File(_)) => Err(not_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let normalized = normalize(path);
+        let entries = self.lock();
+        match entries.get(&normalized) {
+            Some(Entry::Dir) => {}
+            _ if is_root(&normalized) => {}
+            Some(Entry::File(_)) => return Err(not_a_directory(&normalized)),
+            None => return Err(not_found(&normalized)),
+        }
+        Ok(entries
+            .keys()
+            .filter(|entry| entry.parent() == Some(normalized.as_path()))
+            .filter_map(|entry| entry.file_name())
+            .map(|name| path.join(name))
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        is_root(path) || self.lock().contains_key(&normalize(path))
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let path = normalize(path);
+        if is_root(&path) {
+            return Ok(Metadata::dir());
+        }
+        match self.lock().get(&path) {
+            Some(Entry::File(contents)) => Ok(Metadata::file(contents.len() as u64)),
+            Some(Entry::Dir) => Ok(Metadata::dir()),
+            None => Err(not_found(&path)),
+        }
+    }
+}
+
+/// Inserts `path` and all of its ancestors as directories.
+fn insert_dirs(entries: &mut BTreeMap<PathBuf, Entry>, path: &Path) {
+    for ancestor in path.ancestors().filter(|ancestor| !is_root(ancestor)) {
+        entries.entry(ancestor.to_path_buf()).or_insert(Entry::Dir);
+    }
+}
+
+/// Ensures the parent of `path` exists and is a directory.
+fn check_parent(entries: &BTreeMap<PathBuf, Entry>, path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !is_root(parent) => match entries.get(parent) {
+            Some(Entry::Dir) => Ok(()),
+            Some(Entry::File(_)) => Err(not_a_directory(parent)),
+            None => Err(not_found(parent)),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Whether anything is nested beneath `path`.
+fn has_children(entries: &BTreeMap<PathBuf, Entry>, path: &Path) -> bool {
+    entries
+        .keys()
+        .any(|entry| entry != path && entry.starts_with(path))
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no such file or directory: {}", path.display()),
+    )
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::IsADirectory,
+        format!("is a directory: {}", path.display()),
+    )
+}
+
+fn not_a_directory(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotADirectory,
+        format!("not a directory: {}", path.display()),
+    )
+}
+
+fn directory_not_empty(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::DirectoryNotEmpty,
+        format!("directory not empty: {}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{append::Append, generate::Generate, mover::Move, transform::Transform};
+    use crate::{Anvil, Forge};
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_memory_runs_every_builtin_operation() {
+        // Seed the filesystem with an existing module file
+        let fs = Memory::new().with_file("src/models/mod.rs", "pub mod post;\n");
+
+        // Generate a new file in a directory that does not exist yet
+        Generate::new(MockAnvil {
+            content: "pub struct User;\n".to_string(),
+        })
+        .forge_in(&fs, "src/models/user.rs")
+        .unwrap();
+
+        // Append a module declaration to the existing file
+        Append::new(MockAnvil {
+            content: "pub mod user;\n".to_string(),
+        })
+        .forge_in(&fs, "src/models/mod.rs")
+        .unwrap();
+
+        // Transform the generated file and then move it
+        Transform::new(|content| Ok(content.replace("User", "Account")))
+            .forge_in(&fs, "src/models/user.rs")
+            .unwrap();
+        Move::new("src/models/user.rs")
+            .forge_in(&fs, "src/models/account.rs")
+            .unwrap();
+
+        let expected = BTreeMap::from([
+            (
+                PathBuf::from("src/models/account.rs"),
+                "pub struct Account;\n".to_string(),
+            ),
+            (
+                PathBuf::from("src/models/mod.rs"),
+                "pub mod post;\npub mod user;\n".to_string(),
+            ),
+        ]);
+        assert_eq!(fs.snapshot(), expected);
+    }
+
+    #[test]
+    fn test_memory_mirrors_disk_errors() {
+        let fs = Memory::new().with_file("existing.txt", "content");
+
+        // create_new fails on existing files
+        let result = fs.create_new(Path::new("existing.txt"), b"new");
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(io::ErrorKind::AlreadyExists)
+        );
+
+        // append fails on missing files
+        let result = fs.append(Path::new("missing.txt"), b"new");
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+
+        // writes fail when the parent directory is missing
+        let result = fs.write(Path::new("missing/file.txt"), b"content");
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        // renames fail when the source is missing
+        let result = fs.rename(Path::new("missing.txt"), Path::new("other.txt"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_memory_rename_moves_nested_entries() {
+        let fs = Memory::new()
+            .with_file("templates/a.txt", "a")
+            .with_file("templates/nested/b.txt", "b");
+
+        fs.rename(Path::new("templates"), Path::new("moved"))
+            .unwrap();
+
+        assert!(!fs.exists(Path::new("templates")));
+        assert!(fs.metadata(Path::new("moved/nested")).unwrap().is_dir());
+        assert_eq!(
+            fs.read_to_string(Path::new("moved/nested/b.txt")).unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn test_memory_rename_rejects_conflicting_destinations() {
+        let fs = Memory::new()
+            .with_file("a.txt", "a")
+            .with_file("dir/b.txt", "b")
+            .with_file("full/c.txt", "c")
+            .with_dir("empty");
+
+        let result = fs.rename(Path::new("a.txt"), Path::new("dir"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::IsADirectory);
+        let result = fs.rename(Path::new("dir"), Path::new("a.txt"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotADirectory);
+        let result = fs.rename(Path::new("dir"), Path::new("full"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::DirectoryNotEmpty);
+        let result = fs.rename(Path::new("dir"), Path::new("dir/inner"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs.read(Path::new("full/c.txt")).unwrap(), b"c");
+        assert_eq!(fs.read(Path::new("dir/b.txt")).unwrap(), b"b");
+
+        // Empty directories are replaced, just like on disk
+        fs.rename(Path::new("dir"), Path::new("empty")).unwrap();
+        assert_eq!(fs.read(Path::new("empty/b.txt")).unwrap(), b"b");
+    }
+
+    #[test]
+    fn test_memory_remove_dir_requires_empty_directory() {
+        let fs = Memory::new().with_file("dir/file.txt", "content");
+
+        let result = fs.remove_dir(Path::new("dir"));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::DirectoryNotEmpty);
+
+        fs.remove_file(Path::new("dir/file.txt")).unwrap();
+        fs.remove_dir(Path::new("dir")).unwrap();
+        assert!(fs.files().is_empty());
+        assert!(!fs.exists(Path::new("dir")));
+    }
+
+    #[test]
+    fn test_memory_normalizes_paths() {
+        let fs = Memory::new().with_file("./src/lib.rs", "lib");
+
+        assert!(fs.exists(Path::new("src/../src/lib.rs")));
+        assert_eq!(fs.read(Path::new("src/./lib.rs")).unwrap(), b"lib");
+    }
+}
@@ -1,8 +1,14 @@
-use std::path::Path;
-use std::{fs::File, io::BufWriter};
+use std::{
+    ffi::OsString,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 use thiserror::Error;
 
+use crate::fs::{FileSystem, Kept};
+use crate::merge::{merge, Bases};
+use crate::render;
 use crate::Anvil;
 use crate::Forge;
 
@@ -12,6 +18,13 @@ use crate::Forge;
 /// the rendered content. It will create any necessary parent directories automatically,
 /// ensuring that the file can be created even in a nested directory structure.
 ///
+/// The template is rendered in full before the filesystem is touched, so a template
+/// that fails to render never leaves an empty or partial file behind.
+///
+/// By default, generating a file that already exists is an error. An [`Overwrite`] policy
+/// set with [`policy`](Generate::policy) chooses what happens instead, and
+/// [`generate_in`](Generate::generate_in) reports which [`Outcome`] occurred.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -48,6 +61,111 @@ use crate::Forge;
 pub struct Generate<A: Anvil> {
     /// The template to render in the generated file
     template: A,
+    /// What to do when the file already exists
+    policy: Overwrite,
+}
+
+/// What [`Generate`] does when the file it would create already exists.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::generate::{Generate, Outcome, Overwrite};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// let force = std::env::args().any(|arg| arg == "--force");
+/// let policy = if force { Overwrite::Backup } else { Overwrite::SkipIdentical };
+///
+/// match Generate::new(SimpleTemplate).policy(policy).generate("./src/lib.rs") {
+///     Ok(Outcome::Unchanged) => println!("Already up to date"),
+///     Ok(outcome) => println!("{:?}", outcome),
+///     Err(e) => eprintln!("Failed to generate file: {}", e),
+/// }
+/// ```
+#[derive(Default)]
+pub enum Overwrite {
+    /// Fail with an `AlreadyExists` error. This is the default.
+    #[default]
+    Fail,
+    /// Leave the existing file as it is.
+    Skip,
+    /// Replace the existing file with the rendered content.
+    Force,
+    /// Copy the existing file to `<name>.orig`, then replace it with the rendered content.
+    /// Earlier backups are never replaced: if `<name>.orig` exists, the copy goes to
+    /// `<name>.orig.1`, then `<name>.orig.2`, and so on.
+    Backup,
+    /// Do nothing if the existing file already matches the rendered content, and fail
+    /// with an `AlreadyExists` error if it does not.
+    SkipIdentical,
+    /// Ask a callback whether to replace the existing file. Returning `true` overwrites
+    /// the file, while returning `false` skips it. The callback is not consulted when the
+    /// existing file already matches the rendered content.
+    Ask(Box<dyn Fn(&Conflict) -> bool>),
+    /// Three-way merge the user's edits with the rendered content, using the content that
+    /// was generated last time as the common base. Regions that both sides changed are
+    /// written with conflict markers, as described in [`merge`].
+    ///
+    /// Every file generated with this policy has its rendered content recorded in the
+    /// given [`Bases`], ready for the next merge. Merging a file that has no recorded base
+    /// fails with [`GenerateError::MissingBase`].
+    Merge(Bases),
+}
+
+impl fmt::Debug for Overwrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Overwrite::Fail => f.write_str("Fail"),
+            Overwrite::Skip => f.write_str("Skip"),
+            Overwrite::Force => f.write_str("Force"),
+            Overwrite::Backup => f.write_str("Backup"),
+            Overwrite::SkipIdentical => f.write_str("SkipIdentical"),
+            Overwrite::Ask(_) => f.write_str("Ask(..)"),
+            Overwrite::Merge(bases) => f.debug_tuple("Merge").field(bases).finish(),
+        }
+    }
+}
+
+/// An existing file that [`Generate`] would replace, as passed to [`Overwrite::Ask`].
+#[derive(Debug)]
+pub struct Conflict<'a> {
+    /// The path of the existing file
+    pub path: &'a Path,
+    /// The current contents of the file
+    pub existing: &'a [u8],
+    /// The rendered content that would replace it
+    pub rendered: &'a [u8],
+}
+
+/// What [`Generate`] did to the file it was asked to create.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The file did not exist and was created.
+    Created,
+    /// The file existed and was left untouched because of the [`Overwrite`] policy.
+    Skipped,
+    /// The file existed with exactly the rendered content, so nothing was written.
+    Unchanged,
+    /// The file existed and was replaced.
+    Overwritten,
+    /// The file existed, was copied to `backup`, and was then replaced.
+    BackedUp {
+        /// Where the original contents were saved
+        backup: PathBuf,
+    },
+    /// The file existed and the user's edits were merged with the rendered content.
+    Merged {
+        /// The number of regions written with conflict markers
+        conflicts: usize,
+    },
 }
 
 /// Errors that can occur during file generation operations.
@@ -62,7 +180,11 @@ pub enum GenerateError {
 
     /// Error that occurred during template rendering.
     #[error("failed to render template during file generation: {0}")]
-    Template(#[from] Box<dyn std::error::Error>),
+    Template(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Error that occurred because a file could not be merged without a recorded base.
+    #[error("cannot merge {} because no previously generated version was recorded", .0.display())]
+    MissingBase(PathBuf),
 }
 
 impl<A: Anvil> Forge for Generate<A> {
@@ -70,20 +192,24 @@ impl<A: Anvil> Forge for Generate<A> {
 
     /// Generates a new file with content from the template.
     ///
-    /// This method:
-    /// 1. Creates all necessary parent directories
-    /// 2. Creates a new file (fails if it already exists)
-    /// 3. Renders the template into the file
+    /// This method performs all file access through the given [`FileSystem`]:
+    /// 1. Renders the template into memory
+    /// 2. Creates all necessary parent directories
+    /// 3. Creates a new file with the rendered content, applying the [`Overwrite`]
+    ///    policy if it already exists
+    ///
+    /// Use [`generate_in`](Generate::generate_in) to find out which [`Outcome`] occurred.
     ///
     /// # Parameters
     ///
+    /// * `fs` - The filesystem in which the file will be created
     /// * `into` - Path where the new file will be created
     ///
     /// # Returns
     ///
     /// * `Result<(), GenerateError>` - Ok if successful, or an error if:
     ///   - Parent directories couldn't be created
-    ///   - File already exists
+    ///   - File already exists and the policy does not allow replacing it
     ///   - File permissions don't allow writing
     ///   - Template rendering fails
     ///
@@ -91,21 +217,8 @@ impl<A: Anvil> Forge for Generate<A> {
     ///
     /// Returns `GenerateError::StdIo` if there's an IO error (like file already exists),
     /// or `GenerateError::Template` if there's an error during template rendering.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
-        let path = into.as_ref();
-
-        let prefix = path.parent().expect("no parent directory");
-        std::fs::create_dir_all(prefix).map_err(GenerateError::StdIo)?;
-
-        let file = File::create_new(path).map_err(GenerateError::StdIo)?;
-
-        let mut writer = BufWriter::new(file);
-
-        self.template
-            .anvil(&mut writer)
-            .map_err(|e| GenerateError::Template(Box::new(e)))?;
-
-        Ok(())
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.generate_in(fs, into).map(|_| ())
     }
 }
 
@@ -121,13 +234,206 @@ impl<A: Anvil> Generate<A> {
     ///
     /// A new `Generate` instance configured with the provided template.
     pub fn new(template: A) -> Self {
-        Self { template }
+        Self {
+            template,
+            policy: Overwrite::default(),
+        }
+    }
+
+    /// Sets what happens when the file already exists.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy` - The [`Overwrite`] policy to apply
+    ///
+    /// # Returns
+    ///
+    /// The `Generate` instance with the policy applied.
+    pub fn policy(mut self, policy: Overwrite) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Generates the file on the real disk and reports what happened.
+    ///
+    /// This is shorthand for calling [`generate_in`](Generate::generate_in) with
+    /// [`Disk`](crate::fs::Disk).
+    ///
+    /// # Parameters
+    ///
+    /// * `into` - Path where the file will be created
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Outcome, GenerateError>` - The [`Outcome`] if successful, or an error
+    ///   under the same conditions as [`generate_in`](Generate::generate_in).
+    pub fn generate(&self, into: impl AsRef<Path>) -> Result<Outcome, GenerateError> {
+        self.generate_in(&crate::fs::Disk, into)
+    }
+
+    /// Generates the file against the given filesystem and reports what happened.
+    ///
+    /// This method:
+    /// 1. Renders the template into memory
+    /// 2. Creates all necessary parent directories
+    /// 3. Creates the file, or applies the [`Overwrite`] policy if it already exists
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem in which the file will be created
+    /// * `into` - Path where the file will be created
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Outcome, GenerateError>` - The [`Outcome`] if successful, or an error if:
+    ///   - Parent directories couldn't be created
+    ///   - File already exists and the policy does not allow replacing it
+    ///   - File permissions don't allow writing
+    ///   - Template rendering fails
+    pub fn generate_in(
+        &self,
+        fs: &dyn FileSystem,
+        into: impl AsRef<Path>,
+    ) -> Result<Outcome, GenerateError> {
+        let path = into.as_ref();
+
+        let content = render(&self.template).map_err(|e| GenerateError::Template(Box::new(e)))?;
+
+        let prefix = path.parent().expect("no parent directory");
+        fs.create_dir_all(prefix).map_err(GenerateError::StdIo)?;
+
+        self.policy.apply(fs, path, &content)
     }
 }
 
+impl Overwrite {
+    /// Writes `content` to `path`, applying the policy if a file already exists there.
+    ///
+    /// The parent directory of `path` must already exist.
+    pub(crate) fn apply(
+        &self,
+        fs: &dyn FileSystem,
+        path: &Path,
+        content: &[u8],
+    ) -> Result<Outcome, GenerateError> {
+        let is_file = fs.metadata(path).is_ok_and(|m| m.is_file());
+        if !is_file || matches!(self, Overwrite::Fail) {
+            fs.create_new(path, content).map_err(GenerateError::StdIo)?;
+            self.record_base(fs, path, content)?;
+            return Ok(Outcome::Created);
+        }
+
+        if matches!(self, Overwrite::Skip) {
+            fs.keep(path, Kept::Skipped);
+            return Ok(Outcome::Skipped);
+        }
+
+        let existing = fs.read(path).map_err(GenerateError::StdIo)?;
+        if existing == content {
+            self.record_base(fs, path, content)?;
+            fs.keep(path, Kept::Identical);
+            return Ok(Outcome::Unchanged);
+        }
+
+        match self {
+            // Both return before the existing file is read
+            Overwrite::Fail | Overwrite::Skip => unreachable!("{self:?} handled above"),
+            Overwrite::SkipIdentical => Err(GenerateError::StdIo(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("file already exists: {}", path.display()),
+            ))),
+            Overwrite::Ask(ask) => {
+                let conflict = Conflict {
+                    path,
+                    existing: &existing,
+                    rendered: content,
+                };
+                if !ask(&conflict) {
+                    fs.keep(path, Kept::Skipped);
+                    return Ok(Outcome::Skipped);
+                }
+                fs.write(path, content).map_err(GenerateError::StdIo)?;
+                Ok(Outcome::Overwritten)
+            }
+            Overwrite::Force => {
+                fs.write(path, content).map_err(GenerateError::StdIo)?;
+                Ok(Outcome::Overwritten)
+            }
+            Overwrite::Backup => {
+                let backup = back_up(fs, path, &existing).map_err(GenerateError::StdIo)?;
+                fs.write(path, content).map_err(GenerateError::StdIo)?;
+                Ok(Outcome::BackedUp { backup })
+            }
+            Overwrite::Merge(bases) => {
+                let base = bases
+                    .load(fs, path)
+                    .map_err(GenerateError::StdIo)?
+                    .ok_or_else(|| GenerateError::MissingBase(path.to_path_buf()))?;
+                let merged = merge(
+                    &text(base)?,
+                    &text(existing.clone())?,
+                    &text(content.to_vec())?,
+                );
+                if merged.text().as_bytes() != existing {
+                    fs.write(path, merged.text().as_bytes())
+                        .map_err(GenerateError::StdIo)?;
+                }
+                bases
+                    .store(fs, path, content)
+                    .map_err(GenerateError::StdIo)?;
+                Ok(Outcome::Merged {
+                    conflicts: merged.conflicts(),
+                })
+            }
+        }
+    }
+
+    /// Records the written content as the base for future merges, if merging is enabled.
+    fn record_base(
+        &self,
+        fs: &dyn FileSystem,
+        path: &Path,
+        content: &[u8],
+    ) -> Result<(), GenerateError> {
+        match self {
+            Overwrite::Merge(bases) => bases.store(fs, path, content).map_err(GenerateError::StdIo),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Converts file contents to text for merging.
+fn text(contents: Vec<u8>) -> Result<String, GenerateError> {
+    String::from_utf8(contents)
+        .map_err(|e| GenerateError::StdIo(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+/// Saves `existing`, the original contents of `path`, to the first backup path that is
+/// free, and returns that path.
+fn back_up(fs: &dyn FileSystem, path: &Path, existing: &[u8]) -> std::io::Result<PathBuf> {
+    let name = path.file_name().map(OsString::from).unwrap_or_default();
+    for attempt in 0.. {
+        let mut backup = name.clone();
+        backup.push(".orig");
+        if attempt > 0 {
+            backup.push(format!(".{attempt}"));
+        }
+        let backup = path.with_file_name(backup);
+        match fs.create_new(&backup, existing) {
+            Ok(()) => return Ok(backup),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("the attempts never run out")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+    use std::fs::File;
     use std::io::Read;
     use tempfile::tempdir;
 
@@ -231,5 +537,197 @@ mod tests {
             Err(GenerateError::Template(err)) => assert_eq!(err.to_string(), "Template error"),
             other => unreachable!("Expected Template error but got: {:?}", other),
         }
+
+        // Nothing should have been written
+        assert!(!file_path.exists());
+    }
+
+    #[cfg(feature = "memory")]
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_fails_on_existing_file_by_default() {
+        let fs = Memory::new().with_file("file.txt", "existing");
+
+        let result = Generate::new(mock("new")).generate_in(&fs, "file.txt");
+
+        match result {
+            Err(GenerateError::StdIo(err)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists)
+            }
+            other => unreachable!("Expected StdIo error but got: {:?}", other),
+        }
+        assert_eq!(fs.read(Path::new("file.txt")).unwrap(), b"existing");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_policies_report_outcome() {
+        let fs = Memory::new()
+            .with_file("skip.txt", "existing")
+            .with_file("force.txt", "existing")
+            .with_file("same.txt", "new")
+            .with_file("different.txt", "existing");
+
+        let outcome = |policy, path| {
+            Generate::new(mock("new"))
+                .policy(policy)
+                .generate_in(&fs, path)
+        };
+
+        assert_eq!(
+            outcome(Overwrite::Skip, "skip.txt").unwrap(),
+            Outcome::Skipped
+        );
+        assert_eq!(
+            outcome(Overwrite::Skip, "created.txt").unwrap(),
+            Outcome::Created
+        );
+        assert_eq!(
+            outcome(Overwrite::Force, "force.txt").unwrap(),
+            Outcome::Overwritten
+        );
+        assert_eq!(
+            outcome(Overwrite::SkipIdentical, "same.txt").unwrap(),
+            Outcome::Unchanged
+        );
+        assert!(outcome(Overwrite::SkipIdentical, "different.txt").is_err());
+
+        let snapshot = fs.snapshot();
+        assert_eq!(snapshot[Path::new("skip.txt")], "existing");
+        assert_eq!(snapshot[Path::new("force.txt")], "new");
+        assert_eq!(snapshot[Path::new("different.txt")], "existing");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_backup_keeps_original_contents() {
+        let fs = Memory::new().with_file("src/lib.rs", "hand written");
+
+        let outcome = Generate::new(mock("generated"))
+            .policy(Overwrite::Backup)
+            .generate_in(&fs, "src/lib.rs")
+            .unwrap();
+
+        assert_eq!(
+            outcome,
+            Outcome::BackedUp {
+                backup: PathBuf::from("src/lib.rs.orig")
+            }
+        );
+        let snapshot = fs.snapshot();
+        assert_eq!(snapshot[Path::new("src/lib.rs")], "generated");
+        assert_eq!(snapshot[Path::new("src/lib.rs.orig")], "hand written");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_backup_never_replaces_earlier_backups() {
+        let fs = Memory::new().with_file("src/lib.rs", "hand written");
+        let generate = |content| {
+            Generate::new(mock(content))
+                .policy(Overwrite::Backup)
+                .generate_in(&fs, "src/lib.rs")
+                .unwrap()
+        };
+
+        generate("first");
+        // The second run backs up the first generated file next to the original
+        assert_eq!(
+            generate("second"),
+            Outcome::BackedUp {
+                backup: PathBuf::from("src/lib.rs.orig.1")
+            }
+        );
+
+        let snapshot = fs.snapshot();
+        assert_eq!(snapshot[Path::new("src/lib.rs")], "second");
+        assert_eq!(snapshot[Path::new("src/lib.rs.orig")], "hand written");
+        assert_eq!(snapshot[Path::new("src/lib.rs.orig.1")], "first");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_ask_receives_conflict() {
+        let fs = Memory::new()
+            .with_file("keep.txt", "existing")
+            .with_file("replace.txt", "existing");
+
+        // Only agree to replace one of the two files
+        let policy = || {
+            Overwrite::Ask(Box::new(|conflict: &Conflict| {
+                assert_eq!(conflict.existing, b"existing");
+                assert_eq!(conflict.rendered, b"new");
+                conflict.path == Path::new("replace.txt")
+            }))
+        };
+
+        let generate = |path| {
+            Generate::new(mock("new"))
+                .policy(policy())
+                .generate_in(&fs, path)
+                .unwrap()
+        };
+
+        assert_eq!(generate("keep.txt"), Outcome::Skipped);
+        assert_eq!(generate("replace.txt"), Outcome::Overwritten);
+
+        let snapshot = fs.snapshot();
+        assert_eq!(snapshot[Path::new("keep.txt")], "existing");
+        assert_eq!(snapshot[Path::new("replace.txt")], "new");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_merge_keeps_user_edits() {
+        let fs = Memory::new();
+        let bases = || Overwrite::Merge(Bases::new(".anvil/bases"));
+
+        // The first run creates the file and records what was generated
+        let outcome = Generate::new(mock("use a;\n\nfn v1() {}\n"))
+            .policy(bases())
+            .generate_in(&fs, "src/lib.rs")
+            .unwrap();
+        assert_eq!(outcome, Outcome::Created);
+
+        // The user edits the file, then the template changes
+        fs.write(Path::new("src/lib.rs"), b"use a;\nuse b;\n\nfn v1() {}\n")
+            .unwrap();
+        let outcome = Generate::new(mock("use a;\n\nfn v2() {}\n"))
+            .policy(bases())
+            .generate_in(&fs, "src/lib.rs")
+            .unwrap();
+
+        assert_eq!(outcome, Outcome::Merged { conflicts: 0 });
+        let snapshot = fs.snapshot();
+        assert_eq!(
+            snapshot[Path::new("src/lib.rs")],
+            "use a;\nuse b;\n\nfn v2() {}\n"
+        );
+        assert_eq!(
+            snapshot[Path::new(".anvil/bases/src/lib.rs")],
+            "use a;\n\nfn v2() {}\n"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_merge_requires_recorded_base() {
+        let fs = Memory::new().with_file("lib.rs", "hand written\n");
+
+        let result = Generate::new(mock("generated\n"))
+            .policy(Overwrite::Merge(Bases::new(".anvil/bases")))
+            .generate_in(&fs, "lib.rs");
+
+        match result {
+            Err(GenerateError::MissingBase(path)) => assert_eq!(path, PathBuf::from("lib.rs")),
+            other => unreachable!("Expected MissingBase error but got: {:?}", other),
+        }
+        assert_eq!(fs.read(Path::new("lib.rs")).unwrap(), b"hand written\n");
     }
 }
@@ -0,0 +1,546 @@
+use std::{
+    error::Error,
+    path::{Component, Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    embed::Source,
+    fs::{Disk, FileSystem},
+    generate::{GenerateError, Outcome, Overwrite},
+    Forge,
+};
+
+/// A template engine that renders template source text at runtime.
+///
+/// [`Anvil`](crate::Anvil) renders one template that is known ahead of time, but
+/// [`GenerateDir`] discovers its templates by walking a directory, so it needs an engine
+/// that can render arbitrary source. Each backend crate provides a `Renderer` that pairs
+/// its engine with the context to render against.
+///
+/// Closures taking the source and returning the rendered text implement `Renderer` too.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::generate_dir::Renderer;
+///
+/// // A tiny renderer that fills in a single placeholder
+/// let renderer = |source: &str| -> Result<String, std::io::Error> {
+///     Ok(source.replace("{{name}}", "user"))
+/// };
+///
+/// assert_eq!(renderer.render("{{name}}_controller.rs").unwrap(), "user_controller.rs");
+/// ```
+pub trait Renderer {
+    /// The error type returned when rendering fails.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Renders the template `source` into text.
+    fn render(&self, source: &str) -> Result<String, Self::Error>;
+}
+
+impl<F, E> Renderer for F
+where
+    F: Fn(&str) -> Result<String, E>,
+    E: Error + Send + Sync + 'static,
+{
+    type Error = E;
+
+    fn render(&self, source: &str) -> Result<String, Self::Error> {
+        self(source)
+    }
+}
+
+/// What [`GenerateDir`] does with files that aren't text, such as images.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Binary {
+    /// Copy the file to the output verbatim. This is the default.
+    #[default]
+    Copy,
+    /// Leave the file out of the output.
+    Skip,
+}
+
+/// Errors that can occur while generating a directory tree.
+#[derive(Error, Debug)]
+pub enum GenerateDirError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while generating directory: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// Error that occurred while rendering a template or a templated path.
+    #[error("failed to render template {}: {source}", path.display())]
+    Template {
+        /// The template, relative to the template directory
+        path: PathBuf,
+        /// The error the renderer failed with
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Error that occurred because a file could not be merged without a recorded base.
+    #[error("cannot merge {} because no previously generated version was recorded", .0.display())]
+    MissingBase(PathBuf),
+}
+
+/// Every file that a [`GenerateDir`] operation produced, and what happened to each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Generated {
+    /// The output files and their outcomes, in the order they were generated
+    files: Vec<(PathBuf, Outcome)>,
+}
+
+impl Generated {
+    /// Returns every output file and its [`Outcome`], in the order they were generated.
+    pub fn files(&self) -> &[(PathBuf, Outcome)] {
+        &self.files
+    }
+
+    /// Returns the output files whose contents were written, leaving out files that were
+    /// skipped or already up to date.
+    pub fn written(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, outcome)| !matches!(outcome, Outcome::Skipped | Outcome::Unchanged))
+            .map(|(path, _)| path.as_path())
+    }
+}
+
+/// A file that is ready to be written.
+struct Output {
+    /// The path of the template, relative to the template directory
+    template: PathBuf,
+    /// Where the file will be written
+    path: PathBuf,
+    /// The rendered or copied contents
+    contents: Vec<u8>,
+    /// The permission bits to give the file, if the template records them
+    mode: Option<u32>,
+}
+
+/// A struct that generates a whole directory tree from a directory of templates.
+///
+/// `GenerateDir` walks its template directory and mirrors it into the path it is forged
+/// into. Every text file is rendered with a [`Renderer`], and every file and directory name
+/// is rendered too, so a template at `{{name}}/{{name}}_controller.rs` produces
+/// `user/user_controller.rs` when `name` is `user`. A name that renders to nothing is left
+/// out of the output along with everything beneath it, which makes it easy to generate
+/// files conditionally.
+///
+/// Files that aren't valid UTF-8 are treated as binary and copied verbatim, or skipped,
+/// depending on the [`Binary`] setting. Paths matching an [`ignore`](GenerateDir::ignore)
+/// pattern are left out entirely.
+///
+/// Every template is rendered before anything is written, so a template error leaves the
+/// output untouched. Existing files are handled by an [`Overwrite`] policy, just as they are
+/// by [`Generate`](crate::generate::Generate).
+///
+/// The template directory can be a path, which is read from the same filesystem the output
+/// is written to, or a directory embedded into the binary with
+/// [`embed_dir!`](crate::embed_dir). Embedded files keep their permissions, so executable
+/// scripts stay executable.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate_dir::GenerateDir};
+///
+/// let renderer = |source: &str| -> Result<String, std::io::Error> {
+///     Ok(source.replace("{{name}}", "user"))
+/// };
+///
+/// let generated = GenerateDir::new("./templates/resource", renderer)
+///     .ignore("*.bak")
+///     .generate("./src")
+///     .unwrap();
+///
+/// for path in generated.written() {
+///     println!("created {}", path.display());
+/// }
+/// ```
+pub struct GenerateDir<R: Renderer> {
+    /// The directory of templates to generate from
+    source: Source,
+    /// The engine that renders file contents and names
+    renderer: R,
+    /// Patterns for template paths to leave out
+    ignore: Vec<String>,
+    /// What to do with binary files
+    binary: Binary,
+    /// What to do when an output file already exists
+    policy: Overwrite,
+}
+
+impl<R: Renderer> GenerateDir<R> {
+    /// Creates a new `GenerateDir` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `source` - The directory of templates to generate from, either a path or an
+    ///   embedded [`Source`]
+    /// * `renderer` - The [`Renderer`] used for file contents and names
+    ///
+    /// # Returns
+    ///
+    /// A new `GenerateDir` instance that copies binary files, ignores nothing and fails
+    /// if an output file already exists.
+    pub fn new(source: impl Into<Source>, renderer: R) -> Self {
+        Self {
+            source: source.into(),
+            renderer,
+            ignore: Vec::new(),
+            binary: Binary::default(),
+            policy: Overwrite::default(),
+        }
+    }
+
+    /// Leaves out template paths matching `pattern`.
+    ///
+    /// A pattern without a `/` is matched against each file and directory name, so `.git`
+    /// or `*.bak` match at any depth. A pattern with a `/` is matched against the whole
+    /// path relative to the template directory. In both cases `*` matches any run of
+    /// characters within a name and `?` matches a single character. Ignored directories
+    /// are not descended into.
+    ///
+    /// # Parameters
+    ///
+    /// * `pattern` - The pattern to ignore
+    ///
+    /// # Returns
+    ///
+    /// The `GenerateDir` instance with the pattern added.
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    /// Sets what happens to files that aren't text.
+    ///
+    /// # Parameters
+    ///
+    /// * `binary` - Whether to [`Copy`](Binary::Copy) or [`Skip`](Binary::Skip) binary files
+    ///
+    /// # Returns
+    ///
+    /// The `GenerateDir` instance with the setting applied.
+    pub fn binary(mut self, binary: Binary) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Sets what happens when an output file already exists.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy` - The [`Overwrite`] policy to apply to each output file
+    ///
+    /// # Returns
+    ///
+    /// The `GenerateDir` instance with the policy applied.
+    pub fn policy(mut self, policy: Overwrite) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Generates the directory tree on the real disk.
+    ///
+    /// This is shorthand for calling [`generate_in`](GenerateDir::generate_in) with
+    /// [`Disk`].
+    ///
+    /// # Parameters
+    ///
+    /// * `into` - The directory to generate the tree into
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Generated, GenerateDirError>` - Every output file and its outcome, or an
+    ///   error under the same conditions as [`generate_in`](GenerateDir::generate_in).
+    pub fn generate(&self, into: impl AsRef<Path>) -> Result<Generated, GenerateDirError> {
+        self.generate_in(&Disk, into)
+    }
+
+    /// Generates the directory tree against the given filesystem.
+    ///
+    /// This method:
+    /// 1. Walks the template directory, skipping ignored paths
+    /// 2. Renders every path and text file, or reads binary files as they are
+    /// 3. Creates each output file, applying the [`Overwrite`] policy if it already exists
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to write output to, and to read templates from unless they
+    ///   are embedded
+    /// * `into` - The directory to generate the tree into
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Generated, GenerateDirError>` - Every output file and its outcome, or an
+    ///   error if:
+    ///   - The template directory can't be read
+    ///   - A template or templated name fails to render
+    ///   - An output file exists and the policy does not allow replacing it
+    pub fn generate_in(
+        &self,
+        fs: &dyn FileSystem,
+        into: impl AsRef<Path>,
+    ) -> Result<Generated, GenerateDirError> {
+        let mut outputs = Vec::new();
+        let source = self.source.filesystem(fs);
+        self.collect(source, Path::new(""), into.as_ref(), &mut outputs)?;
+
+        let mut files = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            if let Some(parent) = output.path.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            let outcome = self
+                .policy
+                .apply(fs, &output.path, &output.contents)
+                .map_err(|e| match e {
+                    GenerateError::StdIo(e) => GenerateDirError::StdIo(e),
+                    GenerateError::MissingBase(path) => GenerateDirError::MissingBase(path),
+                    GenerateError::Template(source) => GenerateDirError::Template {
+                        path: output.template.clone(),
+                        source,
+                    },
+                })?;
+            if let Some(mode) = output.mode {
+                if !matches!(outcome, Outcome::Skipped | Outcome::Unchanged) {
+                    fs.set_mode(&output.path, mode)?;
+                }
+            }
+            files.push((output.path, outcome));
+        }
+        Ok(Generated { files })
+    }
+
+    /// Renders everything beneath the template at `relative`, read from `fs`, into
+    /// `outputs`, writing to `into`.
+    fn collect(
+        &self,
+        fs: &dyn FileSystem,
+        relative: &Path,
+        into: &Path,
+        outputs: &mut Vec<Output>,
+    ) -> Result<(), GenerateDirError> {
+        for entry in fs.read_dir(&self.source.path().join(relative))? {
+            let name = entry.file_name().expect("directory entries have names");
+            let template = relative.join(name);
+            if self.is_ignored(&template) {
+                continue;
+            }
+
+            let rendered = self.render(&template, &name.to_string_lossy())?;
+            if rendered.is_empty() {
+                continue;
+            }
+            let mut components = Path::new(&rendered).components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) {
+                return Err(GenerateDirError::StdIo(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} renders to {rendered:?}, which is not a single file name",
+                        template.display()
+                    ),
+                )));
+            }
+            let path = into.join(&rendered);
+
+            if fs.metadata(&entry)?.is_dir() {
+                self.collect(fs, &template, &path, outputs)?;
+                continue;
+            }
+
+            let contents = fs.read(&entry)?;
+            let contents = match String::from_utf8(contents) {
+                Ok(text) => self.render(&template, &text)?.into_bytes(),
+                Err(_) if self.binary == Binary::Skip => continue,
+                Err(e) => e.into_bytes(),
+            };
+            outputs.push(Output {
+                template,
+                path,
+                contents,
+                mode: self.source.mode(&entry),
+            });
+        }
+        Ok(())
+    }
+
+    /// Renders `source` from the template at `template`, which is only used for errors.
+    fn render(&self, template: &Path, source: &str) -> Result<String, GenerateDirError> {
+        let rendered = self
+            .renderer
+            .render(source)
+            .map_err(|e| GenerateDirError::Template {
+                path: template.to_path_buf(),
+                source: Box::new(e),
+            })?;
+        Ok(rendered)
+    }
+
+    fn is_ignored(&self, template: &Path) -> bool {
+        let path = template.to_string_lossy().replace('\\', "/");
+        self.ignore.iter().any(|pattern| {
+            if pattern.contains('/') {
+                matches(pattern.trim_matches('/').as_bytes(), path.as_bytes())
+            } else {
+                template.components().any(|component| match component {
+                    Component::Normal(name) => {
+                        matches(pattern.as_bytes(), name.to_string_lossy().as_bytes())
+                    }
+                    _ => false,
+                })
+            }
+        })
+    }
+}
+
+/// Returns `true` if `text` matches the wildcard `pattern`, where `*` matches any run of
+/// characters other than `/` and `?` matches any single character other than `/`.
+fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches(&pattern[1..], text)
+                || (text.first().is_some_and(|&c| c != b'/') && matches(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(&c)) if c != b'/' => matches(&pattern[1..], &text[1..]),
+        (Some(p), Some(c)) if p == c => matches(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+impl<R: Renderer> Forge for GenerateDir<R> {
+    type Error = GenerateDirError;
+
+    /// Generates the directory tree into the given directory.
+    ///
+    /// Use [`generate_in`](GenerateDir::generate_in) to find out which files were written.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to write output to, and to read templates from unless they
+    ///   are embedded
+    /// * `into` - The directory to generate the tree into
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), GenerateDirError>` - Ok if successful, or an error if:
+    ///   - The template directory can't be read
+    ///   - A template or templated name fails to render
+    ///   - An output file exists and the policy does not allow replacing it
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.generate_in(fs, into).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+
+    #[cfg(feature = "memory")]
+    fn renderer(source: &str) -> Result<String, std::io::Error> {
+        if source.contains("{{broken") {
+            return Err(std::io::Error::other("unclosed tag"));
+        }
+        Ok(source
+            .replace("{{name}}", "user")
+            .replace("{{optional}}", ""))
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_dir_renders_contents_and_names() {
+        let fs = Memory::new()
+            .with_file(
+                "templates/{{name}}/mod.rs",
+                "pub mod {{name}}_controller;\n",
+            )
+            .with_file(
+                "templates/{{name}}/{{name}}_controller.rs",
+                "struct {{name}};\n",
+            )
+            .with_file("templates/{{optional}}/skipped.rs", "skipped")
+            .with_file("templates/logo.png", [0xff, 0xd8, b'{', b'{'])
+            .with_file("templates/notes.bak", "backup")
+            .with_file("templates/.git/HEAD", "ref");
+
+        let generated = GenerateDir::new("templates", renderer)
+            .ignore("*.bak")
+            .ignore(".git")
+            .generate_in(&fs, "out")
+            .unwrap();
+
+        let written: Vec<_> = generated.written().collect();
+        assert_eq!(
+            written,
+            vec![
+                Path::new("out/logo.png"),
+                Path::new("out/user/mod.rs"),
+                Path::new("out/user/user_controller.rs"),
+            ]
+        );
+        assert_eq!(
+            fs.read_to_string(Path::new("out/user/user_controller.rs"))
+                .unwrap(),
+            "struct user;\n"
+        );
+        // Binary files are copied verbatim
+        assert_eq!(
+            fs.read(Path::new("out/logo.png")).unwrap(),
+            [0xff, 0xd8, b'{', b'{']
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_dir_writes_nothing_on_template_error() {
+        let fs = Memory::new()
+            .with_file("templates/a.rs", "fine")
+            .with_file("templates/b.rs", "{{broken");
+
+        let result = GenerateDir::new("templates", renderer).generate_in(&fs, "out");
+
+        assert!(
+            matches!(result, Err(GenerateDirError::Template { path, .. }) if path == Path::new("b.rs"))
+        );
+        assert!(!fs.exists(Path::new("out")));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_generate_dir_skips_binary_files_and_applies_policy() {
+        let fs = Memory::new()
+            .with_file("templates/logo.png", [0xff])
+            .with_file("templates/a.rs", "{{name}}")
+            .with_file("out/a.rs", "existing");
+
+        let generated = GenerateDir::new("templates", renderer)
+            .binary(Binary::Skip)
+            .policy(Overwrite::Skip)
+            .generate_in(&fs, "out")
+            .unwrap();
+
+        assert_eq!(
+            generated.files(),
+            [(PathBuf::from("out/a.rs"), Outcome::Skipped)]
+        );
+        assert!(!fs.exists(Path::new("out/logo.png")));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        assert!(matches(b"*.bak", b"notes.bak"));
+        assert!(matches(b"src/*.rs", b"src/lib.rs"));
+        assert!(!matches(b"src/*.rs", b"src/nested/lib.rs"));
+        assert!(matches(b"?.txt", b"a.txt"));
+        assert!(!matches(b"?.txt", b"ab.txt"));
+    }
+}
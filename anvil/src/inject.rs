@@ -0,0 +1,419 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+pub use regex::Regex;
+
+use crate::{fs::FileSystem, render, Anvil, Forge};
+
+/// Where [`Inject`] inserts rendered content inside an existing file.
+///
+/// Locations are line-based: the rendered content is always inserted on lines of its own,
+/// either directly before or directly after the line that the location identifies. This
+/// keeps the indentation of the surrounding code intact.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::inject::{Location, Regex};
+///
+/// // Before the line containing a marker comment
+/// let routes = Location::Before("// anvil:routes".to_string());
+///
+/// // After the last line declaring a module
+/// let modules = Location::AfterLast(Regex::new(r"(?m)^(pub )?mod \w+;").unwrap());
+///
+/// // After a line tagged with `anvil:imports`
+/// let imports = Location::Anchor("imports".to_string());
+/// ```
+#[derive(Debug, Clone)]
+pub enum Location {
+    /// Before the first line containing the literal marker.
+    Before(String),
+    /// After the first line containing the literal marker.
+    After(String),
+    /// Before the line where the first match of the pattern starts.
+    BeforeFirst(Regex),
+    /// After the line where the first match of the pattern ends.
+    AfterFirst(Regex),
+    /// Before the line where the last match of the pattern starts.
+    BeforeLast(Regex),
+    /// After the line where the last match of the pattern ends.
+    AfterLast(Regex),
+    /// At the given line number, counting from 1. The content ends up starting on that
+    /// line, pushing the existing line down. One past the last line appends to the file.
+    Line(usize),
+    /// After the first line tagged with `anvil:<name>`, typically inside a comment such as
+    /// `// anvil:routes` or `# anvil:dependencies`.
+    Anchor(String),
+}
+
+impl Location {
+    /// Returns the byte offset in `text` at which content should be inserted, or `None` if
+    /// the location cannot be found.
+    fn offset(&self, text: &str) -> Option<usize> {
+        match self {
+            Location::Before(marker) => text.find(marker.as_str()).map(|p| line_start(text, p)),
+            Location::After(marker) => text
+                .find(marker.as_str())
+                .map(|p| line_end(text, last_byte(p, p + marker.len()))),
+            Location::BeforeFirst(pattern) => {
+                pattern.find(text).map(|m| line_start(text, m.start()))
+            }
+            Location::AfterFirst(pattern) => pattern
+                .find(text)
+                .map(|m| line_end(text, last_byte(m.start(), m.end()))),
+            Location::BeforeLast(pattern) => pattern
+                .find_iter(text)
+                .last()
+                .map(|m| line_start(text, m.start())),
+            Location::AfterLast(pattern) => pattern
+                .find_iter(text)
+                .last()
+                .map(|m| line_end(text, last_byte(m.start(), m.end()))),
+            Location::Line(number) => {
+                let index = number.checked_sub(1)?;
+                std::iter::once(0)
+                    .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+                    .filter(|&start| start < text.len())
+                    .nth(index)
+                    // One past the last line is the end of the file
+                    .or_else(|| (index == text.lines().count()).then_some(text.len()))
+            }
+            Location::Anchor(name) => {
+                let tag = Regex::new(&format!(r"anvil:{}\b", regex::escape(name)))
+                    .expect("escaped anchor names are valid patterns");
+                tag.find(text)
+                    .map(|m| line_end(text, last_byte(m.start(), m.end())))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Before(marker) => write!(f, "marker {:?}", marker),
+            Location::After(marker) => write!(f, "marker {:?}", marker),
+            Location::BeforeFirst(pattern)
+            | Location::AfterFirst(pattern)
+            | Location::BeforeLast(pattern)
+            | Location::AfterLast(pattern) => write!(f, "pattern {:?}", pattern.as_str()),
+            Location::Line(number) => write!(f, "line {}", number),
+            Location::Anchor(name) => write!(f, "anchor \"anvil:{}\"", name),
+        }
+    }
+}
+
+/// Returns the offset of the start of the line containing `pos`.
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Returns the offset just past the end of the line containing `pos`, including its newline.
+fn line_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i + 1)
+}
+
+/// Returns the offset of the last byte in `start..end`, or `start` if the range is empty.
+fn last_byte(start: usize, end: usize) -> usize {
+    if end > start {
+        end - 1
+    } else {
+        start
+    }
+}
+
+/// Errors that can occur during inject operations.
+///
+/// This enum represents the different types of errors that can occur when
+/// injecting content into a file using the [`Inject`] struct.
+#[derive(Error, Debug)]
+pub enum InjectError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while injecting content: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// Error that occurred during template rendering.
+    #[error("failed to render template during inject operation: {0}")]
+    Template(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    /// The location to inject at does not exist in the file.
+    #[error("could not find {location} in {}", path.display())]
+    NotFound {
+        /// A description of the location that was searched for
+        location: String,
+        /// The file that was searched
+        path: PathBuf,
+    },
+}
+
+/// A struct that inserts template content at a specific location inside an existing file.
+///
+/// `Inject` takes an [`Anvil`] implementation (template) and a [`Location`], and inserts the
+/// rendered content on its own lines at that location. The file must already exist, and the
+/// operation fails with [`InjectError::NotFound`] if the location cannot be found in it,
+/// leaving the file untouched.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Anvil, Forge, inject::{Inject, Location}};
+/// use std::io::Write;
+///
+/// // Define a simple template
+/// struct Route {
+///     path: String,
+/// }
+///
+/// impl Anvil for Route {
+///     type Error = std::io::Error;
+///
+///     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+///         writeln!(writer, "    .route(\"{}\", get(handler))", self.path)
+///     }
+/// }
+///
+/// let inject = Inject::new(
+///     Route { path: "/users".to_string() },
+///     Location::Before("// anvil:routes".to_string()),
+/// );
+///
+/// match inject.forge("./src/router.rs") {
+///     Ok(_) => println!("Route added"),
+///     Err(e) => eprintln!("Failed to add route: {}", e),
+/// }
+/// ```
+pub struct Inject<A: Anvil> {
+    /// The template to render and insert
+    template: A,
+    /// Where in the file to insert the rendered content
+    location: Location,
+}
+
+impl<A: Anvil> Forge for Inject<A> {
+    type Error = InjectError;
+
+    /// Inserts the template content at the configured location in an existing file.
+    ///
+    /// This method performs all file access through the given [`FileSystem`]:
+    /// 1. Renders the template into memory
+    /// 2. Reads the existing file and finds the location
+    /// 3. Writes the file back with the rendered content inserted
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem containing the file
+    /// * `into` - Path to the file where content will be inserted
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), InjectError>` - Ok if successful, or an error if:
+    ///   - The file doesn't exist or isn't valid UTF-8
+    ///   - The location can't be found in the file
+    ///   - Template rendering fails
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref();
+
+        let rendered = render(&self.template).map_err(|e| InjectError::Template(Box::new(e)))?;
+        let rendered = String::from_utf8(rendered).map_err(|e| {
+            InjectError::StdIo(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })?;
+
+        let content = fs.read_to_string(path).map_err(InjectError::StdIo)?;
+
+        let offset = self
+            .location
+            .offset(&content)
+            .ok_or_else(|| InjectError::NotFound {
+                location: self.location.to_string(),
+                path: path.to_path_buf(),
+            })?;
+
+        let injected = insert_lines(&content, offset, &rendered);
+        fs.write(path, injected.as_bytes())
+            .map_err(InjectError::StdIo)?;
+
+        Ok(())
+    }
+}
+
+/// Inserts `lines` into `content` at `offset`, keeping them on lines of their own.
+fn insert_lines(content: &str, offset: usize, lines: &str) -> String {
+    let mut injected = String::with_capacity(content.len() + lines.len() + 2);
+    injected.push_str(&content[..offset]);
+    if !lines.is_empty() {
+        if !injected.is_empty() && !injected.ends_with('\n') {
+            injected.push('\n');
+        }
+        injected.push_str(lines);
+        if !lines.ends_with('\n') {
+            injected.push('\n');
+        }
+    }
+    injected.push_str(&content[offset..]);
+    injected
+}
+
+impl<A: Anvil> Inject<A> {
+    /// Creates a new `Inject` instance with the given template and location.
+    ///
+    /// # Parameters
+    ///
+    /// * `template` - An implementation of the [`Anvil`] trait that will be rendered
+    ///   and inserted into the target file.
+    /// * `location` - Where in the file to insert the rendered content.
+    ///
+    /// # Returns
+    ///
+    /// A new `Inject` instance configured with the provided template and location.
+    pub fn new(template: A, location: Location) -> Self {
+        Self { template, location }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    const ROUTER: &str = "\
+mod health;
+mod users;
+
+fn router() {
+    Router::new()
+        // anvil:routes
+}
+";
+
+    #[cfg(feature = "memory")]
+    fn inject(content: &str, location: Location) -> Result<String, InjectError> {
+        let fs = Memory::new().with_file("router.rs", ROUTER);
+        let template = MockAnvil {
+            content: content.to_string(),
+        };
+        Inject::new(template, location).forge_in(&fs, "router.rs")?;
+        Ok(fs.read_to_string(Path::new("router.rs")).unwrap())
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_inject_around_literal_marker() {
+        let before = inject(
+            "        .route(\"/\", get(index))\n",
+            Location::Before("// anvil:routes".to_string()),
+        )
+        .unwrap();
+        assert!(before.contains("        .route(\"/\", get(index))\n        // anvil:routes\n"));
+
+        // Content without a trailing newline still ends up on its own line
+        let after = inject(
+            "}\nfn extra() {",
+            Location::After("Router::new()".to_string()),
+        )
+        .unwrap();
+        assert!(after.contains("Router::new()\n}\nfn extra() {\n        // anvil:routes"));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_inject_around_regex_matches() {
+        let modules = Regex::new(r"(?m)^mod \w+;").unwrap();
+
+        let after_last = inject("mod posts;\n", Location::AfterLast(modules.clone())).unwrap();
+        assert!(after_last.starts_with("mod health;\nmod users;\nmod posts;\n\n"));
+
+        let before_first = inject("mod auth;\n", Location::BeforeFirst(modules.clone())).unwrap();
+        assert!(before_first.starts_with("mod auth;\nmod health;\n"));
+
+        let after_first = inject("mod auth;\n", Location::AfterFirst(modules.clone())).unwrap();
+        assert!(after_first.starts_with("mod health;\nmod auth;\nmod users;\n"));
+
+        let before_last = inject("mod auth;\n", Location::BeforeLast(modules)).unwrap();
+        assert!(before_last.starts_with("mod health;\nmod auth;\nmod users;\n"));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_inject_at_line_number() {
+        let first = inject("// header\n", Location::Line(1)).unwrap();
+        assert!(first.starts_with("// header\nmod health;\n"));
+
+        let second = inject("mod auth;\n", Location::Line(2)).unwrap();
+        assert!(second.starts_with("mod health;\nmod auth;\nmod users;\n"));
+
+        // One past the last line appends to the file
+        let end = inject("// footer\n", Location::Line(8)).unwrap();
+        assert!(end.ends_with("}\n// footer\n"));
+
+        // Further past the end, or line zero, cannot be found
+        assert!(matches!(
+            inject("", Location::Line(9)),
+            Err(InjectError::NotFound { .. })
+        ));
+        assert!(matches!(
+            inject("", Location::Line(0)),
+            Err(InjectError::NotFound { .. })
+        ));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_inject_after_named_anchor() {
+        let result = inject(
+            "        .route(\"/\", get(index))\n",
+            Location::Anchor("routes".to_string()),
+        )
+        .unwrap();
+        assert!(result.ends_with("// anvil:routes\n        .route(\"/\", get(index))\n}\n"));
+
+        // Anchor names must match exactly
+        let result = inject("", Location::Anchor("route".to_string()));
+        match result {
+            Err(InjectError::NotFound { location, path }) => {
+                assert_eq!(location, "anchor \"anvil:route\"");
+                assert_eq!(path, PathBuf::from("router.rs"));
+            }
+            other => unreachable!("Expected NotFound error but got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_inject_on_disk_leaves_file_untouched_when_marker_missing() {
+        // Create a temporary file with some initial content
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"fn main() {}\n").unwrap();
+
+        let template = MockAnvil {
+            content: "use std::io;\n".to_string(),
+        };
+        let result = Inject::new(template, Location::Before("// missing".to_string()))
+            .forge(temp_file.path());
+        assert!(matches!(result, Err(InjectError::NotFound { .. })));
+
+        let content = std::fs::read_to_string(temp_file.path()).unwrap();
+        assert_eq!(content, "fn main() {}\n");
+    }
+}
@@ -5,7 +5,7 @@
 //!
 //! Anvil is a modular templating system for creating user-defined scaffolding systems.
 //! It provides a composable API for file operations like generating, appending, transforming,
-//! and moving files.
+//! moving, copying and deleting files.
 //!
 //! ## Core Concepts
 //!
@@ -110,10 +110,121 @@
 /// ```
 pub mod append;
 
-/// Module for fallback mechanisms between two operations.
+/// Module for running operations conditionally.
+///
+/// This module provides the [`When`](`condition::When`) and [`Unless`](`condition::Unless`)
+/// structs, which only run an operation if a [`Predicate`](`condition::Predicate`) holds or
+/// doesn't hold for the path it is forged into, predicates for checking that path, and the
+/// no-op [`Skip`](`condition::Skip`) operation.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, condition::is_dir, mkdir::Mkdir};
+///
+/// // Only create the directory if the user asked for it and it isn't there yet
+/// fn create_assets(with_assets: bool) -> Result<(), Box<dyn std::error::Error>> {
+///     Mkdir::new().unless(is_dir()).when(with_assets).forge("./assets")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod condition;
+
+/// Module for copying files and directory trees.
+///
+/// This module provides the [`Copy`](`copy::Copy`) struct, which copies a file or a whole
+/// directory tree, applying an [`Overwrite`](`generate::Overwrite`) policy to files that
+/// already exist at the destination.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, copy::Copy};
+///
+/// // Copy a directory of static assets into a new project
+/// fn copy_assets() -> Result<(), Box<dyn std::error::Error>> {
+///     Copy::new("./templates/assets").forge("./my-app/assets")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod copy;
+
+/// Module for deleting files and directory trees.
+///
+/// This module provides the [`Delete`](`delete::Delete`) struct, which removes a file or a
+/// directory and everything inside it. With the `manifest` feature enabled, deletion can be
+/// limited to files that are unchanged since they were generated.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, delete::Delete};
+///
+/// // Remove a generated directory
+/// fn remove_generated() -> Result<(), Box<dyn std::error::Error>> {
+///     Delete::new().forge("./src/generated")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod delete;
+
+/// Module for previewing the changes an operation would make as a unified diff.
+///
+/// This module provides the [`Diff`](`diff::Diff`) struct along with the [`diff`](`diff::diff`)
+/// function, which runs any operation against a [`Plan`](`plan::Plan`) and compares the result
+/// with the current contents of every file it touches. Diffs can be displayed as plain unified
+/// diffs or written with terminal colours.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{diff, transform::Transform};
+///
+/// // Review a transform before applying it
+/// fn review() -> Result<(), Box<dyn std::error::Error>> {
+///     let transform = Transform::new(|content| Ok(content.replace("foo", "bar")));
+///
+///     let diff = diff::diff(&transform, "./src/lib.rs")?;
+///     diff.write_colored(&mut std::io::stdout())?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod diff;
+
+/// Module for building plans whose operations and templates are chosen at runtime.
+///
+/// This module provides the object-safe [`DynForge`](`dynamic::DynForge`) and
+/// [`DynAnvil`](`dynamic::DynAnvil`) traits, which are implemented for every operation and
+/// template, so that values of different types can be boxed and stored together.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, dynamic::DynForge, mkdir::Mkdir, sequence::Sequence};
+///
+/// // Create the directories for the features the user selected
+/// fn scaffold(features: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+///     let steps: Vec<Box<dyn DynForge>> = features
+///         .iter()
+///         .map(|feature| Mkdir::new().at(format!("./src/{feature}")).boxed())
+///         .collect();
+///     steps.into_iter().collect::<Sequence<_>>().forge(".")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod dynamic;
+
+/// Module for fallback mechanisms between operations.
 ///
 /// This module provides the [`Either`](`either::Either`) struct for creating fallback operations - if the first
-/// operation fails, the second one will be attempted.
+/// operation fails, the second one will be attempted. The [`Fallback`](`either::Fallback`) and
+/// [`FirstOf`](`either::FirstOf`) structs keep the error of every operation they try, and can
+/// be limited to falling back on expected errors only.
 ///
 /// # Example
 ///
@@ -162,6 +273,56 @@ pub mod append;
 /// ```
 pub mod either;
 
+/// Module for templates that are embedded into the binary.
+///
+/// This module provides [`EmbeddedDir`](`embed::EmbeddedDir`), a read-only filesystem of
+/// files baked into the binary, and [`Source`](`embed::Source`), which lets
+/// [`GenerateDir`](`generate_dir::GenerateDir`) and [`Copy`](`copy::Copy`) read from it
+/// instead of the disk. With the `embed` feature enabled, the [`embed_dir!`] macro embeds a
+/// directory at compile time, so generators can be distributed without their templates.
+/// The embedding crate's build script must emit `cargo:rerun-if-changed` for the directory,
+/// so that added and removed templates are picked up.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use anvil::{Forge, copy::Copy, embed::EmbeddedDir, embed_dir};
+///
+/// static TEMPLATES: EmbeddedDir = embed_dir!("templates/project");
+///
+/// // Copy the embedded project skeleton into a new directory
+/// Copy::new(&TEMPLATES).forge("./my-app").unwrap();
+/// ```
+pub mod embed;
+
+/// Module for the filesystem abstraction that every operation runs against.
+///
+/// This module provides the [`FileSystem`](`fs::FileSystem`) trait along with [`Disk`](`fs::Disk`),
+/// the default implementation backed by [`std::fs`]. Every built-in [`Forge`] performs its file
+/// access through a `FileSystem`, so operations can be pointed at something other than the real
+/// disk by calling [`Forge::forge_in`] instead of [`Forge::forge`].
+///
+/// With the `memory` feature enabled, the module also provides `Memory`, an in-memory
+/// filesystem that makes generator tests fast and free of tempdirs.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, fs::Disk, transform::Transform};
+///
+/// // Run a transform explicitly against the real disk
+/// fn add_header_comment() -> Result<(), Box<dyn std::error::Error>> {
+///     let transform = Transform::new(|content| {
+///         Ok(format!("// Generated file - do not edit directly\n{}", content))
+///     });
+///
+///     transform.forge_in(&Disk, "./src/generated.rs")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod fs;
+
 /// Module for creating files from templates.
 ///
 /// This module provides the [`Generate`](`generate::Generate`) struct for generating new files from templates.
@@ -201,6 +362,128 @@ pub mod either;
 /// ```
 pub mod generate;
 
+/// Module for generating whole directory trees from directories of templates.
+///
+/// This module provides the [`GenerateDir`](`generate_dir::GenerateDir`) struct, which
+/// renders every file in a template directory, along with templated file and directory
+/// names, into an output directory. Templates are rendered at runtime by a
+/// [`Renderer`](`generate_dir::Renderer`), which the backend crates provide.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate_dir::GenerateDir};
+///
+/// fn scaffold(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+///     let renderer = |source: &str| -> Result<String, std::io::Error> {
+///         Ok(source.replace("{{name}}", name))
+///     };
+///     GenerateDir::new("./templates/resource", renderer).forge("./src")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod generate_dir;
+
+/// Module for inserting content at a specific location inside existing files.
+///
+/// This module provides the [`Inject`](`inject::Inject`) struct, which inserts rendered template
+/// content before or after a marker, a regex match, a line number or a named anchor, using
+/// [`Location`](`inject::Location`) to describe where.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, inject::{Inject, Location}};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"pub mod users;\n")
+/// #     }
+/// # }
+///
+/// // Register a new module right before a marker comment
+/// fn register_module() -> Result<(), Box<dyn std::error::Error>> {
+///     let location = Location::Before("// anvil:modules".to_string());
+///     Inject::new(SimpleTemplate, location).forge("./src/lib.rs")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod inject;
+
+/// Module for recording the files generators create or modify.
+///
+/// This module is available with the `manifest` feature. It provides the
+/// [`Manifest`](`manifest::Manifest`), a JSON record of every file written by a generator with
+/// its operation, content hash and timestamp, and the [`Recorded`](`manifest::Recorded`) struct
+/// that keeps it up to date. The manifest can tell which generated files were edited by hand
+/// and which outputs a generator no longer produces.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate::Generate, manifest::Recorded};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// // Generate a file and record it in .anvil/manifest.json
+/// Recorded::new(Generate::new(SimpleTemplate), "scaffold")
+///     .forge("./src/new_file.rs")
+///     .unwrap();
+/// ```
+#[cfg(feature = "manifest")]
+pub mod manifest;
+
+/// Module for merging user edits with regenerated content.
+///
+/// This module provides the [`merge`](`merge::merge`) function, a line-based three-way merge
+/// that writes conflict markers where both sides changed the same lines, and the
+/// [`Bases`](`merge::Bases`) store that records what was last generated for each file. Together
+/// they power the [`Merge`](`generate::Overwrite::Merge`) overwrite policy of
+/// [`Generate`](`generate::Generate`).
+///
+/// # Example
+///
+/// ```rust
+/// use anvil::merge::merge;
+///
+/// let merged = merge("a\nb\n", "a\nb\nuser\n", "A\nb\n");
+///
+/// assert!(merged.is_clean());
+/// assert_eq!(merged.text(), "A\nb\nuser\n");
+/// ```
+pub mod merge;
+
+/// Module for creating directories.
+///
+/// This module provides the [`Mkdir`](`mkdir::Mkdir`) struct, which creates a directory and
+/// any missing parents.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir};
+///
+/// // Create an empty directory
+/// fn create_directory() -> Result<(), Box<dyn std::error::Error>> {
+///     Mkdir::new().forge("./storage/uploads")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod mkdir;
+
 /// Module for moving or renaming files.
 ///
 /// This module provides the [`Move`](`mover::Move`) struct for moving or renaming files.
@@ -220,6 +503,260 @@ pub mod generate;
 /// ```
 pub mod mover;
 
+/// Module for rendering the paths that files are generated at.
+///
+/// This module provides the [`PathTemplate`](`path_template::PathTemplate`) struct, which
+/// renders and validates a destination path with the same [`Renderer`](`generate_dir::Renderer`)
+/// as the contents, the [`Vars`](`path_template::Vars`) renderer with case conversion filters,
+/// and the [`Placed`](`path_template::Placed`) struct, which attaches a path to an operation.
+///
+/// # Example
+///
+/// ```rust
+/// use anvil::path_template::{PathTemplate, Vars};
+///
+/// let path = PathTemplate::new("src/models/{{ name | snakecase }}.rs")
+///     .render(&Vars::new().var("name", "BlogPost"))
+///     .unwrap();
+///
+/// assert_eq!(path, std::path::Path::new("src/models/blog_post.rs"));
+/// ```
+pub mod path_template;
+
+/// Module for previewing operations without touching the disk.
+///
+/// This module provides [`Plan`](`plan::Plan`), a [`FileSystem`](`fs::FileSystem`) that records
+/// every create, append, overwrite and move an operation would perform, along with the rendered
+/// contents, instead of performing it. Any composition of operations can be previewed, including
+/// [`Either`](`either::Either`) fallbacks.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate::Generate, plan::Plan};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// // Preview a generator run
+/// fn preview() -> Result<(), Box<dyn std::error::Error>> {
+///     let plan = Plan::new();
+///     Generate::new(SimpleTemplate).forge_in(&plan, "./src/new_file.rs")?;
+///
+///     print!("{plan}");
+///
+///     Ok(())
+/// }
+/// ```
+pub mod plan;
+
+/// Module for finding the root directory of a project.
+///
+/// This module provides the [`ProjectRoot`](`project_root::ProjectRoot`) struct, which
+/// walks up from the current directory to the nearest directory containing a
+/// [`Marker`](`project_root::Marker`) such as `.git` or a workspace `Cargo.toml`, and the
+/// [`Discovery`](`project_root::Discovery`) struct for configuring which markers count.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir, project_root::ProjectRoot};
+///
+/// // Create a directory at the project root, wherever the generator is run from
+/// fn create_migrations() -> Result<(), Box<dyn std::error::Error>> {
+///     let root = ProjectRoot::find()?;
+///     root.rooted(Mkdir::new()).forge("migrations")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod project_root;
+
+/// Module for keeping generated regions of hand-written files up to date.
+///
+/// This module provides the [`Region`](`region::Region`) struct, which re-renders a template
+/// between `anvil:begin <name>` and `anvil:end <name>` marker comments on every run, leaving
+/// the rest of the file untouched.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, region::Region};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"pub mod user;\n")
+/// #     }
+/// # }
+///
+/// // Regenerate the list of model modules
+/// fn sync_models() -> Result<(), Box<dyn std::error::Error>> {
+///     Region::new(SimpleTemplate, "models").forge("./src/models/mod.rs")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod region;
+
+/// Module for reporting what operations did to each path they touched.
+///
+/// This module provides the [`Report`](`report::Report`) struct, which runs an operation and
+/// records every file and directory it created, changed, left identical or skipped, ready to
+/// print in the style of Rails generators. With the `report` feature enabled, entries also
+/// carry the hashes of each file before and after the operation, and reports can be saved
+/// as JSON.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{report::Report, touch::Touch};
+///
+/// // Print what happened, such as `create  storage/.gitkeep`
+/// fn keep_storage() -> Result<(), Box<dyn std::error::Error>> {
+///     let report = Report::record(&Touch::new(), "./storage/.gitkeep")?;
+///     print!("{report}");
+///
+///     Ok(())
+/// }
+/// ```
+pub mod report;
+
+/// Module for reverting the changes made by operations.
+///
+/// This module provides the [`Revertible`](`revert::Revertible`) record, which runs any
+/// operation while recording each change it makes along with its pre-image, and can later
+/// apply the inverse of those changes: generated files are deleted, appended content is
+/// removed, moves are reversed and transformed files are restored.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{generate::Generate, revert::Revertible};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// fn scaffold_and_destroy() -> Result<(), Box<dyn std::error::Error>> {
+///     let record = Revertible::record(&Generate::new(SimpleTemplate), "./src/new_file.rs")?;
+///     record.revert()?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod revert;
+
+/// Module for confining operations to a project root.
+///
+/// This module provides the [`Rooted`](`sandbox::Rooted`) struct, which runs an operation
+/// at a path relative to a root directory and refuses to let it write anywhere else, and
+/// the [`Sandbox`](`sandbox::Sandbox`) filesystem it is built on. Absolute paths, `..`
+/// escapes and symbolic links pointing outside of the root are reported as an
+/// [`EscapeError`](`sandbox::EscapeError`).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir, sandbox::Rooted};
+///
+/// // Create a directory named by the user, but only inside ./my-app
+/// fn create_directory(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+///     Rooted::new(Mkdir::new(), "./my-app").forge(name)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod sandbox;
+
+/// Module for running several operations one after another.
+///
+/// This module provides the [`Then`](`sequence::Then`) struct, which runs two operations in
+/// turn, the [`Sequence`](`sequence::Sequence`) struct, which runs any number of operations
+/// of the same type, and the [`At`](`sequence::At`) struct, which gives a step its own path.
+/// Failures report which step failed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, mkdir::Mkdir, mover::Move};
+///
+/// // Move a file into a directory that may not exist yet
+/// fn archive_log() -> Result<(), Box<dyn std::error::Error>> {
+///     Mkdir::new()
+///         .at("archive")
+///         .then(Move::new("./app.log").at("archive/app.log"))
+///         .forge(".")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod sequence;
+
+/// Module for making sure files exist.
+///
+/// This module provides the [`Touch`](`touch::Touch`) struct, which creates an empty file if
+/// nothing exists at the path yet and leaves existing files untouched.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, touch::Touch};
+///
+/// // Keep an empty directory in version control
+/// fn keep_directory() -> Result<(), Box<dyn std::error::Error>> {
+///     Touch::new().forge("./storage/.gitkeep")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod touch;
+
+/// Module for running several operations as a single all-or-nothing unit.
+///
+/// This module provides the [`Transaction`](`transaction::Transaction`) struct, which runs a
+/// sequence of operations and, if any of them fails, restores every file and directory they
+/// touched to its original state.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anvil::{Forge, append::Append, generate::Generate, transaction::Transaction};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// // Generate a file and register it, or do neither
+/// fn add_module() -> Result<(), Box<dyn std::error::Error>> {
+///     Transaction::new()
+///         .step(Generate::new(SimpleTemplate), "src/module.rs")
+///         .step(Append::new(SimpleTemplate), "src/lib.rs")
+///         .forge(".")?;
+///
+///     Ok(())
+/// }
+/// ```
+pub mod transaction;
+
 /// Module for transforming the content of existing files.
 ///
 /// This module provides the [`Transform`](`transform::Transform`) struct for reading, modifying,
@@ -243,8 +780,13 @@ pub mod mover;
 /// ```
 pub mod transform;
 
+#[cfg(feature = "embed")]
+pub use anvil_embed::embed_dir;
+
 use std::{error::Error, path::Path};
 
+use fs::{Disk, FileSystem};
+
 /// The core trait for template rendering engines.
 ///
 /// `Anvil` provides the foundational functionality for rendering templates into strings
@@ -281,7 +823,7 @@ use std::{error::Error, path::Path};
 /// ```
 pub trait Anvil {
     /// The error type that this anvil implementation can produce.
-    type Error: Error + 'static;
+    type Error: Error + Send + Sync + 'static;
 
     /// Renders the template to the provided writer.
     ///
@@ -296,24 +838,35 @@ pub trait Anvil {
     fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error>;
 }
 
+/// Renders a template into memory, so that nothing is written unless rendering succeeds.
+pub(crate) fn render<A: Anvil>(template: &A) -> Result<Vec<u8>, A::Error> {
+    let mut buffer = Vec::new();
+    template.anvil(&mut buffer)?;
+    Ok(buffer)
+}
+
 /// The core trait for file operations.
 ///
 /// `Forge` represents operations that can create, modify, or transform files.
 /// It defines a uniform interface for all file manipulation actions in the Anvil system.
 ///
+/// Every operation runs against a [`FileSystem`]. Calling [`forge`](Forge::forge) uses the
+/// real disk, while [`forge_in`](Forge::forge_in) lets the caller choose the filesystem.
+///
 /// # Type Parameters
 ///
 /// * `Error` - The error type that this operation can produce.
 ///
 /// # Implementation
 ///
-/// When implementing this trait, you should provide the file operation logic in the `forge` method,
-/// which typically creates or modifies a file at the specified path.
+/// When implementing this trait, you should provide the file operation logic in the `forge_in`
+/// method, performing all file access through the provided filesystem rather than calling
+/// `std::fs` directly.
 ///
 /// # Examples
 ///
 /// ```
-/// use anvil::Forge;
+/// use anvil::{Forge, fs::FileSystem};
 /// use std::path::Path;
 ///
 /// // A simple file creator
@@ -322,9 +875,9 @@ pub trait Anvil {
 /// impl Forge for EmptyFileCreator {
 ///     type Error = std::io::Error;
 ///
-///     fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+///     fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
 ///         let path = into.as_ref();
-///         std::fs::File::create(path)?;
+///         fs.write(path, b"")?;
 ///         Ok(())
 ///     }
 /// }
@@ -333,7 +886,9 @@ pub trait Forge {
     /// The error type that this forge implementation can produce.
     type Error: Error;
 
-    /// Performs a file operation using the provided path.
+    /// Performs a file operation on the real disk using the provided path.
+    ///
+    /// This is shorthand for calling [`forge_in`](Forge::forge_in) with [`Disk`].
     ///
     /// # Parameters
     ///
@@ -343,9 +898,110 @@ pub trait Forge {
     /// # Returns
     ///
     /// * `Result<(), Self::Error>` - Ok if the operation was successful, Err otherwise.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error>;
+    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.forge_in(&Disk, into)
+    }
+
+    /// Performs a file operation against the given filesystem using the provided path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The [`FileSystem`] that all file access should go through.
+    /// * `into` - A reference to a path where the operation should be performed.
+    ///   This could be a target file to create/modify, or a destination for moving files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Self::Error>` - Ok if the operation was successful, Err otherwise.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error>;
 }
 
+/// Combinator methods available on every [`Forge`].
+///
+/// `ForgeExt` turns the combinators in [`sequence`], [`either`] and [`condition`] into
+/// methods, so that a plan of several operations reads from left to right and is a single
+/// value that can itself be forged.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, append::Append, generate::Generate};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Template(&'static str);
+/// # impl Anvil for Template {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(self.0.as_bytes())
+/// #     }
+/// # }
+///
+/// let with_tests = true;
+///
+/// // Generate the model, register it, and add a test module if asked to
+/// let model = Generate::new(Template("pub struct User;\n"))
+///     .then(Append::new(Template("\n#[cfg(test)]\nmod tests {}\n")).when(with_tests))
+///     .at("user.rs")
+///     .then(Append::new(Template("pub mod user;\n")).at("mod.rs"));
+///
+/// model.forge("./src/models").unwrap();
+/// ```
+pub trait ForgeExt: Forge + Sized {
+    /// Runs `next` at the same path once this operation has succeeded.
+    ///
+    /// See [`Then`](sequence::Then).
+    fn then<B: Forge>(self, next: B) -> sequence::Then<Self, B> {
+        sequence::Then::new(self, next)
+    }
+
+    /// Falls back to `fallback` if this operation fails.
+    ///
+    /// See [`Either`](either::Either).
+    fn or<R: Forge>(self, fallback: R) -> either::Either<Self, R> {
+        either::Either::new(self, fallback)
+    }
+
+    /// Falls back to `fallback` if this operation fails, keeping the errors of both.
+    ///
+    /// See [`Fallback`](either::Fallback).
+    fn fallback<R: Forge>(self, fallback: R) -> either::Fallback<Self, R> {
+        either::Fallback::new(self, fallback)
+    }
+
+    /// Only runs this operation if `predicate` holds.
+    ///
+    /// See [`When`](condition::When).
+    fn when<P: condition::Predicate>(self, predicate: P) -> condition::When<Self, P> {
+        condition::When::new(self, predicate)
+    }
+
+    /// Only runs this operation if `predicate` doesn't hold.
+    ///
+    /// See [`Unless`](condition::Unless).
+    fn unless<P: condition::Predicate>(self, predicate: P) -> condition::Unless<Self, P> {
+        condition::Unless::new(self, predicate)
+    }
+
+    /// Runs this operation at `path`, relative to the path it is forged into.
+    ///
+    /// See [`At`](sequence::At).
+    fn at(self, path: impl AsRef<Path>) -> sequence::At<Self> {
+        sequence::At::new(self, path)
+    }
+
+    /// Boxes this operation as a [`DynForge`](dynamic::DynForge), so that it can be stored
+    /// alongside operations of other types.
+    fn boxed<'a>(self) -> Box<dyn dynamic::DynForge + 'a>
+    where
+        Self: 'a,
+        Self::Error: Send + Sync + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<F: Forge> ForgeExt for F {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,7 +1028,11 @@ mod tests {
     impl Forge for SimpleForge {
         type Error = std::io::Error;
 
-        fn forge(&self, _into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        fn forge_in(
+            &self,
+            _fs: &dyn FileSystem,
+            _into: impl AsRef<Path>,
+        ) -> Result<(), Self::Error> {
             if let Some(ref err) = self.error {
                 Err(std::io::Error::new(err.kind(), err.to_string()))
             } else {
@@ -0,0 +1,731 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    fs::{normalize, FileSystem, Kept, Metadata, Savepoint},
+    Forge,
+};
+
+/// The conventional location of the manifest, relative to the project root.
+pub const DEFAULT_PATH: &str = ".anvil/manifest.json";
+
+/// Errors that can occur while loading or saving a [`Manifest`].
+#[derive(Error, Debug)]
+pub enum ManifestError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O on manifest: {0}")]
+    StdIo(#[from] io::Error),
+
+    /// Error that occurred while parsing or serializing the manifest.
+    #[error("failed to parse manifest: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The kind of change that produced a file recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Operation {
+    /// The file was created.
+    Create,
+    /// Content was appended to the file.
+    Append,
+    /// The file's contents were replaced.
+    Overwrite,
+    /// The file was moved from another path.
+    Rename {
+        /// The path the file was moved from
+        from: PathBuf,
+    },
+}
+
+/// What a [`Manifest`] knows about a single generated file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// The last operation that changed the file
+    operation: Operation,
+    /// The name of the generator that performed the operation
+    generator: String,
+    /// The hash of the file's contents right after the operation
+    hash: String,
+    /// When the operation happened, in seconds since the Unix epoch
+    timestamp: u64,
+    /// The generator run that performed the operation
+    run: u64,
+}
+
+impl Entry {
+    /// Returns the last operation that changed the file.
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// Returns the name of the generator that last changed the file.
+    pub fn generator(&self) -> &str {
+        &self.generator
+    }
+
+    /// Returns the hash of the file's contents as the generator left them.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Returns when the file was last changed, in seconds since the Unix epoch.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+/// A record of every file that generators created or modified.
+///
+/// The manifest maps each path to an [`Entry`] describing the operation, the generator, a
+/// hash of the contents it produced and when. Comparing those hashes with the files on disk
+/// tells tools which generated files users have since modified, and comparing generator runs
+/// tells them which outputs a generator no longer produces.
+///
+/// Manifests are usually kept up to date by wrapping an operation in [`Recorded`], which
+/// stores the manifest as JSON at [`DEFAULT_PATH`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{fs::Disk, manifest::{Manifest, DEFAULT_PATH}};
+///
+/// let manifest = Manifest::load(&Disk, DEFAULT_PATH).unwrap();
+///
+/// for path in manifest.modified(&Disk) {
+///     println!("{} was edited by hand", path.display());
+/// }
+/// for path in manifest.stale("controller") {
+///     println!("{} is no longer generated", path.display());
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The number of recorded runs of each generator
+    #[serde(default)]
+    runs: BTreeMap<String, u64>,
+    /// The recorded files, keyed by path
+    #[serde(default)]
+    files: BTreeMap<PathBuf, Entry>,
+}
+
+impl Manifest {
+    /// Creates a new, empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the manifest stored at `path`, or returns an empty one if there is none yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or isn't a valid manifest.
+    pub fn load(fs: &dyn FileSystem, path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        match fs.read(path.as_ref()) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the manifest as JSON at `path`, creating parent directories as needed.
+    pub fn save(&self, fs: &dyn FileSystem, path: impl AsRef<Path>) -> Result<(), ManifestError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        let mut json = serde_json::to_vec_pretty(self)?;
+        json.push(b'\n');
+        fs.write(path, &json)?;
+        Ok(())
+    }
+
+    /// Returns the entry recorded for `path`, if any.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&Entry> {
+        self.files.get(&normalize(path.as_ref()))
+    }
+
+    /// Returns every recorded file and its entry, ordered by path.
+    pub fn entries(&self) -> impl Iterator<Item = (&Path, &Entry)> {
+        self.files
+            .iter()
+            .map(|(path, entry)| (path.as_path(), entry))
+    }
+
+    /// Returns `true` if no files have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns `true` if `path` was recorded and its contents no longer match what the
+    /// generator produced. Files that have been removed are not considered modified.
+    pub fn is_modified(&self, fs: &dyn FileSystem, path: impl AsRef<Path>) -> bool {
+        let path = normalize(path.as_ref());
+        match (self.files.get(&path), fs.read(&path)) {
+            (Some(entry), Ok(contents)) => entry.hash != hash(&contents),
+            _ => false,
+        }
+    }
+
+    /// Returns every recorded file whose contents no longer match what the generator produced.
+    pub fn modified(&self, fs: &dyn FileSystem) -> Vec<&Path> {
+        self.entries()
+            .filter(|(path, _)| self.is_modified(fs, path))
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Returns every recorded file that no longer exists.
+    pub fn missing(&self, fs: &dyn FileSystem) -> Vec<&Path> {
+        self.entries()
+            .filter(|(path, _)| !fs.metadata(path).is_ok_and(|m| m.is_file()))
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Returns every file that `generator` recorded in an earlier run but did not touch in
+    /// its most recent one.
+    pub fn stale(&self, generator: &str) -> Vec<&Path> {
+        let latest = self.runs.get(generator).copied().unwrap_or_default();
+        self.entries()
+            .filter(|(_, entry)| entry.generator == generator && entry.run < latest)
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Removes the entry for `path`, returning it if it was recorded.
+    pub fn forget(&mut self, path: impl AsRef<Path>) -> Option<Entry> {
+        self.files.remove(&normalize(path.as_ref()))
+    }
+
+    /// Starts recording a new run of `generator` against the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the generator's operations run against
+    /// * `generator` - The name to record the operations under
+    ///
+    /// # Returns
+    ///
+    /// A [`Recorder`] to run operations against. Call [`Recorder::finish`] afterwards to
+    /// get the updated manifest back.
+    pub fn recorder<'a>(
+        mut self,
+        fs: &'a dyn FileSystem,
+        generator: impl Into<String>,
+    ) -> Recorder<'a> {
+        let generator = generator.into();
+        let run = self.runs.get(&generator).copied().unwrap_or_default() + 1;
+        self.runs.insert(generator.clone(), run);
+        Recorder {
+            inner: fs,
+            generator,
+            run,
+            manifest: RefCell::new(self),
+            undo: RefCell::new(Vec::new()),
+            savepoints: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// Returns the hash recorded for `contents`.
+pub fn hash(contents: &[u8]) -> String {
+    crate::report::sha256(contents)
+}
+
+/// A [`FileSystem`] wrapper that records every file it creates or modifies in a [`Manifest`].
+///
+/// Removing a file through the recorder also removes its entry. Files the generator left as
+/// they were, because they were already up to date or its policy skipped them, keep their
+/// entry but count as produced by the current run, so they are not reported as
+/// [`stale`](Manifest::stale). When an operation is rolled back, for example by [`Either`](crate::either::Either), the entries it recorded are
+/// discarded as well.
+pub struct Recorder<'a> {
+    inner: &'a dyn FileSystem,
+    generator: String,
+    run: u64,
+    manifest: RefCell<Manifest>,
+    /// What each entry was before it was changed, while any savepoint is held
+    undo: RefCell<Vec<(PathBuf, Option<Entry>)>>,
+    /// The inner savepoint and length of the undo log at each savepoint taken
+    savepoints: RefCell<Vec<(Savepoint, usize)>>,
+}
+
+impl Recorder<'_> {
+    /// Stops recording and returns the updated manifest.
+    pub fn finish(self) -> Manifest {
+        self.manifest.into_inner()
+    }
+
+    fn record(&self, path: &Path, operation: Operation) -> io::Result<()> {
+        let path = normalize(path);
+        let contents = self.inner.read(&path)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let entry = Entry {
+            operation,
+            generator: self.generator.clone(),
+            hash: hash(&contents),
+            timestamp,
+            run: self.run,
+        };
+        self.set(path, Some(entry));
+        Ok(())
+    }
+
+    /// Sets or clears the entry for `path`, remembering the old one so that it can be
+    /// restored by a rollback.
+    fn set(&self, path: PathBuf, entry: Option<Entry>) {
+        let mut manifest = self.manifest.borrow_mut();
+        let previous = match entry {
+            Some(entry) => manifest.files.insert(path.clone(), entry),
+            None => manifest.files.remove(&path),
+        };
+        if !self.savepoints.borrow().is_empty() {
+            self.undo.borrow_mut().push((path, previous));
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.inner.metadata(path).is_ok_and(|m| m.is_file())
+    }
+}
+
+impl FileSystem for Recorder<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let operation = if self.is_file(path) {
+            Operation::Overwrite
+        } else {
+            Operation::Create
+        };
+        self.inner.write(path, contents)?;
+        self.record(path, operation)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.create_new(path, contents)?;
+        self.record(path, Operation::Create)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.append(path, contents)?;
+        self.record(path, Operation::Append)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.rename(from, to)?;
+        let (from, to) = (normalize(from), normalize(to));
+
+        // Entries for the moved file, or for anything inside a moved directory, move with it
+        let moved: Vec<(PathBuf, Entry)> = self
+            .manifest
+            .borrow()
+            .files
+            .iter()
+            .filter(|(path, _)| path.starts_with(&from))
+            .map(|(path, entry)| (path.clone(), entry.clone()))
+            .collect();
+        for (path, entry) in moved {
+            let rest = path
+                .strip_prefix(&from)
+                .expect("entry is nested under source");
+            let moved_to = to.join(rest);
+            self.set(path, None);
+            self.set(moved_to, Some(entry));
+        }
+
+        if self.is_file(&to) {
+            self.record(&to, Operation::Rename { from })?;
+        }
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_file(path)?;
+        self.set(normalize(path), None);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.inner.set_mode(path, mode)
+    }
+
+    fn keep(&self, path: &Path, kept: Kept) {
+        self.inner.keep(path, kept);
+        // The generator still produces the file, even though it didn't need to write it
+        let path = normalize(path);
+        let entry = self.manifest.borrow().files.get(&path).cloned();
+        if let Some(entry) = entry.filter(|entry| entry.generator == self.generator) {
+            self.set(
+                path,
+                Some(Entry {
+                    run: self.run,
+                    ..entry
+                }),
+            );
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.inner.symlink(target, link)
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        let inner = self.inner.savepoint();
+        let mut savepoints = self.savepoints.borrow_mut();
+        savepoints.push((inner, self.undo.borrow().len()));
+        Savepoint::new(savepoints.len() - 1)
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let mut savepoints = self.savepoints.borrow_mut();
+        if savepoint.position() >= savepoints.len() {
+            return;
+        }
+        let (inner, len) = savepoints
+            .drain(savepoint.position()..)
+            .next()
+            .expect("savepoint position is in bounds");
+        // Undo the entry changes newest first, so each entry ends up as it was
+        let mut manifest = self.manifest.borrow_mut();
+        for (path, previous) in self.undo.borrow_mut().drain(len..).rev() {
+            match previous {
+                Some(entry) => manifest.files.insert(path, entry),
+                None => manifest.files.remove(&path),
+            };
+        }
+        drop(manifest);
+        self.inner.rollback_to(inner, cause);
+    }
+}
+
+/// Errors that can occur while running a [`Recorded`] operation.
+#[derive(Error, Debug)]
+pub enum RecordedError<E: Error + 'static> {
+    /// The wrapped operation failed.
+    #[error(transparent)]
+    Forge(E),
+
+    /// The manifest could not be loaded or saved.
+    #[error("failed to update manifest: {0}")]
+    Manifest(#[from] ManifestError),
+}
+
+/// A struct that runs an operation and records the files it touched in a [`Manifest`].
+///
+/// `Recorded` loads the manifest, runs the wrapped operation against a [`Recorder`], and saves
+/// the manifest again. The manifest is saved even if the operation fails part-way, so that
+/// any files it did produce are still accounted for.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate::Generate, manifest::Recorded};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// // Generate a controller and record it under the "controller" generator
+/// Recorded::new(Generate::new(SimpleTemplate), "controller")
+///     .forge("src/controllers/user.rs")
+///     .unwrap();
+/// ```
+pub struct Recorded<F: Forge> {
+    /// The operation to run
+    forge: F,
+    /// The name to record the operation under
+    generator: String,
+    /// Where the manifest is stored
+    manifest: PathBuf,
+}
+
+impl<F: Forge> Recorded<F> {
+    /// Creates a new `Recorded` operation that stores its manifest at [`DEFAULT_PATH`].
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `generator` - The name to record the operation's files under
+    ///
+    /// # Returns
+    ///
+    /// A new `Recorded` instance wrapping the operation.
+    pub fn new(forge: F, generator: impl Into<String>) -> Self {
+        Self {
+            forge,
+            generator: generator.into(),
+            manifest: PathBuf::from(DEFAULT_PATH),
+        }
+    }
+
+    /// Sets where the manifest is stored.
+    pub fn manifest(mut self, path: impl AsRef<Path>) -> Self {
+        self.manifest = path.as_ref().to_path_buf();
+        self
+    }
+}
+
+impl<F: Forge> Forge for Recorded<F>
+where
+    F::Error: 'static,
+{
+    type Error = RecordedError<F::Error>;
+
+    /// Runs the wrapped operation and records every file it created or modified.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the operation and manifest live in
+    /// * `into` - The path to run the operation on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RecordedError<F::Error>>` - Ok if successful, or an error if:
+    ///   - The wrapped operation failed
+    ///   - The manifest couldn't be loaded or saved
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let manifest = Manifest::load(fs, &self.manifest)?;
+
+        let recorder = manifest.recorder(fs, self.generator.clone());
+        let result = self.forge.forge_in(&recorder, into);
+        let manifest = recorder.finish();
+
+        let saved = manifest.save(fs, &self.manifest);
+        result.map_err(RecordedError::Forge)?;
+        saved?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append,
+        either::either,
+        fs::Memory,
+        generate::{Generate, Overwrite},
+        mover::Move,
+        plan::Plan,
+        Anvil, ForgeExt,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_writes_manifest_entries() {
+        let fs = Memory::new().with_file("src/mod.rs", "pub mod a;\n");
+
+        Recorded::new(Generate::new(mock("pub struct B;\n")), "model")
+            .forge_in(&fs, "src/b.rs")
+            .unwrap();
+        Recorded::new(Append::new(mock("pub mod b;\n")), "model")
+            .forge_in(&fs, "src/mod.rs")
+            .unwrap();
+
+        let manifest = Manifest::load(&fs, DEFAULT_PATH).unwrap();
+        let created = manifest.get("src/b.rs").unwrap();
+        assert_eq!(created.operation(), &Operation::Create);
+        assert_eq!(created.generator(), "model");
+        assert_eq!(created.hash(), hash(b"pub struct B;\n"));
+
+        let appended = manifest.get("src/mod.rs").unwrap();
+        assert_eq!(appended.operation(), &Operation::Append);
+        assert_eq!(appended.hash(), hash(b"pub mod a;\npub mod b;\n"));
+    }
+
+    #[test]
+    fn test_manifest_detects_modified_and_missing_files() {
+        let fs = Memory::new();
+        let recorder = Manifest::new().recorder(&fs, "scaffold");
+        Generate::new(mock("one"))
+            .forge_in(&recorder, "one.txt")
+            .unwrap();
+        Generate::new(mock("two"))
+            .forge_in(&recorder, "two.txt")
+            .unwrap();
+        Generate::new(mock("three"))
+            .forge_in(&recorder, "three.txt")
+            .unwrap();
+        let manifest = recorder.finish();
+
+        fs.write(Path::new("two.txt"), b"edited").unwrap();
+        fs.remove_file(Path::new("three.txt")).unwrap();
+
+        assert!(!manifest.is_modified(&fs, "one.txt"));
+        assert_eq!(manifest.modified(&fs), vec![Path::new("two.txt")]);
+        assert_eq!(manifest.missing(&fs), vec![Path::new("three.txt")]);
+    }
+
+    #[test]
+    fn test_manifest_lists_stale_outputs_of_earlier_runs() {
+        let fs = Memory::new();
+
+        let recorder = Manifest::new().recorder(&fs, "scaffold");
+        Generate::new(mock("kept"))
+            .forge_in(&recorder, "kept.txt")
+            .unwrap();
+        Generate::new(mock("old"))
+            .forge_in(&recorder, "old.txt")
+            .unwrap();
+        let manifest = recorder.finish();
+
+        // The second run only regenerates one of the files
+        let recorder = manifest.recorder(&fs, "scaffold");
+        fs.remove_file(Path::new("kept.txt")).unwrap();
+        Generate::new(mock("kept"))
+            .forge_in(&recorder, "kept.txt")
+            .unwrap();
+        let manifest = recorder.finish();
+
+        assert_eq!(manifest.stale("scaffold"), vec![Path::new("old.txt")]);
+        assert!(manifest.stale("other").is_empty());
+    }
+
+    #[test]
+    fn test_manifest_does_not_list_identical_outputs_as_stale() {
+        let fs = Memory::new();
+        let scaffold = |recorder: &Recorder| {
+            for (path, content) in [("a.txt", "a"), ("b.txt", "b")] {
+                Generate::new(mock(content))
+                    .policy(Overwrite::SkipIdentical)
+                    .forge_in(recorder, path)
+                    .unwrap();
+            }
+        };
+
+        let recorder = Manifest::new().recorder(&fs, "scaffold");
+        scaffold(&recorder);
+        let manifest = recorder.finish();
+
+        // Rerunning leaves both files untouched, but the generator still produces them
+        let recorder = manifest.recorder(&fs, "scaffold");
+        scaffold(&recorder);
+        let manifest = recorder.finish();
+
+        assert!(manifest.stale("scaffold").is_empty());
+    }
+
+    #[test]
+    fn test_recorder_follows_moves_and_rollbacks() {
+        let fs = Memory::new().with_file("mod.rs", "existing\n");
+        let recorder = Manifest::new().recorder(&fs, "scaffold");
+
+        Generate::new(mock("content"))
+            .forge_in(&recorder, "old.txt")
+            .unwrap();
+        Move::new("old.txt").forge_in(&recorder, "new.txt").unwrap();
+
+        // The failed left side is rolled back before the right side runs
+        either(
+            Generate::new(mock("fails")),
+            Append::new(mock("appended\n")),
+        )
+        .forge_in(&recorder, "mod.rs")
+        .unwrap();
+
+        let manifest = recorder.finish();
+        let paths: Vec<_> = manifest.entries().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec![Path::new("mod.rs"), Path::new("new.txt")]);
+        assert_eq!(
+            manifest.get("new.txt").unwrap().operation(),
+            &Operation::Rename {
+                from: PathBuf::from("old.txt")
+            }
+        );
+    }
+
+    #[test]
+    fn test_recorder_rolls_back_entries_of_nested_operations() {
+        let base = Memory::new().with_file("kept.txt", "kept");
+        let plan = Plan::over(&base);
+        let recorder = Manifest::new().recorder(&plan, "scaffold");
+
+        // The left side records an entry and then fails on the existing file
+        let left = Generate::new(mock("one"))
+            .at("a.txt")
+            .then(Generate::new(mock("two")).at("kept.txt"));
+        let right = Generate::new(mock("three")).at("b.txt");
+        either(left, right).forge_in(&recorder, ".").unwrap();
+
+        let manifest = recorder.finish();
+        let paths: Vec<_> = manifest.entries().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec![Path::new("b.txt")]);
+        assert_eq!(manifest.get("b.txt").unwrap().hash(), hash(b"three"));
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    io,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use crate::fs::{normalize, FileSystem};
+
+/// The marker opening a conflict, followed by the user's version of the lines.
+pub const CONFLICT_START: &str = "<<<<<<< current";
+/// The marker separating the user's version from the generated version.
+pub const CONFLICT_SEPARATOR: &str = "=======";
+/// The marker closing a conflict, after the generated version of the lines.
+pub const CONFLICT_END: &str = ">>>>>>> generated";
+
+/// The result of a three-way [`merge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged {
+    /// The merged text, including any conflict markers
+    text: String,
+    /// The number of conflicting regions
+    conflicts: usize,
+}
+
+impl Merged {
+    /// Returns the merged text, including any conflict markers.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of regions where both sides changed the same lines differently.
+    pub fn conflicts(&self) -> usize {
+        self.conflicts
+    }
+
+    /// Returns `true` if the merge produced no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts == 0
+    }
+
+    /// Consumes the result and returns the merged text.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+/// A change one side made to the base: the base lines it replaced and what it replaced them with.
+struct Hunk<'a> {
+    base: Range<usize>,
+    lines: &'a [&'a str],
+}
+
+/// Returns the changes `side` makes to `base`, ordered by position.
+fn hunks<'a>(base: &[&str], side: &'a [&'a str]) -> Vec<Hunk<'a>> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .iter()
+        .map(|op| op.as_tag_tuple())
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, base, new)| Hunk {
+            base,
+            lines: &side[new],
+        })
+        .collect()
+}
+
+/// Returns one side's version of the base lines in `range`, given its changes within it.
+fn apply(base: &[&str], range: Range<usize>, hunks: &[Hunk<'_>]) -> String {
+    let mut text = String::new();
+    let mut position = range.start;
+    for hunk in hunks {
+        text.extend(base[position..hunk.base.start].iter().copied());
+        text.extend(hunk.lines.iter().copied());
+        position = hunk.base.end;
+    }
+    text.extend(base[position..range.end].iter().copied());
+    text
+}
+
+/// Pushes `text` onto `output`, making sure it ends with a newline.
+fn push_line_block(output: &mut String, text: &str) {
+    output.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        output.push('\n');
+    }
+}
+
+/// Performs a line-based three-way merge.
+///
+/// Changes that `current` and `generated` each made to `base` are combined. Where both sides
+/// changed the same (or adjacent) lines in different ways, the merged text contains both
+/// versions between conflict markers:
+///
+/// ```text
+/// <<<<<<< current
+/// the user's lines
+/// =======
+/// the generated lines
+/// >>>>>>> generated
+/// ```
+///
+/// # Parameters
+///
+/// * `base` - The text both sides started from, such as the previously generated file
+/// * `current` - The text as the user has edited it
+/// * `generated` - The newly generated text
+///
+/// # Returns
+///
+/// The [`Merged`] text and the number of conflicts in it.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::merge::merge;
+///
+/// let base = "fn main() {\n    println!(\"v1\");\n}\n";
+/// let current = "// edited by hand\nfn main() {\n    println!(\"v1\");\n}\n";
+/// let generated = "fn main() {\n    println!(\"v2\");\n}\n";
+///
+/// let merged = merge(base, current, generated);
+/// assert!(merged.is_clean());
+/// assert_eq!(
+///     merged.text(),
+///     "// edited by hand\nfn main() {\n    println!(\"v2\");\n}\n"
+/// );
+/// ```
+pub fn merge(base: &str, current: &str, generated: &str) -> Merged {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let current: Vec<&str> = current.split_inclusive('\n').collect();
+    let generated: Vec<&str> = generated.split_inclusive('\n').collect();
+
+    let ours = hunks(&base, &current);
+    let theirs = hunks(&base, &generated);
+
+    let mut text = String::new();
+    let mut conflicts = 0;
+    let mut position = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while i < ours.len() || j < theirs.len() {
+        // Start a region at whichever change comes first in the base
+        let starts_with_ours =
+            j >= theirs.len() || (i < ours.len() && ours[i].base.start <= theirs[j].base.start);
+        let first = if starts_with_ours {
+            &ours[i]
+        } else {
+            &theirs[j]
+        };
+        let mut region = first.base.clone();
+        let (ours_start, theirs_start) = (i, j);
+
+        // Grow the region until no change from either side touches it
+        loop {
+            if i < ours.len() && ours[i].base.start <= region.end {
+                region.end = region.end.max(ours[i].base.end);
+                i += 1;
+            } else if j < theirs.len() && theirs[j].base.start <= region.end {
+                region.end = region.end.max(theirs[j].base.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        text.extend(base[position..region.start].iter().copied());
+        position = region.end;
+
+        let ours_hunks = &ours[ours_start..i];
+        let theirs_hunks = &theirs[theirs_start..j];
+        let our_text = apply(&base, region.clone(), ours_hunks);
+        let their_text = apply(&base, region.clone(), theirs_hunks);
+
+        if theirs_hunks.is_empty() || our_text == their_text {
+            text.push_str(&our_text);
+        } else if ours_hunks.is_empty() {
+            text.push_str(&their_text);
+        } else {
+            conflicts += 1;
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(CONFLICT_START);
+            text.push('\n');
+            push_line_block(&mut text, &our_text);
+            text.push_str(CONFLICT_SEPARATOR);
+            text.push('\n');
+            push_line_block(&mut text, &their_text);
+            text.push_str(CONFLICT_END);
+            text.push('\n');
+        }
+    }
+
+    text.extend(base[position..].iter().copied());
+
+    Merged { text, conflicts }
+}
+
+/// A record of the content Anvil last generated for each file, used as the base of a merge.
+///
+/// `Bases` keeps a copy of every generated file under a directory of its own, mirroring the
+/// path of the file. When the template changes and the file is generated again, the stored
+/// copy is the common ancestor of the user's edits and the new output.
+///
+/// Bases are read and written through the same [`FileSystem`] as the generated files, so
+/// they take part in dry runs and transactions. The directory is usually checked into
+/// version control alongside the project.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{generate::{Generate, Overwrite}, merge::Bases};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content\n")
+/// #     }
+/// # }
+///
+/// let generate = Generate::new(SimpleTemplate)
+///     .policy(Overwrite::Merge(Bases::new(".anvil/bases")));
+///
+/// generate.generate("src/controllers/user.rs").unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bases {
+    /// The directory the stored copies are kept in
+    dir: PathBuf,
+}
+
+impl Bases {
+    /// Creates a store that keeps its copies under `dir`.
+    ///
+    /// # Parameters
+    ///
+    /// * `dir` - The directory to keep stored copies in. Files generated at relative
+    ///   paths are stored at the same relative path beneath it.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns where the base for `path` is stored.
+    ///
+    /// The path is normalized first, so `./src/../src/lib.rs` shares its base with
+    /// `src/lib.rs`. Components that can't be mirrored beneath the store are encoded, so that
+    /// every path gets a distinct location inside it: a leading `..` becomes `%2E%2E`, the
+    /// root of an absolute path becomes a `%2F` directory, a Windows prefix such as `C:`
+    /// becomes `C%3A`, and a literal `%` in a file name becomes `%25`.
+    pub fn path_for(&self, path: &Path) -> PathBuf {
+        let relative: PathBuf = normalize(path)
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => match name.to_str() {
+                    Some(name) if name.contains('%') => Some(name.replace('%', "%25").into()),
+                    _ => Some(name.to_os_string()),
+                },
+                Component::ParentDir => Some("%2E%2E".into()),
+                Component::RootDir => Some("%2F".into()),
+                Component::Prefix(prefix) => Some(
+                    prefix
+                        .as_os_str()
+                        .to_string_lossy()
+                        .replace('%', "%25")
+                        .replace(':', "%3A")
+                        .replace('\\', "%5C")
+                        .into(),
+                ),
+                Component::CurDir => None,
+            })
+            .collect();
+        self.dir.join(relative)
+    }
+
+    /// Loads the stored base for `path`, or `None` if nothing was recorded.
+    pub fn load(&self, fs: &dyn FileSystem, path: &Path) -> io::Result<Option<Vec<u8>>> {
+        match fs.read(&self.path_for(path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Records `contents` as the base for `path`, replacing any previous record.
+    pub fn store(&self, fs: &dyn FileSystem, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let stored = self.path_for(path);
+        if let Some(parent) = stored.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        if fs.read(&stored).is_ok_and(|existing| existing == contents) {
+            return Ok(());
+        }
+        fs.write(&stored, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+
+    #[test]
+    fn test_merge_combines_changes_to_different_lines() {
+        let base = "a\nb\nc\nd\ne\n";
+        let current = "a\nB\nc\nd\ne\n";
+        let generated = "a\nb\nc\nd\nE\nf\n";
+
+        let merged = merge(base, current, generated);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.text(), "a\nB\nc\nd\nE\nf\n");
+    }
+
+    #[test]
+    fn test_merge_marks_conflicting_changes() {
+        let base = "a\nb\nc\n";
+        let current = "a\nuser\nc\n";
+        let generated = "a\ngenerated\nc\n";
+
+        let merged = merge(base, current, generated);
+
+        assert_eq!(merged.conflicts(), 1);
+        assert_eq!(
+            merged.text(),
+            "a\n<<<<<<< current\nuser\n=======\ngenerated\n>>>>>>> generated\nc\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_accepts_identical_changes_on_both_sides() {
+        let base = "a\nb\n";
+        let both = "a\nchanged\nextra\n";
+
+        let merged = merge(base, both, both);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.text(), both);
+    }
+
+    #[test]
+    fn test_merge_handles_missing_trailing_newlines() {
+        let merged = merge("a\nb", "a\nuser", "a\ngenerated");
+
+        assert_eq!(
+            merged.text(),
+            "a\n<<<<<<< current\nuser\n=======\ngenerated\n>>>>>>> generated\n"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_bases_store_and_load_through_filesystem() {
+        let fs = Memory::new();
+        let bases = Bases::new(".anvil/bases");
+
+        assert_eq!(bases.load(&fs, Path::new("src/lib.rs")).unwrap(), None);
+
+        bases
+            .store(&fs, Path::new("./src/lib.rs"), b"generated")
+            .unwrap();
+
+        assert_eq!(
+            bases.load(&fs, Path::new("src/lib.rs")).unwrap(),
+            Some(b"generated".to_vec())
+        );
+        assert!(fs.exists(Path::new(".anvil/bases/src/lib.rs")));
+    }
+
+    #[test]
+    fn test_bases_keep_paths_outside_the_project_apart() {
+        let bases = Bases::new(".anvil/bases");
+        let stored = |path: &str| bases.path_for(Path::new(path));
+
+        assert_eq!(stored("./src/../src/lib.rs"), stored("src/lib.rs"));
+        assert_eq!(
+            stored("../x/lib.rs"),
+            Path::new(".anvil/bases/%2E%2E/x/lib.rs")
+        );
+        assert_eq!(stored("/a/b"), Path::new(".anvil/bases/%2F/a/b"));
+        assert_eq!(stored("%2E%2E/x"), Path::new(".anvil/bases/%252E%252E/x"));
+
+        // Every one of these gets its own stored copy
+        let paths = ["x/lib.rs", "../x/lib.rs", "/x/lib.rs", "%2E%2E/x/lib.rs"];
+        let unique: std::collections::BTreeSet<_> = paths.iter().map(|p| stored(p)).collect();
+        assert_eq!(unique.len(), paths.len());
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{
+    fs::{FileSystem, Kept},
+    Forge,
+};
+
+/// A struct that creates a directory.
+///
+/// `Mkdir` creates the directory it is forged into, along with any missing parent
+/// directories. It succeeds if the directory already exists.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir};
+///
+/// // Create an empty directory for uploads
+/// match Mkdir::new().forge("./my-app/storage/uploads") {
+///     Ok(_) => println!("Directory created"),
+///     Err(e) => eprintln!("Failed to create directory: {}", e),
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Mkdir;
+
+/// Errors that can occur while creating a directory.
+#[derive(Error, Debug)]
+pub enum MkdirError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while creating a directory: {0}")]
+    StdIo(#[from] std::io::Error),
+}
+
+impl Mkdir {
+    /// Creates a new `Mkdir` operation.
+    ///
+    /// # Returns
+    ///
+    /// A new `Mkdir` instance that creates whatever directory it is forged into.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Forge for Mkdir {
+    type Error = MkdirError;
+
+    /// Creates the directory and any missing parents.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem in which the directory is created
+    /// * `into` - The directory to create
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), MkdirError>` - Ok if the directory exists afterwards, or an error if:
+    ///   - A file exists at the path or one of its parents
+    ///   - File permissions don't allow the operation
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref();
+        if fs.metadata(path).is_ok_and(|m| m.is_dir()) {
+            fs.keep(path, Kept::Identical);
+            return Ok(());
+        }
+        Ok(fs.create_dir_all(path)?)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::fs::Memory;
+
+    #[test]
+    fn test_mkdir_creates_nested_directories() {
+        let fs = Memory::new().with_file("file.txt", "content");
+
+        Mkdir::new().forge_in(&fs, "a/b/c").unwrap();
+        // Creating an existing directory succeeds
+        Mkdir::new().forge_in(&fs, "a/b").unwrap();
+
+        assert!(fs.metadata(Path::new("a/b/c")).unwrap().is_dir());
+
+        // A file in the way is an error
+        assert!(matches!(
+            Mkdir::new().forge_in(&fs, "file.txt/dir"),
+            Err(MkdirError::StdIo(_))
+        ));
+    }
+}
@@ -1,28 +1,45 @@
-use std::path::{Path, PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
-use crate::Forge;
+use crate::{
+    fs::{FileSystem, Metadata},
+    generate::{GenerateError, Outcome, Overwrite},
+    Forge,
+};
 
-/// A struct that moves or renames a file.
+/// A struct that moves or renames a file or directory.
 ///
-/// `Move` provides functionality to move a file from one path to another,
-/// which can be used either for relocating a file or renaming it. It uses
-/// Rust's `std::fs::rename` function internally, which is an atomic operation
-/// on most operating systems.
+/// `Move` provides functionality to move a file or directory from one path to another,
+/// which can be used either for relocating it or renaming it. It uses
+/// [`FileSystem::rename`] internally, which on the real disk is Rust's
+/// `std::fs::rename` function, an atomic operation on most operating systems. When the
+/// rename fails because the destination is on a different device, `Move` falls back to
+/// copying everything to the destination and then deleting the source.
+///
+/// What happens when the destination already exists is decided by an [`Overwrite`]
+/// policy rather than by the platform: by default an existing file is replaced, while
+/// other policies apply to each file exactly as they do for
+/// [`Generate`](crate::generate::Generate), with the moved file's contents in place of
+/// rendered content. Moving a directory onto
+/// an existing directory merges the two, applying the policy to each file that exists
+/// in both. Source files that the policy skips are left where they are.
 ///
 /// # Examples
 ///
 /// ```rust,no_run
-/// use anvil::{Forge, mover::Move};
+/// use anvil::{Forge, generate::Overwrite, mover::Move};
 ///
-/// // Moving a file to a new location
-/// let mover = Move::new("./src/old_location.rs");
+/// // Moving a file to a new location, creating the directory if needed
+/// let mover = Move::new("./src/old_location.rs").create_parents();
 /// match mover.forge("./src/new_directory/relocated.rs") {
 ///     Ok(_) => println!("File successfully moved"),
 ///     Err(e) => eprintln!("Failed to move file: {}", e),
 /// }
 ///
-/// // Renaming a file
-/// let renamer = Move::new("./config.old.toml");
+/// // Renaming a file, refusing to replace a file that already has the new name
+/// let renamer = Move::new("./config.old.toml").policy(Overwrite::Fail);
 /// match renamer.forge("./config.toml") {
 ///     Ok(_) => println!("File successfully renamed"),
 ///     Err(e) => eprintln!("Failed to rename file: {}", e),
@@ -31,12 +48,24 @@ use crate::Forge;
 ///
 /// # Notes
 ///
-/// - The operation will fail if the source file does not exist.
-/// - The operation will fail if the destination already exists.
-/// - Moving files across different filesystems may not be atomic and could fail.
+/// - The operation will fail if the source does not exist.
+/// - An existing destination file is replaced, unless another [`Overwrite`] policy
+///   is set.
+/// - The operation will fail if the destination directory does not exist, unless
+///   [`create_parents`](Move::create_parents) is set.
+/// - Moves that fall back to copying are not atomic. The copies keep the permissions of
+///   the originals, and symbolic links are recreated rather than followed.
+/// - The source path is used as given. Inside a
+///   [`Transaction`](crate::transaction::Transaction), [`Sequence`](crate::sequence::Sequence)
+///   or [`At`](crate::sequence::At), only the destination is resolved against the path they
+///   are forged into.
 pub struct Move {
     /// The source path of the file to be moved
     from: PathBuf,
+    /// Whether missing parent directories of the destination are created
+    create_parents: bool,
+    /// What to do when a destination file already exists
+    policy: Overwrite,
 }
 
 impl Move {
@@ -44,55 +73,205 @@ impl Move {
     ///
     /// # Parameters
     ///
-    /// * `from` - The path to the file that will be moved or renamed
+    /// * `from` - The path to the file or directory that will be moved or renamed
     ///
     /// # Returns
     ///
-    /// A new `Move` instance configured with the provided source path.
+    /// A new `Move` instance configured with the provided source path, which replaces
+    /// existing destination files.
     pub fn new(from: impl AsRef<Path>) -> Self {
         Self {
             from: from.as_ref().to_path_buf(),
+            create_parents: false,
+            policy: Overwrite::Force,
+        }
+    }
+
+    /// Creates any missing parent directories of the destination before moving.
+    ///
+    /// # Returns
+    ///
+    /// The `Move` instance with parent creation enabled.
+    pub fn create_parents(mut self) -> Self {
+        self.create_parents = true;
+        self
+    }
+
+    /// Sets what happens when a destination file already exists.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy` - The [`Overwrite`] policy to apply to each moved file
+    ///
+    /// # Returns
+    ///
+    /// The `Move` instance with the policy applied.
+    pub fn policy(mut self, policy: Overwrite) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Moves the file or directory at `from` to `to`, applying the policy to whatever
+    /// already exists there.
+    fn relocate(&self, fs: &dyn FileSystem, from: &Path, to: &Path) -> io::Result<()> {
+        let source = fs.metadata(from)?;
+        let Ok(destination) = fs.metadata(to) else {
+            return match fs.rename(from, to) {
+                Err(e) if crosses_devices(&e) => {
+                    copy_tree(fs, from, to)?;
+                    remove_tree(fs, from)
+                }
+                result => result,
+            };
+        };
+
+        if source.is_dir() && destination.is_dir() {
+            for entry in fs.read_dir(from)? {
+                let name = entry.file_name().expect("directory entries have names");
+                self.relocate(fs, &entry, &to.join(name))?;
+            }
+            // Files that were skipped keep the source directory alive
+            return match fs.remove_dir(from) {
+                Err(e) if e.kind() == io::ErrorKind::DirectoryNotEmpty => Ok(()),
+                result => result,
+            };
+        }
+
+        if source.is_dir() || destination.is_dir() || matches!(self.policy, Overwrite::Fail) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("destination already exists: {}", to.display()),
+            ));
+        }
+
+        if matches!(self.policy, Overwrite::Force) {
+            return match fs.rename(from, to) {
+                Err(e) if crosses_devices(&e) => {
+                    if fs.symlink_metadata(from)?.is_symlink() {
+                        fs.remove_file(to)?;
+                        copy_tree(fs, from, to)?;
+                    } else {
+                        fs.write(to, &fs.read(from)?)?;
+                        copy_mode(fs, &source, to)?;
+                    }
+                    fs.remove_file(from)
+                }
+                result => result,
+            };
+        }
+
+        let contents = fs.read(from)?;
+        match self.policy.apply(fs, to, &contents) {
+            Ok(Outcome::Skipped) => Ok(()),
+            Ok(_) => fs.remove_file(from),
+            Err(GenerateError::StdIo(e)) => Err(e),
+            Err(e) => Err(io::Error::other(e.to_string())),
+        }
+    }
+}
+
+/// The OS error a rename fails with when the destination is on another filesystem:
+/// `EXDEV` on Unix and `ERROR_NOT_SAME_DEVICE` on Windows.
+#[cfg(not(windows))]
+const EXDEV: i32 = 18;
+#[cfg(windows)]
+const EXDEV: i32 = 17;
+
+/// Returns `true` if `error` is a rename failing because it crossed filesystems.
+///
+/// `io::ErrorKind::CrossesDevices` says the same, but needs a newer Rust than this crate
+/// supports.
+fn crosses_devices(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(EXDEV)
+}
+
+/// Copies the file or directory tree at `from` to `to`, which must not exist yet.
+///
+/// Symbolic links are recreated rather than followed, and files and directories keep
+/// their permission bits.
+fn copy_tree(fs: &dyn FileSystem, from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs.symlink_metadata(from)?;
+    if metadata.is_symlink() {
+        return fs.symlink(&fs.read_link(from)?, to);
+    }
+    if metadata.is_dir() {
+        fs.create_dir_all(to)?;
+        for entry in fs.read_dir(from)? {
+            let name = entry.file_name().expect("directory entries have names");
+            copy_tree(fs, &entry, &to.join(name))?;
         }
+    } else {
+        fs.create_new(to, &fs.read(from)?)?;
+    }
+    copy_mode(fs, &metadata, to)
+}
+
+/// Gives `to` the permission bits recorded in `metadata`, if the filesystem reported any.
+fn copy_mode(fs: &dyn FileSystem, metadata: &Metadata, to: &Path) -> io::Result<()> {
+    match metadata.mode() {
+        Some(mode) => fs.set_mode(to, mode),
+        None => Ok(()),
     }
 }
 
+/// Removes the file or directory tree at `path`.
+fn remove_tree(fs: &dyn FileSystem, path: &Path) -> io::Result<()> {
+    if fs.symlink_metadata(path)?.is_dir() {
+        for entry in fs.read_dir(path)? {
+            remove_tree(fs, &entry)?;
+        }
+        return fs.remove_dir(path);
+    }
+    fs.remove_file(path)
+}
+
 impl Forge for Move {
     type Error = std::io::Error;
 
-    /// Moves or renames the file to the destination path.
+    /// Moves or renames the file or directory to the destination path.
     ///
     /// This method:
     /// 1. Takes the source path (provided during construction)
     /// 2. Takes the destination path (provided as argument)
-    /// 3. Renames/moves the file from source to destination
+    /// 3. Creates the destination's parent directories, if enabled
+    /// 4. Renames/moves the source to the destination, copying and deleting it instead
+    ///    if the destination is on another device
     ///
     /// # Parameters
     ///
+    /// * `fs` - The filesystem in which the file is moved
     /// * `into` - Destination path where the file should be moved to
     ///
     /// # Returns
     ///
     /// * `Result<(), std::io::Error>` - Ok if successful, or an error if:
-    ///   - The source file doesn't exist
-    ///   - The destination already exists
+    ///   - The source doesn't exist
+    ///   - The destination already exists and the policy does not allow replacing it
+    ///   - The destination directory doesn't exist and parents aren't created
     ///   - File permissions don't allow the operation
-    ///   - The operation crosses filesystem boundaries and is not supported
     ///
     /// # Errors
     ///
     /// Returns `std::io::Error` with various error kinds depending on what went wrong.
     /// Common error kinds include `NotFound` if the source doesn't exist, and
     /// `AlreadyExists` if the destination already exists.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
         let to = into.as_ref();
-        std::fs::rename(&self.from, to)?;
-        Ok(())
+        fs.metadata(&self.from)?;
+        if self.create_parents {
+            if let Some(parent) = to.parent() {
+                fs.create_dir_all(parent)?;
+            }
+        }
+        self.relocate(fs, &self.from, to)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
     use tempfile::tempdir;
 
     #[test]
@@ -158,20 +337,178 @@ mod tests {
         let mover = Move::new(&source_path);
         let result = mover.forge(&dest_path);
 
-        // This might fail on some systems if directories are on different filesystems
-        if result.is_ok() {
-            // Verify the file was moved
-            assert!(!source_path.exists());
-            assert!(dest_path.exists());
-
-            // Verify content was preserved
-            let content = std::fs::read_to_string(&dest_path).unwrap();
-            assert_eq!(content, test_content);
-        } else {
-            // On some systems, cross-filesystem moves will fail
-            // This is expected behavior, so we don't want the test to fail
-            assert!(result.is_err());
-            // We don't check the specific error kind since CrossesDevices is unstable
+        // Moves across directories (and devices) succeed
+        assert!(result.is_ok());
+        assert!(!source_path.exists());
+        let content = std::fs::read_to_string(&dest_path).unwrap();
+        assert_eq!(content, test_content);
+    }
+
+    /// A filesystem where every rename fails as if it crossed devices.
+    #[cfg(any(unix, feature = "memory"))]
+    struct CrossDevice<F: FileSystem>(F);
+
+    #[cfg(any(unix, feature = "memory"))]
+    impl<F: FileSystem> FileSystem for CrossDevice<F> {
+        fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+            self.0.read(path)
+        }
+
+        fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            self.0.write(path, contents)
+        }
+
+        fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            self.0.create_new(path, contents)
+        }
+
+        fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            self.0.append(path, contents)
+        }
+
+        fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+            Err(io::Error::from_raw_os_error(EXDEV))
+        }
+
+        fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+            self.0.create_dir_all(path)
+        }
+
+        fn remove_file(&self, path: &Path) -> io::Result<()> {
+            self.0.remove_file(path)
+        }
+
+        fn remove_dir(&self, path: &Path) -> io::Result<()> {
+            self.0.remove_dir(path)
+        }
+
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            self.0.read_dir(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+            self.0.metadata(path)
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+            self.0.symlink_metadata(path)
+        }
+
+        fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+            self.0.read_link(path)
+        }
+
+        fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+            self.0.symlink(target, link)
         }
+
+        fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+            self.0.set_mode(path, mode)
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_move_falls_back_to_copy_across_devices() {
+        let fs = CrossDevice(
+            Memory::new()
+                .with_file("src/dir/a.txt", "a")
+                .with_file("src/dir/sub/b.txt", "b"),
+        );
+
+        Move::new("src/dir").forge_in(&fs, "dest").unwrap();
+
+        assert!(!fs.exists(Path::new("src/dir")));
+        assert_eq!(fs.read(Path::new("dest/a.txt")).unwrap(), b"a");
+        assert_eq!(fs.read(Path::new("dest/sub/b.txt")).unwrap(), b"b");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_move_across_devices_keeps_modes_and_links() {
+        use crate::fs::Disk;
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        // A directory with an executable script, a link to it and a link to a directory
+        let temp_dir = tempdir().unwrap();
+        let from = temp_dir.path().join("from");
+        std::fs::create_dir_all(from.join("bin")).unwrap();
+        std::fs::write(from.join("bin/run.sh"), "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(
+            from.join("bin/run.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        symlink("bin/run.sh", from.join("run")).unwrap();
+        symlink("bin", from.join("tools")).unwrap();
+        let single = temp_dir.path().join("single.sh");
+        std::fs::copy(from.join("bin/run.sh"), &single).unwrap();
+        let replaced = temp_dir.path().join("replaced.sh");
+        std::fs::write(&replaced, "old").unwrap();
+
+        let fs = CrossDevice(Disk);
+        let to = temp_dir.path().join("to");
+        Move::new(&from).forge_in(&fs, &to).unwrap();
+        Move::new(&single).forge_in(&fs, &replaced).unwrap();
+
+        assert!(!from.exists());
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&to.join("bin/run.sh")), 0o755);
+        assert_eq!(mode(&replaced), 0o755);
+        assert_eq!(
+            std::fs::read_link(to.join("run")).unwrap(),
+            Path::new("bin/run.sh")
+        );
+        assert_eq!(
+            std::fs::read_link(to.join("tools")).unwrap(),
+            Path::new("bin")
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_move_creates_parents_when_asked() {
+        let fs = Memory::new().with_file("a.txt", "a");
+
+        let result = Move::new("a.txt").forge_in(&fs, "new/dir/a.txt");
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        Move::new("a.txt")
+            .create_parents()
+            .forge_in(&fs, "new/dir/a.txt")
+            .unwrap();
+        assert_eq!(fs.read(Path::new("new/dir/a.txt")).unwrap(), b"a");
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_move_applies_overwrite_policy() {
+        let fs = Memory::new()
+            .with_file("from/a.txt", "new a")
+            .with_file("from/b.txt", "new b")
+            .with_file("to/a.txt", "old a");
+
+        // Fails when asked to, whatever the platform would do
+        let result = Move::new("from/a.txt")
+            .policy(Overwrite::Fail)
+            .forge_in(&fs, "to/a.txt");
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+
+        // Skipped files stay behind, the rest of the directory is merged in
+        Move::new("from")
+            .policy(Overwrite::Skip)
+            .forge_in(&fs, "to")
+            .unwrap();
+        assert_eq!(fs.read(Path::new("to/a.txt")).unwrap(), b"old a");
+        assert_eq!(fs.read(Path::new("to/b.txt")).unwrap(), b"new b");
+        assert!(fs.exists(Path::new("from/a.txt")));
+
+        Move::new("from").forge_in(&fs, "to").unwrap();
+        assert_eq!(fs.read(Path::new("to/a.txt")).unwrap(), b"new a");
+        assert!(!fs.exists(Path::new("from")));
     }
 }
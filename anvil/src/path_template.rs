@@ -0,0 +1,616 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    path::{Component, Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    fs::{Disk, FileSystem},
+    generate_dir::Renderer,
+    Forge,
+};
+
+/// A template for the path a file is generated at.
+///
+/// Destination paths are usually derived from the same data as the file's contents, such as
+/// `src/controllers/user_profile.rs` for a `UserProfile` controller. Building them by hand
+/// with `format!` repeats the naming rules in every generator and makes it easy to produce
+/// an empty file name or a path that escapes the project. A `PathTemplate` is rendered with a
+/// [`Renderer`] instead, so the path uses the same context and filters as the contents, and
+/// the result is validated before anything is written.
+///
+/// A rendered path is rejected if it is empty, has an empty segment (as in `src//mod.rs` or
+/// `src/controllers/`), has a `..` segment, or is absolute (as in `/etc/passwd` or
+/// `C:\Windows`). Rendered paths are always relative to the directory they are placed in,
+/// so a variable can't move a file outside of it.
+///
+/// Use a backend's renderer to render with a template engine, or [`Vars`] for simple
+/// substitutions with the built-in case conversion filters.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::path_template::{PathTemplate, Vars};
+/// use std::path::Path;
+///
+/// let path = PathTemplate::new("src/controllers/{{ name | snakecase }}.rs");
+/// let vars = Vars::new().var("name", "UserProfile");
+///
+/// assert_eq!(
+///     path.render(&vars).unwrap(),
+///     Path::new("src/controllers/user_profile.rs")
+/// );
+///
+/// // An empty name is caught rather than generating `src/controllers/.rs`
+/// let path = PathTemplate::new("src/{{ module }}/mod.rs");
+/// assert!(path.render(&Vars::new().var("module", "")).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate {
+    /// The template source of the path
+    source: String,
+}
+
+/// Errors that can occur while rendering a [`PathTemplate`].
+#[derive(Error, Debug)]
+pub enum PathTemplateError {
+    /// Error that occurred while rendering the template.
+    #[error("failed to render path template {template:?}: {source}")]
+    Template {
+        /// The source of the path template
+        template: String,
+        /// The error the renderer failed with
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// The template rendered to a path that can't be generated.
+    #[error("path template {template:?} rendered to {path:?}, which {reason}")]
+    Invalid {
+        /// The source of the path template
+        template: String,
+        /// The rendered path
+        path: String,
+        /// Why the path was rejected
+        reason: &'static str,
+    },
+}
+
+impl PathTemplate {
+    /// Creates a new path template.
+    ///
+    /// # Parameters
+    ///
+    /// * `source` - The template source of the path, in the syntax of the renderer it will
+    ///   be rendered with
+    ///
+    /// # Returns
+    ///
+    /// A new `PathTemplate` instance.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    /// Returns the template source of the path.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Renders the path and checks that it can be generated.
+    ///
+    /// # Parameters
+    ///
+    /// * `renderer` - The [`Renderer`] that holds the context to render against
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf, PathTemplateError>` - The rendered path, or an error if:
+    ///   - The template fails to render
+    ///   - The rendered path is empty, absolute, or has an empty or `..` segment
+    pub fn render(&self, renderer: &impl Renderer) -> Result<PathBuf, PathTemplateError> {
+        let rendered = renderer
+            .render(&self.source)
+            .map_err(|e| PathTemplateError::Template {
+                template: self.source.clone(),
+                source: Box::new(e),
+            })?;
+        self.validate(rendered)
+    }
+
+    fn validate(&self, rendered: String) -> Result<PathBuf, PathTemplateError> {
+        let invalid = |reason| PathTemplateError::Invalid {
+            template: self.source.clone(),
+            path: rendered.clone(),
+            reason,
+        };
+        if rendered.trim().is_empty() {
+            return Err(invalid("is empty"));
+        }
+
+        // Joining an absolute path onto the target directory would replace it entirely.
+        // Drive prefixes are checked by hand so that `C:\` is rejected on every platform.
+        let path = Path::new(&rendered);
+        let bytes = rendered.as_bytes();
+        let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+        if rendered.starts_with(['/', '\\'])
+            || has_drive
+            || path.has_root()
+            || matches!(path.components().next(), Some(Component::Prefix(_)))
+        {
+            return Err(invalid("is absolute"));
+        }
+
+        for segment in rendered.split(['/', '\\']) {
+            if segment.trim().is_empty() {
+                return Err(invalid("has an empty segment"));
+            }
+            if segment == ".." {
+                return Err(invalid("has a `..` segment"));
+            }
+        }
+        Ok(PathBuf::from(rendered))
+    }
+}
+
+impl From<&str> for PathTemplate {
+    fn from(source: &str) -> Self {
+        Self::new(source)
+    }
+}
+
+impl From<String> for PathTemplate {
+    fn from(source: String) -> Self {
+        Self::new(source)
+    }
+}
+
+/// A [`Renderer`] that substitutes named variables, for rendering paths without a template
+/// engine.
+///
+/// `Vars` replaces `{{ name }}` with the value of the variable `name`. Values can be piped
+/// through filters, as in `{{ name | snakecase }}`. The filters are the same case
+/// conversions that the askama backend provides for template contents:
+///
+/// * `snakecase` - `user_profile`
+/// * `kebabcase` - `user-profile`
+/// * `camelcase` - `userProfile`
+/// * `pascalcase` - `UserProfile`
+/// * `titlecase` - `User Profile`
+/// * `lowercase` and `uppercase`
+///
+/// Using an undefined variable or an unknown filter is an error.
+///
+/// # Examples
+///
+/// ```
+/// use anvil::{generate_dir::Renderer, path_template::Vars};
+///
+/// let vars = Vars::new().var("name", "user profile");
+///
+/// assert_eq!(
+///     vars.render("{{ name | pascalcase }}Controller").unwrap(),
+///     "UserProfileController"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vars {
+    /// The value of each variable
+    vars: BTreeMap<String, String>,
+}
+
+/// Errors that can occur while rendering with [`Vars`].
+#[derive(Error, Debug)]
+pub enum VarsError {
+    /// A variable was used without being defined.
+    #[error("undefined variable `{0}`")]
+    Undefined(String),
+
+    /// A filter was used that doesn't exist.
+    #[error("unknown filter `{0}`")]
+    UnknownFilter(String),
+
+    /// A `{{` was not closed by `}}`.
+    #[error("unclosed `{{{{` in {0:?}")]
+    Unclosed(String),
+}
+
+impl Vars {
+    /// Creates an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines a variable.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - The name of the variable
+    /// * `value` - The value to substitute for it
+    ///
+    /// # Returns
+    ///
+    /// The `Vars` instance with the variable defined.
+    pub fn var(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.vars.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Evaluates the inside of a `{{ }}` tag.
+    fn evaluate(&self, expression: &str) -> Result<String, VarsError> {
+        let mut parts = expression.split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let mut value = self
+            .vars
+            .get(name)
+            .cloned()
+            .ok_or_else(|| VarsError::Undefined(name.to_string()))?;
+
+        for filter in parts {
+            let words = words(&value);
+            value = match filter {
+                "snakecase" => join(&words, "_", str::to_lowercase),
+                "kebabcase" => join(&words, "-", str::to_lowercase),
+                "pascalcase" => join(&words, "", capitalize),
+                "camelcase" => {
+                    let pascal = join(&words, "", capitalize);
+                    let mut chars = pascal.chars();
+                    match chars.next() {
+                        Some(first) => first.to_lowercase().chain(chars).collect(),
+                        None => pascal,
+                    }
+                }
+                "titlecase" => join(&words, " ", capitalize),
+                "lowercase" => value.to_lowercase(),
+                "uppercase" => value.to_uppercase(),
+                _ => return Err(VarsError::UnknownFilter(filter.to_string())),
+            };
+        }
+        Ok(value)
+    }
+}
+
+impl Renderer for Vars {
+    type Error = VarsError;
+
+    fn render(&self, source: &str) -> Result<String, Self::Error> {
+        let mut output = String::with_capacity(source.len());
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let tag = &rest[start + 2..];
+            let end = tag
+                .find("}}")
+                .ok_or_else(|| VarsError::Unclosed(source.to_string()))?;
+            output.push_str(&self.evaluate(tag[..end].trim())?);
+            rest = &tag[end + 2..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// Splits `text` into words at separators and at changes of case, so that `UserProfile`,
+/// `user_profile` and `user-profile` all become `["User", "Profile"]` or equivalent.
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (index, current) = chars[i];
+            let previous = chars[i - 1].1;
+            let next_is_lower = chars.get(i + 1).is_some_and(|(_, c)| c.is_lowercase());
+            // Split before an uppercase letter that starts a new word, keeping acronyms
+            // such as `HTTP` in `HTTPServer` together
+            if current.is_uppercase() && (!previous.is_uppercase() || next_is_lower) {
+                words.push(&part[start..index]);
+                start = index;
+            }
+        }
+        if start < part.len() {
+            words.push(&part[start..]);
+        }
+    }
+    words
+}
+
+fn join(words: &[&str], separator: &str, case: impl Fn(&str) -> String) -> String {
+    words
+        .iter()
+        .map(|word| case(word))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// A struct that runs an operation at a path rendered from a [`PathTemplate`].
+///
+/// `Placed` attaches a destination to an operation, so that callers don't have to work out
+/// where its output goes. [`place`](Placed::place) runs the operation at the rendered path
+/// without being given one. As a [`Forge`], the rendered path is taken relative to the path
+/// it is forged into, which makes it easy to generate into a project root.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{generate::Generate, path_template::{Placed, Vars}};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Controller;
+/// # impl Anvil for Controller {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// let vars = Vars::new().var("name", "UserProfile");
+/// let controller = Placed::new(
+///     Generate::new(Controller),
+///     "src/controllers/{{ name | snakecase }}.rs",
+///     vars,
+/// );
+///
+/// // Generates src/controllers/user_profile.rs
+/// let path = controller.place().unwrap();
+/// println!("created {}", path.display());
+/// ```
+pub struct Placed<F: Forge, R: Renderer> {
+    /// The operation to run
+    forge: F,
+    /// Where to run it
+    path: PathTemplate,
+    /// The context to render the path with
+    renderer: R,
+}
+
+/// Errors that can occur when running a [`Placed`] operation.
+#[derive(Error, Debug)]
+pub enum PlacedError<E: Error + 'static> {
+    /// The wrapped operation failed.
+    #[error(transparent)]
+    Forge(E),
+
+    /// The path could not be rendered.
+    #[error(transparent)]
+    Path(#[from] PathTemplateError),
+}
+
+impl<F: Forge, R: Renderer> Placed<F, R>
+where
+    F::Error: 'static,
+{
+    /// Attaches a path template to an operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `path` - The template of the path to run it at
+    /// * `renderer` - The [`Renderer`] to render the path with
+    ///
+    /// # Returns
+    ///
+    /// A new `Placed` instance.
+    pub fn new(forge: F, path: impl Into<PathTemplate>, renderer: R) -> Self {
+        Self {
+            forge,
+            path: path.into(),
+            renderer,
+        }
+    }
+
+    /// Renders the path the operation runs at, without running it.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf, PathTemplateError>` - The rendered path, or an error if it fails
+    ///   to render or is invalid.
+    pub fn path(&self) -> Result<PathBuf, PathTemplateError> {
+        self.path.render(&self.renderer)
+    }
+
+    /// Runs the operation at the rendered path on the real disk.
+    ///
+    /// This is shorthand for calling [`place_in`](Placed::place_in) with [`Disk`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf, PlacedError<F::Error>>` - The path the operation ran at, or an
+    ///   error under the same conditions as [`place_in`](Placed::place_in).
+    pub fn place(&self) -> Result<PathBuf, PlacedError<F::Error>> {
+        self.place_in(&Disk)
+    }
+
+    /// Runs the operation at the rendered path against the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to run the operation in
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf, PlacedError<F::Error>>` - The path the operation ran at, or an
+    ///   error if:
+    ///   - The path fails to render or is invalid
+    ///   - The wrapped operation fails
+    pub fn place_in(&self, fs: &dyn FileSystem) -> Result<PathBuf, PlacedError<F::Error>> {
+        let path = self.path()?;
+        self.forge.forge_in(fs, &path).map_err(PlacedError::Forge)?;
+        Ok(path)
+    }
+}
+
+impl<F: Forge, R: Renderer> Forge for Placed<F, R>
+where
+    F::Error: 'static,
+{
+    type Error = PlacedError<F::Error>;
+
+    /// Runs the operation at the rendered path, relative to the given directory.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to run the operation in
+    /// * `into` - The directory the rendered path is relative to
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), PlacedError<F::Error>>` - Ok if successful, or an error if:
+    ///   - The path fails to render or is invalid
+    ///   - The wrapped operation fails
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref().join(self.path()?);
+        self.forge.forge_in(fs, path).map_err(PlacedError::Forge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::{fs::Memory, generate::Generate, Anvil};
+
+    // Mock implementation for Anvil
+    #[cfg(feature = "memory")]
+    struct MockAnvil {
+        content: String,
+    }
+
+    #[cfg(feature = "memory")]
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_vars_case_filters() {
+        let vars = Vars::new()
+            .var("pascal", "UserProfile")
+            .var("acronym", "HTTPServer")
+            .var("words", "user profile-page");
+
+        let render = |source| vars.render(source).unwrap();
+        assert_eq!(render("{{ pascal | snakecase }}"), "user_profile");
+        assert_eq!(render("{{ pascal | kebabcase }}"), "user-profile");
+        assert_eq!(render("{{ pascal|camelcase }}"), "userProfile");
+        assert_eq!(render("{{ acronym | snakecase }}"), "http_server");
+        assert_eq!(render("{{ words | pascalcase }}"), "UserProfilePage");
+        assert_eq!(render("{{ words | titlecase }}"), "User Profile Page");
+        assert_eq!(
+            render("{{ pascal | snakecase | uppercase }}"),
+            "USER_PROFILE"
+        );
+
+        assert!(matches!(
+            vars.render("{{ missing }}"),
+            Err(VarsError::Undefined(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            vars.render("{{ pascal | shout }}"),
+            Err(VarsError::UnknownFilter(name)) if name == "shout"
+        ));
+        assert!(matches!(
+            vars.render("{{ pascal"),
+            Err(VarsError::Unclosed(_))
+        ));
+    }
+
+    #[test]
+    fn test_path_template_rejects_invalid_paths() {
+        let render = |source: &str, name: &str| {
+            PathTemplate::new(source).render(&Vars::new().var("name", name))
+        };
+
+        assert_eq!(
+            render("srv/{{ name }}/main.rs", "app").unwrap(),
+            Path::new("srv/app/main.rs")
+        );
+        assert!(matches!(
+            render("{{ name }}", ""),
+            Err(PathTemplateError::Invalid {
+                reason: "is empty",
+                ..
+            })
+        ));
+        assert!(matches!(
+            render("src/{{ name }}/mod.rs", ""),
+            Err(PathTemplateError::Invalid {
+                reason: "has an empty segment",
+                ..
+            })
+        ));
+        assert!(matches!(
+            render("src/{{ name }}.rs", "../../etc/passwd"),
+            Err(PathTemplateError::Invalid {
+                reason: "has a `..` segment",
+                ..
+            })
+        ));
+        for name in ["/etc/x", "\\etc\\x", "C:\\Windows\\x", "c:x"] {
+            assert!(matches!(
+                render("{{ name }}/mod.rs", name),
+                Err(PathTemplateError::Invalid {
+                    reason: "is absolute",
+                    ..
+                })
+            ));
+        }
+        assert!(matches!(
+            render("src/{{ other }}.rs", "app"),
+            Err(PathTemplateError::Template { .. })
+        ));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_placed_runs_operation_at_rendered_path() {
+        let fs = Memory::new().with_dir("project");
+        let placed = Placed::new(
+            Generate::new(MockAnvil {
+                content: "controller".to_string(),
+            }),
+            "src/controllers/{{ name | snakecase }}.rs",
+            Vars::new().var("name", "UserProfile"),
+        );
+
+        // Relative to the directory it is forged into
+        placed.forge_in(&fs, "project").unwrap();
+        assert_eq!(
+            fs.read(Path::new("project/src/controllers/user_profile.rs"))
+                .unwrap(),
+            b"controller"
+        );
+
+        // Or on its own
+        let path = placed.place_in(&fs).unwrap();
+        assert_eq!(path, Path::new("src/controllers/user_profile.rs"));
+        assert!(fs.exists(&path));
+
+        // Nothing is generated when the path is invalid
+        let placed = Placed::new(
+            Generate::new(MockAnvil {
+                content: "controller".to_string(),
+            }),
+            "src/{{ name }}/mod.rs",
+            Vars::new().var("name", ""),
+        );
+        assert!(matches!(placed.place_in(&fs), Err(PlacedError::Path(_))));
+    }
+}
@@ -0,0 +1,825 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fs::{is_root, normalize, Disk, FileSystem, Metadata, Savepoint},
+    Forge,
+};
+
+/// A single change that a [`Plan`] recorded instead of performing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// A directory (and any missing parents) would be created.
+    CreateDir {
+        /// The directory that would be created
+        path: PathBuf,
+    },
+    /// A new file would be created with the given contents.
+    Create {
+        /// The file that would be created
+        path: PathBuf,
+        /// The rendered contents of the new file
+        contents: Vec<u8>,
+    },
+    /// Content would be appended to an existing file.
+    Append {
+        /// The file that would be appended to
+        path: PathBuf,
+        /// The rendered content that would be appended
+        contents: Vec<u8>,
+    },
+    /// An existing file would be replaced with new contents, as a [`Transform`](crate::transform::Transform) does.
+    Overwrite {
+        /// The file that would be overwritten
+        path: PathBuf,
+        /// The new contents of the file
+        contents: Vec<u8>,
+    },
+    /// A file would be removed.
+    Remove {
+        /// The file that would be removed
+        path: PathBuf,
+    },
+    /// An empty directory would be removed.
+    RemoveDir {
+        /// The directory that would be removed
+        path: PathBuf,
+    },
+    /// A file or directory would be moved.
+    Rename {
+        /// The current path of the file or directory
+        from: PathBuf,
+        /// The path it would be moved to
+        to: PathBuf,
+    },
+    /// An operation failed and its steps were discarded in favour of a fallback, as
+    /// happens when the left side of an [`Either`](crate::either::Either) fails.
+    Fallback {
+        /// The error that made the operation fail
+        cause: String,
+        /// How many recorded steps were discarded
+        discarded: usize,
+    },
+}
+
+impl Step {
+    /// Returns the short label used when printing this step, such as `create` or `append`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Step::CreateDir { .. } => "mkdir",
+            Step::Create { .. } => "create",
+            Step::Append { .. } => "append",
+            Step::Overwrite { .. } => "overwrite",
+            Step::Remove { .. } => "remove",
+            Step::RemoveDir { .. } => "rmdir",
+            Step::Rename { .. } => "rename",
+            Step::Fallback { .. } => "fallback",
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = self.label();
+        match self {
+            Step::CreateDir { path } | Step::Remove { path } | Step::RemoveDir { path } => {
+                write!(f, "{label:>9}  {}", path.display())
+            }
+            Step::Create { path, contents }
+            | Step::Append { path, contents }
+            | Step::Overwrite { path, contents } => {
+                write!(f, "{label:>9}  {}", path.display())?;
+                match std::str::from_utf8(contents) {
+                    Ok(text) => text
+                        .lines()
+                        .try_for_each(|line| write!(f, "\n{:>9}  | {line}", "")),
+                    Err(_) => write!(f, "\n{:>9}  | <{} bytes of binary data>", "", contents.len()),
+                }
+            }
+            Step::Rename { from, to } => {
+                write!(f, "{label:>9}  {} -> {}", from.display(), to.display())
+            }
+            Step::Fallback { cause, discarded } => write!(
+                f,
+                "{label:>9}  operation failed ({cause}); discarded {discarded} step(s) and tried the alternative"
+            ),
+        }
+    }
+}
+
+/// The state of a path in a [`Plan`]'s view of the filesystem, where it differs from the base.
+#[derive(Debug, Clone)]
+enum Node {
+    File(Vec<u8>),
+    Dir,
+    Removed,
+    /// A directory that was moved here from the given path in the base filesystem
+    MovedFrom(PathBuf),
+}
+
+/// Where a path's current state can be found.
+enum Resolved {
+    File(Vec<u8>),
+    Dir,
+    Missing,
+    Base(PathBuf),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    steps: Vec<Step>,
+    overlay: BTreeMap<PathBuf, Node>,
+    /// What each overlay entry was before it was changed, while any savepoint is held
+    undo: Vec<(PathBuf, Option<Node>)>,
+    /// The number of steps and the length of the undo log at each savepoint taken
+    savepoints: Vec<(usize, usize)>,
+}
+
+impl State {
+    /// Sets or clears the overlay entry for `path`, remembering the old one so that it can
+    /// be restored by a rollback.
+    fn set(&mut self, path: PathBuf, node: Option<Node>) {
+        let previous = match node {
+            Some(node) => self.overlay.insert(path.clone(), node),
+            None => self.overlay.remove(&path),
+        };
+        if !self.savepoints.is_empty() {
+            self.undo.push((path, previous));
+        }
+    }
+
+    fn resolve(&self, path: &Path) -> Resolved {
+        match self.overlay.get(path) {
+            Some(Node::File(contents)) => return Resolved::File(contents.clone()),
+            Some(Node::Dir) => return Resolved::Dir,
+            Some(Node::Removed) => return Resolved::Missing,
+            Some(Node::MovedFrom(source)) => return Resolved::Base(source.clone()),
+            None => {}
+        }
+        for ancestor in path.ancestors().skip(1) {
+            match self.overlay.get(ancestor) {
+                Some(Node::File(_)) | Some(Node::Removed) => return Resolved::Missing,
+                Some(Node::MovedFrom(source)) => {
+                    let suffix = path.strip_prefix(ancestor).expect("ancestor of path");
+                    return Resolved::Base(source.join(suffix));
+                }
+                Some(Node::Dir) | None => {}
+            }
+        }
+        Resolved::Base(path.to_path_buf())
+    }
+}
+
+/// A [`FileSystem`] that records what operations would do instead of doing it.
+///
+/// A `Plan` reads from a base filesystem (the real disk by default) but never writes to
+/// it. Every create, append, overwrite, rename and directory creation is recorded as a
+/// [`Step`] along with the rendered contents, and kept in an overlay so that later
+/// operations in the same run see the effects of earlier ones. This makes it possible to
+/// preview any [`Forge`] composition, including [`Either`](crate::either::Either)
+/// fallbacks, which are recorded as [`Step::Fallback`] so the plan shows which branch
+/// would be taken.
+///
+/// A `Plan` implements [`Display`](std::fmt::Display), printing one line per step
+/// followed by the rendered contents.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Anvil, Forge, generate::Generate, plan::Plan};
+/// use std::io::Write;
+///
+/// struct Readme;
+///
+/// impl Anvil for Readme {
+///     type Error = std::io::Error;
+///
+///     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+///         writer.write_all(b"# Hello\n")
+///     }
+/// }
+///
+/// let plan = Plan::new();
+/// Generate::new(Readme).forge_in(&plan, "./docs/README.md").unwrap();
+///
+/// // Nothing has been written to disk; print what would have happened instead
+/// print!("{plan}");
+/// ```
+pub struct Plan<'a> {
+    /// The filesystem that is read from but never written to
+    base: &'a dyn FileSystem,
+    /// Recorded steps and the overlay of their effects
+    state: RefCell<State>,
+}
+
+impl Plan<'static> {
+    /// Creates a new, empty plan that reads from the real disk.
+    pub fn new() -> Self {
+        Self::over(&Disk)
+    }
+}
+
+impl Default for Plan<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Plan<'a> {
+    /// Creates a new, empty plan that reads from the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `base` - The filesystem whose current state the plan starts from. It is never
+    ///   written to.
+    pub fn over(base: &'a dyn FileSystem) -> Self {
+        Self {
+            base,
+            state: RefCell::new(State::default()),
+        }
+    }
+
+    /// Returns the steps recorded so far, in the order they would be performed.
+    pub fn steps(&self) -> Vec<Step> {
+        self.state.borrow().steps.clone()
+    }
+
+    /// Returns `true` if no steps have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().steps.is_empty()
+    }
+
+    /// Returns the filesystem the plan reads from.
+    pub(crate) fn base(&self) -> &'a dyn FileSystem {
+        self.base
+    }
+
+    fn resolve(&self, path: &Path) -> Resolved {
+        if is_root(path) {
+            return Resolved::Dir;
+        }
+        self.state.borrow().resolve(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        match self.resolve(path) {
+            Resolved::Dir => true,
+            Resolved::File(_) | Resolved::Missing => false,
+            Resolved::Base(base) => self.base.metadata(&base).is_ok_and(|m| m.is_dir()),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        match self.resolve(path) {
+            Resolved::File(_) => true,
+            Resolved::Dir | Resolved::Missing => false,
+            Resolved::Base(base) => self.base.metadata(&base).is_ok_and(|m| m.is_file()),
+        }
+    }
+
+    fn check_parent(&self, path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) if !self.is_dir(parent) => Err(not_found(parent)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a step that leaves `contents` in the file at `path`.
+    fn record(&self, step: Step, path: &Path, contents: Vec<u8>) {
+        let mut state = self.state.borrow_mut();
+        state.set(path.to_path_buf(), Some(Node::File(contents)));
+        state.steps.push(step);
+    }
+}
+
+impl FileSystem for Plan<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.resolve(&path) {
+            Resolved::File(contents) => Ok(contents),
+            Resolved::Dir => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("is a directory: {}", path.display()),
+            )),
+            Resolved::Missing => Err(not_found(&path)),
+            Resolved::Base(base) => self.base.read(&base),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        self.check_parent(&path)?;
+        let step = if self.is_file(&path) {
+            Step::Overwrite {
+                path: path.clone(),
+                contents: contents.to_vec(),
+            }
+        } else {
+            Step::Create {
+                path: path.clone(),
+                contents: contents.to_vec(),
+            }
+        };
+        self.record(step, &path, contents.to_vec());
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        if self.exists(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("file already exists: {}", path.display()),
+            ));
+        }
+        self.check_parent(&path)?;
+        let step = Step::Create {
+            path: path.clone(),
+            contents: contents.to_vec(),
+        };
+        self.record(step, &path, contents.to_vec());
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        let mut existing = self.read(&path)?;
+        existing.extend_from_slice(contents);
+        let step = Step::Append {
+            path: path.clone(),
+            contents: contents.to_vec(),
+        };
+        self.record(step, &path, existing);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        let node = match self.resolve(&from) {
+            Resolved::File(contents) => Node::File(contents),
+            Resolved::Dir => Node::Dir,
+            Resolved::Missing => return Err(not_found(&from)),
+            Resolved::Base(base) => match self.base.metadata(&base) {
+                Ok(metadata) if metadata.is_dir() => Node::MovedFrom(base),
+                Ok(_) => Node::File(self.base.read(&base)?),
+                Err(e) => return Err(e),
+            },
+        };
+        self.check_parent(&to)?;
+
+        let mut state = self.state.borrow_mut();
+        // Carry over anything already recorded beneath the source directory
+        let nested: Vec<(PathBuf, Node)> = state
+            .overlay
+            .range(from.clone()..)
+            .take_while(|(path, _)| path.starts_with(&from))
+            .filter(|(path, _)| **path != from)
+            .map(|(path, node)| (path.clone(), node.clone()))
+            .collect();
+        for (path, node) in nested {
+            let suffix = path.strip_prefix(&from).expect("nested under source");
+            state.set(path.clone(), None);
+            state.set(to.join(suffix), Some(node));
+        }
+        state.set(from.clone(), Some(Node::Removed));
+        state.set(to.clone(), Some(node));
+        state.steps.push(Step::Rename { from, to });
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        if self.is_dir(&path) {
+            return Ok(());
+        }
+        let missing: Vec<PathBuf> = path
+            .ancestors()
+            .take_while(|ancestor| !self.is_dir(ancestor))
+            .map(Path::to_path_buf)
+            .collect();
+        if let Some(file) = missing.iter().find(|ancestor| self.is_file(ancestor)) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("a file exists at {}", file.display()),
+            ));
+        }
+        let mut state = self.state.borrow_mut();
+        for dir in missing {
+            state.set(dir, Some(Node::Dir));
+        }
+        state.steps.push(Step::CreateDir { path });
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        if !self.is_file(&path) {
+            return Err(not_found(&path));
+        }
+        let mut state = self.state.borrow_mut();
+        state.set(path.clone(), Some(Node::Removed));
+        state.steps.push(Step::Remove { path });
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        if !self.is_dir(&path) {
+            return Err(not_found(&path));
+        }
+        let mut state = self.state.borrow_mut();
+        state.set(path.clone(), Some(Node::Removed));
+        state.steps.push(Step::RemoveDir { path });
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let normalized = normalize(path);
+        let not_a_directory = || {
+            io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("not a directory: {}", normalized.display()),
+            )
+        };
+        let base = match self.resolve(&normalized) {
+            Resolved::Dir => self
+                .base
+                .metadata(path)
+                .is_ok_and(|m| m.is_dir())
+                .then(|| path.to_path_buf()),
+            Resolved::File(_) => return Err(not_a_directory()),
+            Resolved::Missing => return Err(not_found(&normalized)),
+            Resolved::Base(base) => match self.base.metadata(&base) {
+                Ok(metadata) if metadata.is_dir() => Some(base),
+                Ok(_) => return Err(not_a_directory()),
+                Err(e) => return Err(e),
+            },
+        };
+
+        // Entries from the base that still exist, plus anything the plan added
+        let mut names = BTreeSet::new();
+        if let Some(base) = base {
+            names.extend(
+                self.base
+                    .read_dir(&base)?
+                    .into_iter()
+                    .filter_map(|entry| entry.file_name().map(ToOwned::to_owned)),
+            );
+        }
+        names.extend(
+            self.state
+                .borrow()
+                .overlay
+                .keys()
+                .filter(|entry| entry.parent() == Some(normalized.as_path()))
+                .filter_map(|entry| entry.file_name().map(ToOwned::to_owned)),
+        );
+        Ok(names
+            .into_iter()
+            .filter(|name| self.exists(&normalized.join(name)))
+            .map(|name| path.join(name))
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        match self.resolve(&normalize(path)) {
+            Resolved::File(_) | Resolved::Dir => true,
+            Resolved::Missing => false,
+            Resolved::Base(base) => self.base.exists(&base),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        // Planned operations never create symbolic links, so only the base can have any
+        self.base.canonicalize(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let path = normalize(path);
+        match self.resolve(&path) {
+            Resolved::File(contents) => Ok(Metadata::file(contents.len() as u64)),
+            Resolved::Dir => Ok(Metadata::dir()),
+            Resolved::Missing => Err(not_found(&path)),
+            Resolved::Base(base) => self.base.metadata(&base),
+        }
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let path = normalize(path);
+        match self.resolve(&path) {
+            Resolved::File(contents) => Ok(Metadata::file(contents.len() as u64)),
+            Resolved::Dir => Ok(Metadata::dir()),
+            Resolved::Missing => Err(not_found(&path)),
+            // Planned operations never create symbolic links, so only the base can have any
+            Resolved::Base(base) => self.base.symlink_metadata(&base),
+        }
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(path);
+        match self.resolve(&path) {
+            Resolved::Base(base) => self.base.read_link(&base),
+            Resolved::Missing => Err(not_found(&path)),
+            Resolved::File(_) | Resolved::Dir => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("not a symbolic link: {}", path.display()),
+            )),
+        }
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        let mut state = self.state.borrow_mut();
+        let mark = (state.steps.len(), state.undo.len());
+        state.savepoints.push(mark);
+        Savepoint::new(state.savepoints.len() - 1)
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let mut state = self.state.borrow_mut();
+        if savepoint.position() >= state.savepoints.len() {
+            return;
+        }
+        let (len, undo) = state.savepoints[savepoint.position()];
+        state.savepoints.truncate(savepoint.position());
+        // Undo the overlay changes newest first, so each entry ends up as it was
+        while state.undo.len() > undo {
+            let (path, previous) = state.undo.pop().expect("undo log is longer than mark");
+            match previous {
+                Some(node) => state.overlay.insert(path, node),
+                None => state.overlay.remove(&path),
+            };
+        }
+        let discarded = state.steps.len().saturating_sub(len);
+        state.steps.truncate(len);
+        state.steps.push(Step::Fallback {
+            cause: cause.to_string(),
+            discarded,
+        });
+    }
+}
+
+impl fmt::Display for Plan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.state
+            .borrow()
+            .steps
+            .iter()
+            .try_for_each(|step| writeln!(f, "{step}"))
+    }
+}
+
+impl fmt::Debug for Plan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plan")
+            .field("steps", &self.state.borrow().steps)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs an operation against a [`Plan`] of the real disk and returns the plan.
+///
+/// This is a convenience for previewing what `forge.forge(into)` would do without
+/// touching the disk.
+///
+/// # Parameters
+///
+/// * `forge` - The operation to preview
+/// * `into` - The path the operation would be performed on
+///
+/// # Returns
+///
+/// * `Result<Plan, F::Error>` - The recorded plan, or the error the operation would fail with.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{plan::dry_run, transform::Transform};
+///
+/// let transform = Transform::new(|content| Ok(content.replace("foo", "bar")));
+/// let plan = dry_run(&transform, "./src/lib.rs").unwrap();
+/// print!("{plan}");
+/// ```
+pub fn dry_run<F: Forge>(forge: &F, into: impl AsRef<Path>) -> Result<Plan<'static>, F::Error> {
+    let plan = Plan::new();
+    forge.forge_in(&plan, into)?;
+    Ok(plan)
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no such file or directory: {}", path.display()),
+    )
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append, either::either, fs::Memory, generate::Generate, mover::Move,
+        transform::Transform, Anvil, ForgeExt,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plan_records_steps_without_touching_base() {
+        // Create a base filesystem with an existing module file
+        let base = Memory::new().with_file("src/mod.rs", "pub mod a;\n");
+        let plan = Plan::over(&base);
+
+        Generate::new(mock("pub struct B;\n"))
+            .forge_in(&plan, "src/b/b.rs")
+            .unwrap();
+        Append::new(mock("pub mod b;\n"))
+            .forge_in(&plan, "src/mod.rs")
+            .unwrap();
+
+        assert_eq!(
+            plan.steps(),
+            vec![
+                Step::CreateDir {
+                    path: PathBuf::from("src/b")
+                },
+                Step::Create {
+                    path: PathBuf::from("src/b/b.rs"),
+                    contents: b"pub struct B;\n".to_vec()
+                },
+                Step::Append {
+                    path: PathBuf::from("src/mod.rs"),
+                    contents: b"pub mod b;\n".to_vec()
+                },
+            ]
+        );
+
+        // The base filesystem is untouched
+        assert_eq!(base.snapshot().len(), 1);
+        assert_eq!(
+            base.read_to_string(Path::new("src/mod.rs")).unwrap(),
+            "pub mod a;\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_later_steps_see_earlier_effects() {
+        let base = Memory::new().with_dir("src");
+        let plan = Plan::over(&base);
+
+        // Each operation depends on the file produced by the previous one
+        Generate::new(mock("hello"))
+            .forge_in(&plan, "src/a.txt")
+            .unwrap();
+        Transform::new(|content| Ok(content.to_uppercase()))
+            .forge_in(&plan, "src/a.txt")
+            .unwrap();
+        Move::new("src/a.txt").forge_in(&plan, "src/b.txt").unwrap();
+
+        assert!(!plan.exists(Path::new("src/a.txt")));
+        assert_eq!(plan.read(Path::new("src/b.txt")).unwrap(), b"HELLO");
+        assert_eq!(
+            plan.steps().last(),
+            Some(&Step::Rename {
+                from: PathBuf::from("src/a.txt"),
+                to: PathBuf::from("src/b.txt")
+            })
+        );
+        assert!(!base.exists(Path::new("src/b.txt")));
+    }
+
+    #[test]
+    fn test_plan_shows_either_fallback() {
+        let base = Memory::new().with_file("mod.rs", "existing\n");
+        let plan = Plan::over(&base);
+
+        // Generating fails because the file exists, so the append branch is taken
+        either(
+            Generate::new(mock("generated\n")),
+            Append::new(mock("appended\n")),
+        )
+        .forge_in(&plan, "mod.rs")
+        .unwrap();
+
+        let steps = plan.steps();
+        assert_eq!(steps.len(), 2);
+        assert!(matches!(steps[0], Step::Fallback { discarded: 0, .. }));
+        assert_eq!(
+            steps[1],
+            Step::Append {
+                path: PathBuf::from("mod.rs"),
+                contents: b"appended\n".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn test_plan_discards_partial_effects_of_failed_branch() {
+        // An operation that creates the file before failing
+        struct PartialForge;
+
+        impl Forge for PartialForge {
+            type Error = std::io::Error;
+
+            fn forge_in(
+                &self,
+                fs: &dyn FileSystem,
+                into: impl AsRef<Path>,
+            ) -> Result<(), Self::Error> {
+                fs.create_new(into.as_ref(), b"partial")?;
+                Err(std::io::Error::other("Operation error"))
+            }
+        }
+
+        let base = Memory::new();
+        let plan = Plan::over(&base);
+
+        either(PartialForge, Generate::new(mock("complete")))
+            .forge_in(&plan, "file.txt")
+            .unwrap();
+
+        assert_eq!(plan.read(Path::new("file.txt")).unwrap(), b"complete");
+        assert!(matches!(
+            plan.steps()[0],
+            Step::Fallback { discarded: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_plan_rolls_back_nested_fallbacks() {
+        let base = Memory::new().with_file("mod.rs", "old\n");
+        let plan = Plan::over(&base);
+
+        // The inner fallback succeeds, but the outer left side fails after it
+        let left = Transform::new(|content| Ok(content.to_uppercase()))
+            .then(either(
+                Generate::new(mock("fails")),
+                Append::new(mock("more\n")),
+            ))
+            .then(Generate::new(mock("fails")));
+        either(left, Append::new(mock("fallback\n")))
+            .forge_in(&plan, "mod.rs")
+            .unwrap();
+
+        assert_eq!(plan.read(Path::new("mod.rs")).unwrap(), b"old\nfallback\n");
+        let steps = plan.steps();
+        assert_eq!(steps.len(), 2);
+        assert!(matches!(steps[0], Step::Fallback { discarded: 3, .. }));
+    }
+
+    #[test]
+    fn test_plan_display_includes_rendered_contents() {
+        let base = Memory::new().with_dir("src");
+        let plan = Plan::over(&base);
+
+        Generate::new(mock("line one\nline two\n"))
+            .forge_in(&plan, "src/lib.rs")
+            .unwrap();
+
+        assert_eq!(
+            plan.to_string(),
+            "   create  src/lib.rs\n           | line one\n           | line two\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_read_dir_merges_base_and_overlay() {
+        let base = Memory::new()
+            .with_file("dir/kept.txt", "kept")
+            .with_file("dir/removed.txt", "removed");
+        let plan = Plan::over(&base);
+
+        plan.remove_file(Path::new("dir/removed.txt")).unwrap();
+        plan.create_new(Path::new("dir/added.txt"), b"added")
+            .unwrap();
+
+        assert_eq!(
+            plan.read_dir(Path::new("dir")).unwrap(),
+            vec![
+                PathBuf::from("dir/added.txt"),
+                PathBuf::from("dir/kept.txt")
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    fs::{Disk, FileSystem},
+    sandbox::Rooted,
+    Forge,
+};
+
+/// Something whose presence in a directory marks it as the root of a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Marker {
+    /// An `.anvil` directory, which can be added to pin the root explicitly.
+    Anvil,
+    /// A `Cargo.toml` that declares a `[workspace]`. The `Cargo.toml` of a crate inside a
+    /// workspace doesn't count, so generators run in a member crate find the workspace root.
+    CargoWorkspace,
+    /// A `.git` directory, or the `.git` file of a worktree or submodule.
+    Git,
+    /// A `package.json` file.
+    PackageJson,
+    /// A file or directory with the given name.
+    Named(String),
+}
+
+impl Marker {
+    /// Returns `true` if the marker is present in `dir`.
+    fn is_in(&self, fs: &dyn FileSystem, dir: &Path) -> bool {
+        match self {
+            Marker::Anvil => fs.metadata(&dir.join(".anvil")).is_ok_and(|m| m.is_dir()),
+            Marker::CargoWorkspace => {
+                fs.read_to_string(&dir.join("Cargo.toml"))
+                    .is_ok_and(|manifest| {
+                        manifest.lines().any(|line| {
+                            let line = line.trim();
+                            line == "[workspace]" || line.starts_with("[workspace.")
+                        })
+                    })
+            }
+            Marker::Git => fs.exists(&dir.join(".git")),
+            Marker::PackageJson => fs
+                .metadata(&dir.join("package.json"))
+                .is_ok_and(|m| m.is_file()),
+            Marker::Named(name) => fs.exists(&dir.join(name)),
+        }
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Marker::Anvil => f.write_str(".anvil"),
+            Marker::CargoWorkspace => f.write_str("Cargo.toml with [workspace]"),
+            Marker::Git => f.write_str(".git"),
+            Marker::PackageJson => f.write_str("package.json"),
+            Marker::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Errors that can occur while looking for a project root.
+#[derive(Error, Debug)]
+pub enum ProjectRootError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while looking for the project root: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// No directory from the starting point upwards contains any of the markers.
+    #[error("no project root found above {}", start.display())]
+    NotFound {
+        /// The directory the search started from
+        start: PathBuf,
+    },
+}
+
+/// The root directory of a project, and the [`Marker`] it was recognised by.
+///
+/// Generators are often run from somewhere inside a project rather than at its root, but
+/// the files they produce belong at paths relative to the root. `ProjectRoot` finds that
+/// root by walking up from the current directory until it reaches a directory containing
+/// one of a set of markers, which can be configured with [`Discovery`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir, project_root::ProjectRoot};
+///
+/// // Works the same from the project root or any directory beneath it
+/// let root = ProjectRoot::find().unwrap();
+/// println!("found {} by its {}", root.path().display(), root.marker());
+///
+/// Mkdir::new().forge(root.path().join("src/controllers")).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectRoot {
+    /// The root directory
+    path: PathBuf,
+    /// The marker found in it
+    marker: Marker,
+}
+
+impl ProjectRoot {
+    /// Finds the project root above the current directory using the default markers.
+    ///
+    /// This is shorthand for `Discovery::new().find()`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ProjectRoot, ProjectRootError>` - The project root, or an error if none
+    ///   of the default markers are found.
+    pub fn find() -> Result<Self, ProjectRootError> {
+        Discovery::new().find()
+    }
+
+    /// Returns the root directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the marker the root was recognised by.
+    pub fn marker(&self) -> &Marker {
+        &self.marker
+    }
+
+    /// Resolves a path relative to the root.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - The path relative to the root
+    ///
+    /// # Returns
+    ///
+    /// The path joined onto the root directory.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+
+    /// Confines an operation to the root, so that it can only write inside the project.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to confine
+    ///
+    /// # Returns
+    ///
+    /// A [`Rooted`] operation whose paths are relative to the root.
+    pub fn rooted<F: Forge>(&self, forge: F) -> Rooted<F> {
+        Rooted::new(forge, &self.path)
+    }
+}
+
+/// A configurable search for a [`ProjectRoot`].
+///
+/// The search starts at a directory and moves up one parent at a time. The first directory
+/// that contains any of the markers is the root; if it contains several, the one that was
+/// added first is reported. By default the markers are, in order, [`Marker::Anvil`],
+/// [`Marker::CargoWorkspace`], [`Marker::Git`] and [`Marker::PackageJson`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::project_root::{Discovery, Marker};
+///
+/// // Only accept roots that have been set up for the generator
+/// let root = Discovery::empty()
+///     .marker(Marker::Anvil)
+///     .marker(Marker::Named("generator.toml".to_string()))
+///     .find()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    /// The markers to look for, in order of preference
+    markers: Vec<Marker>,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            markers: vec![
+                Marker::Anvil,
+                Marker::CargoWorkspace,
+                Marker::Git,
+                Marker::PackageJson,
+            ],
+        }
+    }
+}
+
+impl Discovery {
+    /// Creates a search for the default markers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a search without any markers, to be added with [`marker`](Discovery::marker).
+    pub fn empty() -> Self {
+        Self {
+            markers: Vec::new(),
+        }
+    }
+
+    /// Adds a marker to look for, with lower preference than those already added.
+    ///
+    /// # Parameters
+    ///
+    /// * `marker` - The marker to look for
+    ///
+    /// # Returns
+    ///
+    /// The `Discovery` instance with the marker added.
+    pub fn marker(mut self, marker: Marker) -> Self {
+        self.markers.push(marker);
+        self
+    }
+
+    /// Searches upwards from the current directory on the real disk.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ProjectRoot, ProjectRootError>` - The project root, or an error under the
+    ///   same conditions as [`find_in`](Discovery::find_in).
+    pub fn find(&self) -> Result<ProjectRoot, ProjectRootError> {
+        self.find_in(&Disk, std::env::current_dir()?)
+    }
+
+    /// Searches upwards from `start` in the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to search
+    /// * `start` - The directory to start from
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ProjectRoot, ProjectRootError>` - The project root, or an error if:
+    ///   - `start` doesn't exist
+    ///   - No directory from `start` upwards contains any of the markers
+    pub fn find_in(
+        &self,
+        fs: &dyn FileSystem,
+        start: impl AsRef<Path>,
+    ) -> Result<ProjectRoot, ProjectRootError> {
+        let start = fs.canonicalize(start.as_ref())?;
+        for dir in start.ancestors() {
+            if let Some(marker) = self.markers.iter().find(|marker| marker.is_in(fs, dir)) {
+                return Ok(ProjectRoot {
+                    path: dir.to_path_buf(),
+                    marker: marker.clone(),
+                });
+            }
+        }
+        Err(ProjectRootError::NotFound { start })
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::fs::Memory;
+
+    #[test]
+    fn test_discovery_finds_nearest_root_from_subdirectory() {
+        let fs = Memory::new()
+            .with_dir("repo/.git")
+            .with_file("repo/web/package.json", "{}")
+            .with_dir("repo/web/src/components");
+
+        let root = Discovery::new()
+            .find_in(&fs, "repo/web/src/components")
+            .unwrap();
+        assert_eq!(root.path(), Path::new("repo/web"));
+        assert_eq!(root.marker(), &Marker::PackageJson);
+
+        let root = Discovery::new().find_in(&fs, "repo").unwrap();
+        assert_eq!(root.path(), Path::new("repo"));
+        assert_eq!(root.marker(), &Marker::Git);
+    }
+
+    #[test]
+    fn test_cargo_workspace_marker_skips_member_crates() {
+        let fs = Memory::new()
+            .with_file("ws/Cargo.toml", "[workspace]\nmembers = [\"app\"]\n")
+            .with_file("ws/app/Cargo.toml", "[package]\nname = \"app\"\n")
+            .with_dir("ws/app/src");
+
+        let root = Discovery::new().find_in(&fs, "ws/app/src").unwrap();
+        assert_eq!(root.path(), Path::new("ws"));
+        assert_eq!(root.marker(), &Marker::CargoWorkspace);
+        assert_eq!(
+            root.join("app/src/main.rs"),
+            Path::new("ws/app/src/main.rs")
+        );
+    }
+
+    #[test]
+    fn test_discovery_uses_configured_markers() {
+        let fs = Memory::new()
+            .with_dir("project/.git")
+            .with_file("project/generator.toml", "")
+            .with_dir("project/src");
+
+        // Markers found in the same directory are reported in order of preference
+        let root = Discovery::empty()
+            .marker(Marker::Named("generator.toml".to_string()))
+            .marker(Marker::Git)
+            .find_in(&fs, "project/src")
+            .unwrap();
+        assert_eq!(root.marker(), &Marker::Named("generator.toml".to_string()));
+
+        let result = Discovery::empty()
+            .marker(Marker::Anvil)
+            .find_in(&fs, "project/src");
+        assert!(matches!(result, Err(ProjectRootError::NotFound { .. })));
+    }
+}
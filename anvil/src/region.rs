@@ -0,0 +1,416 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::{fs::FileSystem, render, Anvil, Forge};
+
+/// Errors that can occur during region operations.
+///
+/// This enum represents the different types of errors that can occur when
+/// regenerating a region using the [`Region`] struct.
+#[derive(Error, Debug)]
+pub enum RegionError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while updating region: {0}")]
+    StdIo(#[from] std::io::Error),
+
+    /// Error that occurred during template rendering.
+    #[error("failed to render template during region update: {0}")]
+    Template(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    /// The region has a begin marker without a matching end marker, or vice versa.
+    #[error("region {name:?} in {} has unbalanced begin and end markers", path.display())]
+    Unbalanced {
+        /// The name of the region
+        name: String,
+        /// The file containing the region
+        path: PathBuf,
+    },
+
+    /// The region's markers appear more than once.
+    #[error("region {name:?} appears more than once in {}", path.display())]
+    Duplicated {
+        /// The name of the region
+        name: String,
+        /// The file containing the region
+        path: PathBuf,
+    },
+}
+
+/// A struct that keeps a named, generated region of a file in sync with a template.
+///
+/// A region is the text between two marker comments:
+///
+/// ```text
+/// // anvil:begin routes
+/// ...generated content...
+/// // anvil:end routes
+/// ```
+///
+/// Every time `Region` is forged, it renders the template and replaces only the text
+/// between the markers, leaving everything outside of them untouched. If the markers are
+/// missing, they are added to the end of the file (which is created if needed), using the
+/// comment syntax for the file's extension. Markers are recognised regardless of the
+/// comment syntax surrounding them.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Anvil, Forge, region::Region};
+/// use std::io::Write;
+///
+/// // A template listing every model module
+/// struct ModelMods {
+///     models: Vec<String>,
+/// }
+///
+/// impl Anvil for ModelMods {
+///     type Error = std::io::Error;
+///
+///     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+///         for model in &self.models {
+///             writeln!(writer, "pub mod {};", model)?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mods = ModelMods {
+///     models: vec!["post".to_string(), "user".to_string()],
+/// };
+///
+/// match Region::new(mods, "models").forge("./src/models/mod.rs") {
+///     Ok(_) => println!("Module list updated"),
+///     Err(e) => eprintln!("Failed to update module list: {}", e),
+/// }
+/// ```
+pub struct Region<A: Anvil> {
+    /// The template to render between the markers
+    template: A,
+    /// The name identifying the region's markers
+    name: String,
+    /// The comment syntax to use for new markers, overriding the one for the extension
+    comment: Option<(String, String)>,
+}
+
+impl<A: Anvil> Forge for Region<A> {
+    type Error = RegionError;
+
+    /// Replaces the content of the region with the rendered template.
+    ///
+    /// This method performs all file access through the given [`FileSystem`]:
+    /// 1. Renders the template into memory
+    /// 2. Reads the file, if it exists, and finds the region's markers
+    /// 3. Replaces the text between the markers, or adds the markers and content to the
+    ///    end of the file if they are missing
+    /// 4. Writes the file back, if anything changed
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem containing the file
+    /// * `into` - Path to the file containing the region
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RegionError>` - Ok if successful, or an error if:
+    ///   - The file can't be read or written, or isn't valid UTF-8
+    ///   - The markers are unbalanced or duplicated
+    ///   - Template rendering fails
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref();
+
+        let rendered = render(&self.template).map_err(|e| RegionError::Template(Box::new(e)))?;
+        let mut rendered = String::from_utf8(rendered).map_err(|e| {
+            RegionError::StdIo(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })?;
+        if !rendered.is_empty() && !rendered.ends_with('\n') {
+            rendered.push('\n');
+        }
+
+        let existing = if fs.exists(path) {
+            Some(fs.read_to_string(path).map_err(RegionError::StdIo)?)
+        } else {
+            None
+        };
+        let content = existing.as_deref().unwrap_or_default();
+
+        let lines: Vec<&str> = content.split_inclusive('\n').collect();
+        let begins = self.find_markers(&lines, "begin");
+        let ends = self.find_markers(&lines, "end");
+
+        let updated = match (begins.as_slice(), ends.as_slice()) {
+            ([], []) => {
+                let (prefix, suffix) = self.comment_for(path);
+                let mut updated = content.to_string();
+                if !updated.is_empty() && !updated.ends_with('\n') {
+                    updated.push('\n');
+                }
+                updated.push_str(&format!("{prefix}anvil:begin {}{suffix}\n", self.name));
+                updated.push_str(&rendered);
+                updated.push_str(&format!("{prefix}anvil:end {}{suffix}\n", self.name));
+                updated
+            }
+            ([begin], [end]) if begin < end => {
+                let mut updated = lines[..=*begin].concat();
+                updated.push_str(&rendered);
+                updated.push_str(&lines[*end..].concat());
+                updated
+            }
+            ([_], [_]) | ([], [_]) | ([_], []) => {
+                return Err(RegionError::Unbalanced {
+                    name: self.name.clone(),
+                    path: path.to_path_buf(),
+                })
+            }
+            (_, _) => {
+                return Err(RegionError::Duplicated {
+                    name: self.name.clone(),
+                    path: path.to_path_buf(),
+                })
+            }
+        };
+
+        if existing.as_deref() == Some(updated.as_str()) {
+            return Ok(());
+        }
+
+        if existing.is_none() {
+            if let Some(parent) = path.parent() {
+                fs.create_dir_all(parent).map_err(RegionError::StdIo)?;
+            }
+        }
+        fs.write(path, updated.as_bytes())
+            .map_err(RegionError::StdIo)?;
+
+        Ok(())
+    }
+}
+
+impl<A: Anvil> Region<A> {
+    /// Creates a new `Region` instance with the given template and region name.
+    ///
+    /// # Parameters
+    ///
+    /// * `template` - An implementation of the [`Anvil`] trait that will be rendered
+    ///   between the region's markers.
+    /// * `name` - The name identifying the region, as in `anvil:begin <name>`. Names
+    ///   must not contain whitespace.
+    ///
+    /// # Returns
+    ///
+    /// A new `Region` instance configured with the provided template and name.
+    pub fn new(template: A, name: impl Into<String>) -> Self {
+        Self {
+            template,
+            name: name.into(),
+            comment: None,
+        }
+    }
+
+    /// Sets the comment syntax used when the markers have to be added to the file.
+    ///
+    /// By default the syntax is chosen from the file's extension. Use this for file
+    /// types that are not recognised.
+    ///
+    /// # Parameters
+    ///
+    /// * `prefix` - The text that opens a comment, such as `"// "`
+    /// * `suffix` - The text that closes a comment, such as `" */"`, or `""` for line
+    ///   comments
+    ///
+    /// # Returns
+    ///
+    /// The `Region` instance with the comment syntax applied.
+    pub fn comment(mut self, prefix: impl Into<String>, suffix: impl Into<String>) -> Self {
+        self.comment = Some((prefix.into(), suffix.into()));
+        self
+    }
+
+    /// Returns the indices of every line holding a marker of the given kind for this region.
+    fn find_markers(&self, lines: &[&str], kind: &str) -> Vec<usize> {
+        let tag = format!("anvil:{kind}");
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                line.split_once(tag.as_str()).is_some_and(|(_, rest)| {
+                    rest.starts_with(char::is_whitespace)
+                        && rest.split_whitespace().next() == Some(self.name.as_str())
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the comment prefix and suffix to write new markers with.
+    fn comment_for(&self, path: &Path) -> (String, String) {
+        if let Some((prefix, suffix)) = &self.comment {
+            return (prefix.clone(), suffix.clone());
+        }
+        let (prefix, suffix) = comment_syntax(path);
+        (prefix.to_string(), suffix.to_string())
+    }
+}
+
+/// Returns the line comment syntax conventionally used by files like `path`.
+fn comment_syntax(path: &Path) -> (&'static str, &'static str) {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "go" | "java" | "kt" | "kts" | "swift"
+        | "scala" | "dart" | "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "proto" | "scss"
+        | "less" | "zig" => ("// ", ""),
+        "sql" | "lua" | "hs" | "elm" => ("-- ", ""),
+        "html" | "htm" | "xml" | "svg" | "md" | "markdown" | "vue" | "svelte" => ("<!-- ", " -->"),
+        "css" => ("/* ", " */"),
+        "ini" => ("; ", ""),
+        "erl" | "hrl" | "tex" => ("% ", ""),
+        _ => ("# ", ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::fs::Memory;
+    use tempfile::tempdir;
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn region(content: &str) -> Region<MockAnvil> {
+        let template = MockAnvil {
+            content: content.to_string(),
+        };
+        Region::new(template, "routes")
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_region_replaces_only_inner_text() {
+        let fs = Memory::new().with_file(
+            "router.rs",
+            "fn router() {\n    // anvil:begin routes\n    .route(\"/old\")\n    // anvil:end routes\n}\n",
+        );
+
+        region("    .route(\"/a\")\n    .route(\"/b\")")
+            .forge_in(&fs, "router.rs")
+            .unwrap();
+
+        let content = fs.read_to_string(Path::new("router.rs")).unwrap();
+        assert_eq!(
+            content,
+            "fn router() {\n    // anvil:begin routes\n    .route(\"/a\")\n    .route(\"/b\")\n    // anvil:end routes\n}\n"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_region_adds_markers_with_comment_syntax_for_extension() {
+        let fs = Memory::new()
+            .with_file("src/mod.rs", "pub mod manual;")
+            .with_file("templates/index.html", "<main></main>\n");
+
+        region("pub mod post;\n")
+            .forge_in(&fs, "src/mod.rs")
+            .unwrap();
+        region("<a href=\"/\">Home</a>\n")
+            .forge_in(&fs, "templates/index.html")
+            .unwrap();
+        region("ROUTES = []\n")
+            .forge_in(&fs, "config/routes.py")
+            .unwrap();
+
+        let snapshot = fs.snapshot();
+        assert_eq!(
+            snapshot[Path::new("src/mod.rs")],
+            "pub mod manual;\n// anvil:begin routes\npub mod post;\n// anvil:end routes\n"
+        );
+        assert_eq!(
+            snapshot[Path::new("templates/index.html")],
+            "<main></main>\n<!-- anvil:begin routes -->\n<a href=\"/\">Home</a>\n<!-- anvil:end routes -->\n"
+        );
+        assert_eq!(
+            snapshot[Path::new("config/routes.py")],
+            "# anvil:begin routes\nROUTES = []\n# anvil:end routes\n"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_region_is_stable_across_runs() {
+        let fs = Memory::new().with_file("mod.rs", "pub mod manual;\n");
+
+        region("pub mod post;\n").forge_in(&fs, "mod.rs").unwrap();
+        let first = fs.read_to_string(Path::new("mod.rs")).unwrap();
+        region("pub mod post;\n").forge_in(&fs, "mod.rs").unwrap();
+        let second = fs.read_to_string(Path::new("mod.rs")).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_region_rejects_unbalanced_and_duplicated_markers() {
+        let fs = Memory::new()
+            .with_file("unbalanced.rs", "// anvil:begin routes\n")
+            .with_file("reversed.rs", "// anvil:end routes\n// anvil:begin routes\n")
+            .with_file(
+                "duplicated.rs",
+                "// anvil:begin routes\n// anvil:end routes\n// anvil:begin routes\n// anvil:end routes\n",
+            )
+            .with_file("other.rs", "// anvil:begin routes_v2\n");
+
+        let result = region("").forge_in(&fs, "unbalanced.rs");
+        assert!(matches!(result, Err(RegionError::Unbalanced { .. })));
+
+        let result = region("").forge_in(&fs, "reversed.rs");
+        assert!(matches!(result, Err(RegionError::Unbalanced { .. })));
+
+        let result = region("").forge_in(&fs, "duplicated.rs");
+        match result {
+            Err(RegionError::Duplicated { name, path }) => {
+                assert_eq!(name, "routes");
+                assert_eq!(path, PathBuf::from("duplicated.rs"));
+            }
+            other => unreachable!("Expected Duplicated error but got: {:?}", other),
+        }
+
+        // Markers for other regions are ignored
+        assert!(region("").forge_in(&fs, "other.rs").is_ok());
+    }
+
+    #[test]
+    fn test_region_on_disk_with_custom_comment_syntax() {
+        // Create a temporary directory with an unrecognised file type
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("routes.custom");
+        std::fs::write(&file_path, "manual\n").unwrap();
+
+        region("generated\n")
+            .comment("!! ", "")
+            .forge(&file_path)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            "manual\n!! anvil:begin routes\ngenerated\n!! anvil:end routes\n"
+        );
+    }
+}
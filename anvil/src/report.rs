@@ -0,0 +1,628 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt, io,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fs::{Disk, FileSystem, Kept, Metadata, Savepoint},
+    Forge,
+};
+
+/// What an operation did to a single path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "report",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "lowercase")
+)]
+pub enum Action {
+    /// A file was created.
+    Create,
+    /// A directory was created, along with any missing parents.
+    CreateDir,
+    /// An existing file was given new contents.
+    Update,
+    /// Content was appended to an existing file.
+    Append,
+    /// The file already had the contents the operation would have written.
+    Identical,
+    /// The directory already existed.
+    Exist,
+    /// The file differed from what the operation would have written, but was left alone.
+    Skip,
+    /// A file was removed.
+    Remove,
+    /// An empty directory was removed.
+    RemoveDir,
+    /// A file or directory was moved here.
+    Rename {
+        /// The path it was moved from
+        from: PathBuf,
+    },
+    /// A symbolic link was created.
+    Link {
+        /// The path the link points to
+        target: PathBuf,
+    },
+    /// The file's permission bits were set.
+    Chmod {
+        /// The new permission bits, such as `0o755`
+        mode: u32,
+    },
+}
+
+impl Action {
+    /// Returns the label used when printing this action, in the style of Rails generators,
+    /// such as `create` or `identical`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Create | Action::CreateDir => "create",
+            Action::Update => "update",
+            Action::Append => "append",
+            Action::Identical => "identical",
+            Action::Exist => "exist",
+            Action::Skip => "skip",
+            Action::Remove | Action::RemoveDir => "remove",
+            Action::Rename { .. } => "rename",
+            Action::Link { .. } => "link",
+            Action::Chmod { .. } => "chmod",
+        }
+    }
+
+    /// Returns the ANSI colour code the label is printed in.
+    fn color(&self) -> &'static str {
+        match self {
+            Action::Identical | Action::Exist => "1;34",
+            Action::Skip => "1;33",
+            Action::Remove | Action::RemoveDir => "1;31",
+            _ => "1;32",
+        }
+    }
+}
+
+/// A single path touched by an operation, as recorded in a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "report", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The path that was touched
+    path: PathBuf,
+    /// What was done to it
+    action: Action,
+    /// How many bytes were written to it
+    bytes: u64,
+    /// The hash of the file's contents before the operation
+    before: Option<String>,
+    /// The hash of the file's contents after the operation
+    after: Option<String>,
+}
+
+impl Entry {
+    /// Returns the path that was touched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns what was done to the path.
+    pub fn action(&self) -> &Action {
+        &self.action
+    }
+
+    /// Returns how many bytes were written to the path.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the hash of the file's contents before the operation, or `None` if there
+    /// was no file at the path.
+    ///
+    /// Hashes are only recorded with the `report` feature enabled, and are `None` without
+    /// it. They are in the same `sha256:` format as the hashes in a manifest.
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Returns the hash of the file's contents after the operation, or `None` if there is
+    /// no file at the path.
+    ///
+    /// Hashes are only recorded with the `report` feature enabled, and are `None` without
+    /// it.
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+
+    /// Writes the entry as a line of console output, with ANSI colours if `colored` is set.
+    fn write(&self, writer: &mut dyn Write, colored: bool) -> io::Result<()> {
+        let label = format!("{:>12}", self.action.label());
+        if colored {
+            write!(writer, "\x1b[{}m{label}\x1b[0m", self.action.color())?;
+        } else {
+            write!(writer, "{label}")?;
+        }
+        match &self.action {
+            Action::Rename { from } => {
+                writeln!(writer, "  {} -> {}", from.display(), self.path.display())
+            }
+            _ => writeln!(writer, "  {}", self.path.display()),
+        }
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut line = Vec::new();
+        self.write(&mut line, false).map_err(|_| fmt::Error)?;
+        f.write_str(String::from_utf8_lossy(&line).trim_end())
+    }
+}
+
+/// A record of what an operation did to every path it touched.
+///
+/// Reports are made by running an operation against a [`Reporter`], which notes each
+/// change as it passes through to the underlying filesystem. Because it works at the level
+/// of the filesystem, every built-in operation is covered, as are combinators: the steps of
+/// a [`Sequence`](crate::sequence::Sequence) are reported in order, and the changes of the
+/// failed side of an [`Either`](crate::either::Either) are dropped along with the changes
+/// themselves.
+///
+/// A report prints one line per entry in the style of Rails generators. With the `report`
+/// feature enabled, each entry also records the hashes of the file's contents before and
+/// after the operation, and the report can be serialized as JSON.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{ForgeExt, generate::Generate, mkdir::Mkdir, report::Report};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"pub struct User;\n")
+/// #     }
+/// # }
+///
+/// let model = Mkdir::new()
+///     .at("src/models")
+///     .then(Generate::new(SimpleTemplate).at("src/models/user.rs"));
+/// let report = Report::record(&model, ".").unwrap();
+///
+/// //       exist  src/models
+/// //      create  src/models/user.rs
+/// report.write_colored(&mut std::io::stdout()).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "report", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// The recorded entries, in the order they happened
+    entries: Vec<Entry>,
+}
+
+impl Report {
+    /// Runs an operation on the real disk and reports what it did.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `into` - The path to run the operation on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Report, F::Error>` - The report, or the error the operation failed with.
+    pub fn record<F: Forge>(forge: &F, into: impl AsRef<Path>) -> Result<Self, F::Error> {
+        Self::record_in(forge, &Disk, into)
+    }
+
+    /// Runs an operation on the given filesystem and reports what it did.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `fs` - The filesystem to run the operation against
+    /// * `into` - The path to run the operation on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Report, F::Error>` - The report, or the error the operation failed with.
+    pub fn record_in<F: Forge>(
+        forge: &F,
+        fs: &dyn FileSystem,
+        into: impl AsRef<Path>,
+    ) -> Result<Self, F::Error> {
+        let reporter = Reporter::new(fs);
+        forge.forge_in(&reporter, into)?;
+        Ok(reporter.finish())
+    }
+
+    /// Returns the recorded entries, in the order they happened.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns `true` if the operation didn't touch anything.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the total number of bytes written.
+    pub fn bytes_written(&self) -> u64 {
+        self.entries.iter().map(Entry::bytes).sum()
+    }
+
+    /// Writes the report as console output, with each label coloured with ANSI escape codes
+    /// for terminals.
+    ///
+    /// # Parameters
+    ///
+    /// * `writer` - Where to write the report, such as standard output
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<()>` - Ok if the report was written, or the error from the writer.
+    pub fn write_colored(&self, writer: &mut impl Write) -> io::Result<()> {
+        for entry in &self.entries {
+            entry.write(writer, true)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the report as pretty-printed JSON, for machine-readable summaries.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, serde_json::Error>` - The JSON, or an error if serialization fails.
+    #[cfg(feature = "report")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entries
+            .iter()
+            .try_for_each(|entry| writeln!(f, "{entry}"))
+    }
+}
+
+/// A [`FileSystem`] wrapper that reports every change made through it.
+///
+/// Most callers should use [`Report::record`] instead. The reporter is useful when the
+/// filesystem is needed for more than a single operation, such as when running several
+/// operations against it by hand.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "memory")] {
+/// use anvil::{Forge, fs::Memory, mkdir::Mkdir, report::Reporter};
+///
+/// let fs = Memory::new().with_dir("src");
+/// let reporter = Reporter::new(&fs);
+/// Mkdir::new().forge_in(&reporter, "src").unwrap();
+/// Mkdir::new().forge_in(&reporter, "tests").unwrap();
+///
+/// assert_eq!(reporter.finish().to_string(), "       exist  src\n      create  tests\n");
+/// # }
+/// ```
+pub struct Reporter<'a> {
+    /// The filesystem the changes are made in
+    inner: &'a dyn FileSystem,
+    /// The entries recorded so far
+    entries: RefCell<Vec<Entry>>,
+    /// The inner savepoint and number of entries at each savepoint taken
+    savepoints: RefCell<Vec<(Savepoint, usize)>>,
+}
+
+impl<'a> Reporter<'a> {
+    /// Creates a reporter for changes made to the given filesystem.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the changes are made in
+    ///
+    /// # Returns
+    ///
+    /// A new `Reporter` with no entries.
+    pub fn new(fs: &'a dyn FileSystem) -> Self {
+        Self {
+            inner: fs,
+            entries: RefCell::new(Vec::new()),
+            savepoints: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Stops reporting and returns the report.
+    pub fn finish(self) -> Report {
+        Report {
+            entries: self.entries.into_inner(),
+        }
+    }
+
+    /// Returns the hash of the file at `path`, or `None` if there is no readable file.
+    fn hash(&self, path: &Path) -> Option<String> {
+        if !self.inner.metadata(path).is_ok_and(|m| m.is_file()) {
+            return None;
+        }
+        self.inner
+            .read(path)
+            .ok()
+            .and_then(|contents| digest(&contents))
+    }
+
+    fn push(
+        &self,
+        path: &Path,
+        action: Action,
+        bytes: usize,
+        before: Option<String>,
+        after: Option<String>,
+    ) {
+        self.entries.borrow_mut().push(Entry {
+            path: path.to_path_buf(),
+            action,
+            bytes: bytes as u64,
+            before,
+            after,
+        });
+    }
+}
+
+/// Returns the SHA-256 hash of `contents` in `sha256:<hex>` form.
+#[cfg(feature = "report")]
+pub(crate) fn sha256(contents: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("sha256:{:x}", Sha256::digest(contents))
+}
+
+/// Returns the hash reported for `contents`.
+#[cfg(feature = "report")]
+fn digest(contents: &[u8]) -> Option<String> {
+    Some(sha256(contents))
+}
+
+/// Returns the hash reported for `contents`, which is nothing without the `report` feature.
+#[cfg(not(feature = "report"))]
+fn digest(_contents: &[u8]) -> Option<String> {
+    None
+}
+
+impl FileSystem for Reporter<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let original = if self.inner.metadata(path).is_ok_and(|m| m.is_file()) {
+            Some(self.inner.read(path)?)
+        } else {
+            None
+        };
+        self.inner.write(path, contents)?;
+        let action = match &original {
+            None => Action::Create,
+            Some(original) if original == contents => Action::Identical,
+            Some(_) => Action::Update,
+        };
+        let before = original.and_then(|original| digest(&original));
+        self.push(path, action, contents.len(), before, digest(contents));
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.create_new(path, contents)?;
+        self.push(path, Action::Create, contents.len(), None, digest(contents));
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let before = self.hash(path);
+        self.inner.append(path, contents)?;
+        self.push(
+            path,
+            Action::Append,
+            contents.len(),
+            before,
+            self.hash(path),
+        );
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let before = self.hash(to);
+        self.inner.rename(from, to)?;
+        let action = Action::Rename {
+            from: from.to_path_buf(),
+        };
+        self.push(to, action, 0, before, self.hash(to));
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let existed = self.inner.exists(path);
+        self.inner.create_dir_all(path)?;
+        if !existed {
+            self.push(path, Action::CreateDir, 0, None, None);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let before = self.hash(path);
+        self.inner.remove_file(path)?;
+        self.push(path, Action::Remove, 0, before, None);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_dir(path)?;
+        self.push(path, Action::RemoveDir, 0, None, None);
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.inner.set_mode(path, mode)?;
+        let hash = self.hash(path);
+        self.push(path, Action::Chmod { mode }, 0, hash.clone(), hash);
+        Ok(())
+    }
+
+    fn keep(&self, path: &Path, kept: Kept) {
+        self.inner.keep(path, kept);
+        let is_dir = self.inner.metadata(path).is_ok_and(|m| m.is_dir());
+        let action = match kept {
+            Kept::Identical if is_dir => Action::Exist,
+            Kept::Identical => Action::Identical,
+            Kept::Skipped => Action::Skip,
+        };
+        let hash = self.hash(path);
+        self.push(path, action, 0, hash.clone(), hash);
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.inner.symlink(target, link)?;
+        let action = Action::Link {
+            target: target.to_path_buf(),
+        };
+        self.push(link, action, 0, None, None);
+        Ok(())
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        let inner = self.inner.savepoint();
+        let mut savepoints = self.savepoints.borrow_mut();
+        savepoints.push((inner, self.entries.borrow().len()));
+        Savepoint::new(savepoints.len() - 1)
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let mut savepoints = self.savepoints.borrow_mut();
+        if savepoint.position() >= savepoints.len() {
+            return;
+        }
+        let (inner, len) = savepoints
+            .drain(savepoint.position()..)
+            .next()
+            .expect("savepoint position is in bounds");
+        self.entries.borrow_mut().truncate(len);
+        self.inner.rollback_to(inner, cause);
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append,
+        either::either,
+        fs::Memory,
+        generate::{Generate, Overwrite},
+        mkdir::Mkdir,
+        touch::Touch,
+        transform::Transform,
+        Anvil, ForgeExt,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil(&'static str);
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.0.as_bytes())
+        }
+    }
+
+    #[test]
+    fn test_report_describes_each_change() {
+        let fs = Memory::new()
+            .with_file("src/lib.rs", "pub mod user;\n")
+            .with_file("README.md", "# App\n");
+
+        let scaffold = Generate::new(MockAnvil("pub struct User;\n"))
+            .at("src/models/user.rs")
+            .then(
+                Append::new(MockAnvil("pub mod user;\n"))
+                    .once()
+                    .at("src/lib.rs"),
+            )
+            .then(Touch::new().at("README.md"))
+            .then(
+                Transform::new(|content| Ok(content.replace("User", "Account")))
+                    .at("src/models/user.rs"),
+            );
+        let report = Report::record_in(&scaffold, &fs, "").unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            concat!(
+                "      create  src/models\n",
+                "      create  src/models/user.rs\n",
+                "   identical  src/lib.rs\n",
+                "   identical  README.md\n",
+                "      update  src/models/user.rs\n",
+            )
+        );
+        assert_eq!(report.bytes_written(), 17 + 20);
+
+        // Hashes identify the contents before and after each change
+        let update = &report.entries()[4];
+        assert_eq!(update.before(), digest(b"pub struct User;\n").as_deref());
+        assert_eq!(update.after(), digest(b"pub struct Account;\n").as_deref());
+    }
+
+    #[test]
+    fn test_report_drops_changes_of_failed_fallback() {
+        let fs = Memory::new().with_file("a.txt", "old");
+
+        // The left side creates a directory before failing; the right side skips the file
+        let left = Mkdir::new()
+            .at("partial")
+            .then(Generate::new(MockAnvil("new")).at("a.txt"));
+        let right = Generate::new(MockAnvil("new"))
+            .policy(Overwrite::Skip)
+            .at("a.txt");
+        let report = Report::record_in(&either(left, right), &fs, "").unwrap();
+
+        assert_eq!(report.entries().len(), 1);
+        assert_eq!(report.entries()[0].action(), &Action::Skip);
+
+        #[cfg(feature = "report")]
+        {
+            let json = report.to_json().unwrap();
+            assert!(json.contains("\"kind\": \"skip\""));
+        }
+    }
+}
@@ -0,0 +1,633 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    fs::{Disk, FileSystem, Kept, Metadata, Savepoint},
+    plan::Plan,
+    Forge,
+};
+
+/// Errors that can occur while reverting recorded changes.
+#[derive(Error, Debug)]
+pub enum RevertError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O: {0}")]
+    StdIo(#[from] io::Error),
+
+    /// A file was changed after it was generated, so reverting would lose those changes.
+    #[error("{} was modified after it was generated", path.display())]
+    Modified {
+        /// The file that was modified
+        path: PathBuf,
+    },
+
+    /// Error that occurred while loading or saving the record.
+    #[cfg(feature = "manifest")]
+    #[error("failed to parse revert record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A single change made by an operation, with what is needed to undo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "manifest",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "lowercase")
+)]
+pub enum Change {
+    /// A directory was created. Reverting removes it if it is empty.
+    CreatedDir {
+        /// The directory that was created
+        path: PathBuf,
+    },
+    /// A file was created. Reverting removes it.
+    Created {
+        /// The file that was created
+        path: PathBuf,
+        /// The contents it was created with
+        contents: Vec<u8>,
+    },
+    /// Content was appended to a file. Reverting removes that content again.
+    Appended {
+        /// The file that was appended to
+        path: PathBuf,
+        /// The content that was appended
+        contents: Vec<u8>,
+    },
+    /// A file's contents were replaced. Reverting restores the pre-image.
+    Replaced {
+        /// The file that was replaced
+        path: PathBuf,
+        /// The contents before the change
+        original: Vec<u8>,
+        /// The contents after the change
+        contents: Vec<u8>,
+    },
+    /// A symbolic link was created. Reverting removes it.
+    Linked {
+        /// The link that was created
+        path: PathBuf,
+        /// The path the link points to
+        target: PathBuf,
+    },
+    /// A file or directory was moved. Reverting moves it back.
+    Renamed {
+        /// The original path
+        from: PathBuf,
+        /// The new path
+        to: PathBuf,
+    },
+    /// A file was removed. Reverting recreates it.
+    Removed {
+        /// The file that was removed
+        path: PathBuf,
+        /// The contents it had
+        original: Vec<u8>,
+    },
+    /// An empty directory was removed. Reverting recreates it.
+    RemovedDir {
+        /// The directory that was removed
+        path: PathBuf,
+    },
+}
+
+/// A record of the changes an operation made, which can be reverted later.
+///
+/// Anvil can't reconstruct what an operation did from the operation alone: a transform
+/// discards the text it replaced, and whether a generated file was created or overwritten
+/// depends on the tree it ran in. `Revertible` therefore records every change as the
+/// operation runs, including the pre-image of anything it replaced, and
+/// [`revert`](Revertible::revert) applies the inverse of each change in reverse order:
+///
+/// * Files created by [`Generate`](crate::generate::Generate) are deleted
+/// * Content added by [`Append`](crate::append::Append) is removed again
+/// * Files moved by [`Move`](crate::mover::Move) are moved back
+/// * Files changed by [`Transform`](crate::transform::Transform) are restored from their
+///   pre-image
+///
+/// Reverting refuses to touch a file that was modified after the operation ran, and checks
+/// every change before making any of them, so a refused revert leaves the tree untouched.
+///
+/// When an operation such as [`Either`](crate::either::Either) rolls back a failed branch,
+/// the changes that branch made are dropped from the record, and the filesystem the record
+/// was made in is asked to roll them back. Filesystems without history, such as
+/// [`Disk`], keep those changes, so run operations that may fall back inside a
+/// [`Transaction`](crate::transaction::Transaction) if nothing may be left behind.
+///
+/// With the `manifest` feature enabled, a record can be saved as JSON and loaded again, so a
+/// later run can destroy what an earlier one generated.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate::Generate, revert::Revertible};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct SimpleTemplate;
+/// # impl Anvil for SimpleTemplate {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// // Scaffold a resource, keeping a record of what was changed
+/// let record = Revertible::record(&Generate::new(SimpleTemplate), "src/models/usr.rs").unwrap();
+///
+/// // Wrong name: undo everything the scaffold did
+/// record.revert().unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "manifest", derive(serde::Serialize, serde::Deserialize))]
+pub struct Revertible {
+    /// The recorded changes, in the order they were made
+    changes: Vec<Change>,
+}
+
+impl Revertible {
+    /// Runs an operation on the real disk and records the changes it makes.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `into` - The path to run the operation on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Revertible, F::Error>` - The recorded changes, or the error the operation
+    ///   failed with.
+    pub fn record<F: Forge>(forge: &F, into: impl AsRef<Path>) -> Result<Self, F::Error> {
+        Self::record_in(forge, &Disk, into)
+    }
+
+    /// Runs an operation on the given filesystem and records the changes it makes.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `fs` - The filesystem to run the operation against
+    /// * `into` - The path to run the operation on
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Revertible, F::Error>` - The recorded changes, or the error the operation
+    ///   failed with.
+    pub fn record_in<F: Forge>(
+        forge: &F,
+        fs: &dyn FileSystem,
+        into: impl AsRef<Path>,
+    ) -> Result<Self, F::Error> {
+        let recorder = Recorder {
+            inner: fs,
+            changes: RefCell::new(Vec::new()),
+            savepoints: RefCell::new(Vec::new()),
+        };
+        forge.forge_in(&recorder, into)?;
+        Ok(Self {
+            changes: recorder.changes.into_inner(),
+        })
+    }
+
+    /// Returns the recorded changes, in the order they were made.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns `true` if the operation didn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Reverts the recorded changes on the real disk.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RevertError>` - Ok if every change was reverted, or an error if:
+    ///   - A file was modified after the operation ran
+    ///   - File system operations fail
+    pub fn revert(&self) -> Result<(), RevertError> {
+        self.revert_in(&Disk)
+    }
+
+    /// Reverts the recorded changes on the given filesystem.
+    ///
+    /// The changes are first reverted against a [`Plan`], so nothing is touched unless every
+    /// change can be undone.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to revert the changes in
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RevertError>` - Ok if every change was reverted, or an error if:
+    ///   - A file was modified after the operation ran
+    ///   - File system operations fail
+    pub fn revert_in(&self, fs: &dyn FileSystem) -> Result<(), RevertError> {
+        self.undo(&Plan::over(fs))?;
+        self.undo(fs)
+    }
+
+    /// Applies the inverse of every change, newest first.
+    fn undo(&self, fs: &dyn FileSystem) -> Result<(), RevertError> {
+        let unchanged = |path: &Path, expected: &[u8]| -> Result<(), RevertError> {
+            if fs.read(path)? == expected {
+                Ok(())
+            } else {
+                Err(RevertError::Modified {
+                    path: path.to_path_buf(),
+                })
+            }
+        };
+
+        for change in self.changes.iter().rev() {
+            match change {
+                Change::CreatedDir { path } => match fs.remove_dir(path) {
+                    // Directories that gained other files are left in place
+                    Ok(()) => {}
+                    Err(_) if fs.metadata(path).is_ok_and(|m| m.is_dir()) => {}
+                    Err(e) => return Err(e.into()),
+                },
+                Change::Created { path, contents } => {
+                    unchanged(path, contents)?;
+                    fs.remove_file(path)?;
+                }
+                Change::Linked { path, target } => {
+                    if fs.read_link(path)? != *target {
+                        return Err(RevertError::Modified { path: path.clone() });
+                    }
+                    fs.remove_file(path)?;
+                }
+                Change::Appended { path, contents } => {
+                    if contents.is_empty() {
+                        continue;
+                    }
+                    // Remove the last occurrence, in case the same content was added by hand
+                    let current = fs.read(path)?;
+                    let position = current
+                        .windows(contents.len())
+                        .rposition(|window| window == contents.as_slice())
+                        .ok_or_else(|| RevertError::Modified { path: path.clone() })?;
+                    let mut restored = current[..position].to_vec();
+                    restored.extend_from_slice(&current[position + contents.len()..]);
+                    fs.write(path, &restored)?;
+                }
+                Change::Replaced {
+                    path,
+                    original,
+                    contents,
+                } => {
+                    unchanged(path, contents)?;
+                    fs.write(path, original)?;
+                }
+                Change::Renamed { from, to } => {
+                    if let Some(parent) = from.parent() {
+                        fs.create_dir_all(parent)?;
+                    }
+                    fs.rename(to, from)?;
+                }
+                Change::Removed { path, original } => {
+                    if let Some(parent) = path.parent() {
+                        fs.create_dir_all(parent)?;
+                    }
+                    fs.create_new(path, original)?;
+                }
+                Change::RemovedDir { path } => fs.create_dir_all(path)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads a record previously saved with [`save`](Revertible::save).
+    #[cfg(feature = "manifest")]
+    pub fn load(fs: &dyn FileSystem, path: impl AsRef<Path>) -> Result<Self, RevertError> {
+        Ok(serde_json::from_slice(&fs.read(path.as_ref())?)?)
+    }
+
+    /// Saves the record as JSON, creating parent directories as needed.
+    #[cfg(feature = "manifest")]
+    pub fn save(&self, fs: &dyn FileSystem, path: impl AsRef<Path>) -> Result<(), RevertError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        fs.write(path, &serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+/// A [`FileSystem`] wrapper that records every change made through it.
+struct Recorder<'a> {
+    /// The filesystem the changes are made in
+    inner: &'a dyn FileSystem,
+    /// The changes made so far
+    changes: RefCell<Vec<Change>>,
+    /// The inner savepoint and number of changes at each savepoint taken
+    savepoints: RefCell<Vec<(Savepoint, usize)>>,
+}
+
+impl Recorder<'_> {
+    /// Returns the contents of `path` if it is an existing file.
+    fn original(&self, path: &Path) -> io::Result<Option<Vec<u8>>> {
+        if self.inner.metadata(path).is_ok_and(|m| m.is_file()) {
+            self.inner.read(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn push(&self, change: Change) {
+        self.changes.borrow_mut().push(change);
+    }
+}
+
+impl FileSystem for Recorder<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let original = self.original(path)?;
+        self.inner.write(path, contents)?;
+        let path = path.to_path_buf();
+        let contents = contents.to_vec();
+        self.push(match original {
+            Some(original) => Change::Replaced {
+                path,
+                original,
+                contents,
+            },
+            None => Change::Created { path, contents },
+        });
+        Ok(())
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.create_new(path, contents)?;
+        self.push(Change::Created {
+            path: path.to_path_buf(),
+            contents: contents.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let existed = self.inner.exists(path);
+        self.inner.append(path, contents)?;
+        let path = path.to_path_buf();
+        let contents = contents.to_vec();
+        self.push(if existed {
+            Change::Appended { path, contents }
+        } else {
+            Change::Created { path, contents }
+        });
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        // A file replaced by the move is restored after the move is undone
+        let replaced = self.original(to)?;
+        self.inner.rename(from, to)?;
+        if let Some(original) = replaced {
+            self.push(Change::Removed {
+                path: to.to_path_buf(),
+                original,
+            });
+        }
+        self.push(Change::Renamed {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut missing: Vec<PathBuf> = path
+            .ancestors()
+            .filter(|ancestor| ancestor.parent().is_some())
+            .take_while(|ancestor| !self.inner.exists(ancestor))
+            .map(Path::to_path_buf)
+            .collect();
+        self.inner.create_dir_all(path)?;
+        missing.reverse();
+        self.changes
+            .borrow_mut()
+            .extend(missing.into_iter().map(|path| Change::CreatedDir { path }));
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let original = self.inner.read(path)?;
+        self.inner.remove_file(path)?;
+        self.push(Change::Removed {
+            path: path.to_path_buf(),
+            original,
+        });
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_dir(path)?;
+        self.push(Change::RemovedDir {
+            path: path.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.inner.set_mode(path, mode)
+    }
+
+    fn keep(&self, path: &Path, kept: Kept) {
+        self.inner.keep(path, kept)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.inner.symlink(target, link)?;
+        self.push(Change::Linked {
+            path: link.to_path_buf(),
+            target: target.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        let inner = self.inner.savepoint();
+        let mut savepoints = self.savepoints.borrow_mut();
+        savepoints.push((inner, self.changes.borrow().len()));
+        Savepoint::new(savepoints.len() - 1)
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let mut savepoints = self.savepoints.borrow_mut();
+        if savepoint.position() >= savepoints.len() {
+            return;
+        }
+        let (inner, len) = savepoints
+            .drain(savepoint.position()..)
+            .next()
+            .expect("savepoint position is in bounds");
+        self.changes.borrow_mut().truncate(len);
+        self.inner.rollback_to(inner, cause);
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append, either::either, fs::Memory, generate::Generate, mkdir::Mkdir, mover::Move,
+        transform::Transform, Anvil, ForgeExt,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_revert_undoes_generate_append_move_and_transform() {
+        let fs = Memory::new()
+            .with_file("src/mod.rs", "pub mod a;\n")
+            .with_file("src/a.rs", "struct A;\n")
+            .with_file("README.md", "# Old\n");
+        let before = fs.files();
+
+        let generated =
+            Revertible::record_in(&Generate::new(mock("struct B;\n")), &fs, "src/b/mod.rs")
+                .unwrap();
+        let appended =
+            Revertible::record_in(&Append::new(mock("pub mod b;\n")), &fs, "src/mod.rs").unwrap();
+        let moved = Revertible::record_in(&Move::new("src/a.rs"), &fs, "src/alpha.rs").unwrap();
+        let transformed = Revertible::record_in(
+            &Transform::new(|content| Ok(content.replace("Old", "New"))),
+            &fs,
+            "README.md",
+        )
+        .unwrap();
+
+        for record in [transformed, moved, appended, generated] {
+            record.revert_in(&fs).unwrap();
+        }
+
+        // The tree is back to where it started, including the created directory
+        assert_eq!(fs.files(), before);
+        assert!(!fs.exists(Path::new("src/b")));
+    }
+
+    #[test]
+    fn test_revert_removes_appended_content_despite_later_edits() {
+        let fs = Memory::new().with_file("mod.rs", "pub mod a;\n");
+
+        let record =
+            Revertible::record_in(&Append::new(mock("pub mod b;\n")), &fs, "mod.rs").unwrap();
+        fs.append(Path::new("mod.rs"), b"pub mod c;\n").unwrap();
+
+        record.revert_in(&fs).unwrap();
+
+        assert_eq!(
+            fs.read_to_string(Path::new("mod.rs")).unwrap(),
+            "pub mod a;\npub mod c;\n"
+        );
+    }
+
+    #[test]
+    fn test_revert_refuses_to_discard_modifications() {
+        let fs = Memory::new().with_file("mod.rs", "pub mod a;\n");
+
+        let generated =
+            Revertible::record_in(&Generate::new(mock("generated")), &fs, "new.rs").unwrap();
+        let appended =
+            Revertible::record_in(&Append::new(mock("pub mod b;\n")), &fs, "mod.rs").unwrap();
+        let record = Revertible {
+            changes: [generated.changes(), appended.changes()].concat(),
+        };
+        fs.write(Path::new("new.rs"), b"edited by hand").unwrap();
+
+        let result = record.revert_in(&fs);
+
+        assert!(
+            matches!(result, Err(RevertError::Modified { path }) if path == Path::new("new.rs"))
+        );
+        // The append was not reverted either, since the revert was checked first
+        assert_eq!(
+            fs.read_to_string(Path::new("mod.rs")).unwrap(),
+            "pub mod a;\npub mod b;\n"
+        );
+    }
+
+    #[test]
+    fn test_record_drops_changes_of_rolled_back_branch() {
+        let base = Memory::new().with_file("mod.rs", "existing\n");
+        let plan = Plan::over(&base);
+
+        // The left side creates a directory before failing on the existing file
+        let left = Mkdir::new()
+            .at("partial")
+            .then(Generate::new(mock("fails")).at("mod.rs"));
+        let right = Append::new(mock("appended\n")).at("mod.rs");
+        let record = Revertible::record_in(&either(left, right), &plan, "").unwrap();
+
+        // The plan rolled the directory back, and the record forgot about it
+        assert!(!plan.exists(Path::new("partial")));
+        assert_eq!(
+            record.changes(),
+            [Change::Appended {
+                path: PathBuf::from("mod.rs"),
+                contents: b"appended\n".to_vec()
+            }]
+        );
+
+        record.revert_in(&plan).unwrap();
+        assert_eq!(plan.read(Path::new("mod.rs")).unwrap(), b"existing\n");
+    }
+}
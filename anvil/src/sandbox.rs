@@ -0,0 +1,483 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    fs::{normalize, FileSystem, Kept, Metadata, Savepoint},
+    Forge,
+};
+
+/// Errors that report an attempt to write outside of a sandbox root.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EscapeError {
+    /// The target was an absolute path rather than one relative to the root.
+    #[error("{} is an absolute path, but paths must be relative to {}", path.display(), root.display())]
+    Absolute {
+        /// The path that was rejected
+        path: PathBuf,
+        /// The root of the sandbox
+        root: PathBuf,
+    },
+
+    /// The path leads outside of the root, for example through `..` components.
+    #[error("{} is outside of {}", path.display(), root.display())]
+    Outside {
+        /// The path that was rejected
+        path: PathBuf,
+        /// The root of the sandbox
+        root: PathBuf,
+    },
+
+    /// The path is inside the root, but a symbolic link makes it resolve outside of it.
+    #[error("{} resolves to {} through a symbolic link, which is outside of {}", path.display(), target.display(), root.display())]
+    Symlink {
+        /// The path that was rejected
+        path: PathBuf,
+        /// Where the path actually resolves to
+        target: PathBuf,
+        /// The root of the sandbox
+        root: PathBuf,
+    },
+}
+
+/// A [`FileSystem`] that refuses to modify anything outside of a root directory.
+///
+/// `Sandbox` wraps another filesystem and checks the path of every operation that creates,
+/// changes or removes something. A path is allowed if it lies inside the root both lexically
+/// (after resolving `.` and `..` components) and physically (after resolving symbolic
+/// links). Anything else fails with an error of kind `PermissionDenied` that wraps an
+/// [`EscapeError`], and the first such error is kept so that it can be reported with
+/// [`escape`](Sandbox::escape) even if an operation handles the I/O error itself.
+///
+/// Reading is allowed anywhere, so operations can still read templates from outside the
+/// root.
+///
+/// [`Rooted`] runs a single operation in a sandbox, which is usually what you want.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{fs::{Disk, FileSystem}, sandbox::Sandbox};
+/// use std::path::Path;
+///
+/// let sandbox = Sandbox::new(&Disk, "./my-app");
+///
+/// assert!(sandbox.write(Path::new("./my-app/README.md"), b"# My App").is_ok());
+/// assert!(sandbox.write(Path::new("./my-app/../escaped.txt"), b"").is_err());
+/// assert!(sandbox.escape().is_some());
+/// ```
+pub struct Sandbox<'a> {
+    /// The filesystem that allowed operations are performed in
+    inner: &'a dyn FileSystem,
+    /// The directory that every modified path must be inside
+    root: PathBuf,
+    /// The first attempt to escape the root
+    escape: RefCell<Option<EscapeError>>,
+}
+
+impl<'a> Sandbox<'a> {
+    /// Creates a sandbox that only allows modifications inside `root`.
+    ///
+    /// # Parameters
+    ///
+    /// * `inner` - The filesystem to perform allowed operations in
+    /// * `root` - The directory that every modified path must be inside
+    ///
+    /// # Returns
+    ///
+    /// A new `Sandbox` instance.
+    pub fn new(inner: &'a dyn FileSystem, root: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            root: root.as_ref().to_path_buf(),
+            escape: RefCell::new(None),
+        }
+    }
+
+    /// Returns the directory that every modified path must be inside.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the first attempt to modify something outside of the root, if there was one.
+    pub fn escape(&self) -> Option<EscapeError> {
+        self.escape.borrow().clone()
+    }
+
+    /// Fails if `path` is outside of the root, recording the attempt.
+    fn check(&self, path: &Path) -> io::Result<()> {
+        let Some(escape) = self.find_escape(path) else {
+            return Ok(());
+        };
+        let error = io::Error::new(io::ErrorKind::PermissionDenied, escape.clone());
+        self.escape.borrow_mut().get_or_insert(escape);
+        Err(error)
+    }
+
+    fn find_escape(&self, path: &Path) -> Option<EscapeError> {
+        if !is_within(&self.root, path) {
+            return Some(EscapeError::Outside {
+                path: path.to_path_buf(),
+                root: self.root.clone(),
+            });
+        }
+
+        let target = self.resolve_links(path);
+        let root = self.resolve_links(&self.root);
+        if !target.starts_with(&root) {
+            return Some(EscapeError::Symlink {
+                path: path.to_path_buf(),
+                target,
+                root: self.root.clone(),
+            });
+        }
+        None
+    }
+
+    /// Resolves symbolic links in `path` as far as it exists, appending the rest as it is.
+    fn resolve_links(&self, path: &Path) -> PathBuf {
+        let path = normalize(path);
+        for ancestor in path.ancestors() {
+            let existing = if ancestor.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                ancestor
+            };
+            if let Ok(canonical) = self.inner.canonicalize(existing) {
+                let rest = path.strip_prefix(ancestor).expect("ancestors are prefixes");
+                return canonical.join(rest);
+            }
+        }
+        path
+    }
+}
+
+/// Returns `true` if `path` is lexically inside `root`.
+fn is_within(root: &Path, path: &Path) -> bool {
+    let root = normalize(root);
+    let path = normalize(path);
+    if path.has_root() != root.has_root() {
+        return false;
+    }
+    // Once normalised, `..` can only remain at the start of a path, where it leaves `root`
+    match path.strip_prefix(&root) {
+        Ok(rest) => !rest.components().any(|c| c == Component::ParentDir),
+        Err(_) => false,
+    }
+}
+
+impl FileSystem for Sandbox<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.write(path, contents)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.create_new(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.append(path, contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.check(from)?;
+        self.check(to)?;
+        self.inner.rename(from, to)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.remove_dir(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.set_mode(path, mode)
+    }
+
+    fn keep(&self, path: &Path, kept: Kept) {
+        self.inner.keep(path, kept)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.check(link)?;
+        self.inner.symlink(target, link)
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        self.inner.savepoint()
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        self.inner.rollback_to(savepoint, cause)
+    }
+}
+
+/// Errors that can occur when running a [`Rooted`] operation.
+#[derive(Error, Debug)]
+pub enum RootedError<E: Error + 'static> {
+    /// The wrapped operation failed.
+    #[error(transparent)]
+    Forge(E),
+
+    /// The operation tried to modify something outside of the root.
+    #[error("refused to write outside of the project root: {0}")]
+    Escape(#[from] EscapeError),
+}
+
+/// A struct that confines an operation to a root directory.
+///
+/// `Rooted` resolves the path it is forged into relative to its root, and runs the wrapped
+/// operation in a [`Sandbox`] so that nothing outside of the root can be created, changed
+/// or removed. Absolute paths, `..` components that climb out of the root and symbolic
+/// links that point outside of it are all rejected with a [`RootedError::Escape`], even if
+/// the wrapped operation would have handled the underlying I/O error itself.
+///
+/// Use it whenever part of a path comes from user input.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, generate::Generate, sandbox::{Rooted, RootedError}};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Controller;
+/// # impl Anvil for Controller {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"content")
+/// #     }
+/// # }
+///
+/// let name = "../../etc/profile"; // from the command line
+/// let controller = Rooted::new(Generate::new(Controller), "./my-app");
+///
+/// let result = controller.forge(format!("src/controllers/{name}.rs"));
+/// assert!(matches!(result, Err(RootedError::Escape(_))));
+/// ```
+pub struct Rooted<F: Forge> {
+    /// The operation to confine
+    forge: F,
+    /// The directory that the operation must stay inside
+    root: PathBuf,
+}
+
+impl<F: Forge> Rooted<F> {
+    /// Creates a new `Rooted` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to confine
+    /// * `root` - The directory that paths are relative to and that the operation must
+    ///   stay inside
+    ///
+    /// # Returns
+    ///
+    /// A new `Rooted` instance.
+    pub fn new(forge: F, root: impl AsRef<Path>) -> Self {
+        Self {
+            forge,
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl<F: Forge> Forge for Rooted<F>
+where
+    F::Error: 'static,
+{
+    type Error = RootedError<F::Error>;
+
+    /// Runs the wrapped operation at the given path inside the root.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem to run the operation in
+    /// * `into` - The path to run the operation at, relative to the root
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RootedError<F::Error>>` - Ok if successful, or an error if:
+    ///   - The path is absolute or leads outside of the root
+    ///   - The operation tried to modify anything outside of the root
+    ///   - The wrapped operation failed
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        if into.has_root() || matches!(into.components().next(), Some(Component::Prefix(_))) {
+            return Err(RootedError::Escape(EscapeError::Absolute {
+                path: into.to_path_buf(),
+                root: self.root.clone(),
+            }));
+        }
+        let target = self.root.join(into);
+        if !is_within(&self.root, &target) {
+            return Err(RootedError::Escape(EscapeError::Outside {
+                path: into.to_path_buf(),
+                root: self.root.clone(),
+            }));
+        }
+
+        let sandbox = Sandbox::new(fs, &self.root);
+        let result = self.forge.forge_in(&sandbox, &target);
+        if let Some(escape) = sandbox.escape() {
+            return Err(RootedError::Escape(escape));
+        }
+        result.map_err(RootedError::Forge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "memory")]
+    use crate::{fs::Memory, mover::Move};
+    use crate::{generate::Generate, Anvil};
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn generate() -> Generate<MockAnvil> {
+        Generate::new(MockAnvil {
+            content: "content".to_string(),
+        })
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_rooted_resolves_paths_relative_to_root() {
+        let fs = Memory::new().with_dir("project");
+
+        Rooted::new(generate(), "project")
+            .forge_in(&fs, "src/./models/../lib.rs")
+            .unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("project/src/lib.rs")).unwrap(),
+            b"content"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_rooted_rejects_absolute_paths_and_parent_escapes() {
+        let fs = Memory::new().with_dir("project");
+        let rooted = Rooted::new(generate(), "project");
+
+        let result = rooted.forge_in(&fs, "/etc/passwd");
+        assert!(matches!(
+            result,
+            Err(RootedError::Escape(EscapeError::Absolute { .. }))
+        ));
+
+        let result = rooted.forge_in(&fs, "src/../../outside.txt");
+        assert!(matches!(
+            result,
+            Err(RootedError::Escape(EscapeError::Outside { .. }))
+        ));
+        assert!(!fs.exists(Path::new("outside.txt")));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_rooted_rejects_writes_outside_root_made_by_the_operation() {
+        let fs = Memory::new()
+            .with_file("secret.txt", "secret")
+            .with_dir("project");
+
+        // Moving a file into the root still removes it from outside of the root
+        let result = Rooted::new(Move::new("secret.txt"), "project").forge_in(&fs, "stolen.txt");
+
+        assert!(matches!(
+            result,
+            Err(RootedError::Escape(EscapeError::Outside { .. }))
+        ));
+        assert!(fs.exists(Path::new("secret.txt")));
+        assert!(!fs.exists(Path::new("project/stolen.txt")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rooted_rejects_symlinks_pointing_outside_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        let outside = temp.path().join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        let result = Rooted::new(generate(), &root).forge("link/file.txt");
+
+        assert!(matches!(
+            result,
+            Err(RootedError::Escape(EscapeError::Symlink { .. }))
+        ));
+        assert!(!outside.join("file.txt").exists());
+
+        // Paths that don't go through the link are fine
+        Rooted::new(generate(), &root)
+            .forge("src/file.txt")
+            .unwrap();
+        assert!(root.join("src/file.txt").exists());
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{fs::FileSystem, Forge};
+
+/// A struct that runs one operation and then another at the same path.
+///
+/// `Then` is the building block for multi-step plans such as generating a file and then
+/// transforming it. The second operation only runs if the first succeeds, and a failure is
+/// reported as a [`ThenError`] that says which of the two failed. Longer chains are built
+/// by nesting, which [`ForgeExt::then`](crate::ForgeExt::then) makes easy to read.
+///
+/// The steps are not undone if a later one fails. Wrap the chain in a
+/// [`Transaction`](crate::transaction::Transaction) for that.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, generate::Generate, transform::Transform};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Readme;
+/// # impl Anvil for Readme {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"# Title")
+/// #     }
+/// # }
+///
+/// let readme = Generate::new(Readme).then(Transform::new(
+///     |content| -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///         Ok(content.replace("Title", "My App"))
+///     },
+/// ));
+///
+/// readme.forge("./README.md").unwrap();
+/// ```
+pub struct Then<A: Forge, B: Forge> {
+    /// The operation to run first
+    first: A,
+    /// The operation to run once the first has succeeded
+    second: B,
+}
+
+/// Errors that can occur when running a [`Then`] operation.
+///
+/// A chain built with [`ForgeExt::then`](crate::ForgeExt::then) nests one `Then` inside
+/// another, so its error nests too: the failure of the third step of `a.then(b).then(c)` is
+/// `ThenError::Second`, but the failure of the first is `ThenError::First(ThenError::First(_))`.
+/// There is no flat step index. When the failing step must be identified by position, use
+/// a [`Sequence`], whose [`SequenceError`] carries the index and path of the failed step.
+#[derive(Error, Debug)]
+pub enum ThenError<A: Error + 'static, B: Error + 'static> {
+    /// The first operation failed, so the second was not run.
+    #[error("first step failed: {0}")]
+    First(#[source] A),
+
+    /// The first operation succeeded, but the second failed.
+    #[error("second step failed: {0}")]
+    Second(#[source] B),
+}
+
+impl<A: Forge, B: Forge> Then<A, B> {
+    /// Creates a new `Then` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `first` - The operation to run first
+    /// * `second` - The operation to run once the first has succeeded
+    ///
+    /// # Returns
+    ///
+    /// A new `Then` instance.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Forge, B: Forge> Forge for Then<A, B>
+where
+    A::Error: 'static,
+    B::Error: 'static,
+{
+    type Error = ThenError<A::Error, B::Error>;
+
+    /// Runs the first operation, then the second, at the given path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem both operations run against
+    /// * `into` - Path where both operations are performed
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), ThenError<A::Error, B::Error>>` - Ok if both operations succeed, or the
+    ///   error from whichever failed.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        self.first.forge_in(fs, into).map_err(ThenError::First)?;
+        self.second.forge_in(fs, into).map_err(ThenError::Second)
+    }
+}
+
+/// A struct that runs an operation at its own path, relative to the path it is forged into.
+///
+/// `At` lets a step of a [`Then`] chain or a [`Sequence`] target its own file, such as
+/// registering a new module in `mod.rs` after generating it. Like the steps of a
+/// [`Sequence`] or a [`Transaction`](crate::transaction::Transaction), the path is joined
+/// onto the path the operation is forged into, so a chain of `At`s is usually forged into
+/// the project directory. An absolute path is used as is.
+///
+/// Only the path the wrapped operation runs at is rebased. Paths that the operation holds
+/// itself, such as the source of a [`Move`](crate::mover::Move), are used as given.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, ForgeExt, append::Append, generate::Generate};
+/// # use anvil::Anvil;
+/// # use std::io::Write;
+/// # struct Model;
+/// # impl Anvil for Model {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"pub struct User;\n")
+/// #     }
+/// # }
+/// # struct ModLine;
+/// # impl Anvil for ModLine {
+/// #     type Error = std::io::Error;
+/// #     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+/// #         writer.write_all(b"pub mod user;\n")
+/// #     }
+/// # }
+///
+/// let model = Generate::new(Model)
+///     .at("src/models/user.rs")
+///     .then(Append::new(ModLine).at("src/models/mod.rs"));
+///
+/// model.forge(".").unwrap();
+/// ```
+pub struct At<F: Forge> {
+    /// The operation to run
+    forge: F,
+    /// The path to run it at
+    path: PathBuf,
+}
+
+impl<F: Forge> At<F> {
+    /// Creates a new `At` operation.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `path` - The path to run it at, relative to the path it is forged into
+    ///
+    /// # Returns
+    ///
+    /// A new `At` instance.
+    pub fn new(forge: F, path: impl AsRef<Path>) -> Self {
+        Self {
+            forge,
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl<F: Forge> Forge for At<F> {
+    type Error = F::Error;
+
+    /// Runs the wrapped operation at its own path.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the operation runs against
+    /// * `into` - The base path the operation's own path is resolved against
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), F::Error>` - The result of the wrapped operation.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        self.forge.forge_in(fs, into.as_ref().join(&self.path))
+    }
+}
+
+/// A struct that runs a list of operations of the same type, one after another.
+///
+/// Each step runs at the path the sequence is forged into, or at its own path if it was
+/// added with [`step_at`](Sequence::step_at). Like those of a
+/// [`Transaction`](crate::transaction::Transaction), a step's own path is relative to the
+/// path the sequence is forged into, and an absolute path is used as is. This makes it easy
+/// to apply one kind of operation to many files. The sequence stops at the first step that fails, and reports
+/// it as a [`SequenceError`] with the step's position and path.
+///
+/// Operations of different types can be chained with [`Then`] instead.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, mkdir::Mkdir, sequence::Sequence};
+///
+/// let layout = Sequence::new()
+///     .step_at(Mkdir::new(), "src/models")
+///     .step_at(Mkdir::new(), "src/controllers")
+///     .step_at(Mkdir::new(), "migrations");
+///
+/// layout.forge("./my-app").unwrap();
+/// ```
+pub struct Sequence<F: Forge> {
+    /// The steps to run, with the path each runs at if it has its own
+    steps: Vec<(F, Option<PathBuf>)>,
+}
+
+/// Error that occurred in one step of a [`Sequence`].
+#[derive(Error, Debug)]
+#[error("step {step} failed at {}: {source}", path.display())]
+pub struct SequenceError<E: Error + 'static> {
+    /// The position of the failed step, counting from zero
+    pub step: usize,
+    /// The path the failed step ran at
+    pub path: PathBuf,
+    /// The error the step failed with
+    #[source]
+    pub source: E,
+}
+
+impl<F: Forge> Default for Sequence<F> {
+    fn default() -> Self {
+        Self { steps: Vec::new() }
+    }
+}
+
+impl<F: Forge> Sequence<F> {
+    /// Creates an empty sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step that runs at the path the sequence is forged into.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    ///
+    /// # Returns
+    ///
+    /// The `Sequence` instance with the step added.
+    pub fn step(mut self, forge: F) -> Self {
+        self.steps.push((forge, None));
+        self
+    }
+
+    /// Adds a step that runs at its own path.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `path` - The path to run it at, relative to the path the sequence is forged into
+    ///
+    /// # Returns
+    ///
+    /// The `Sequence` instance with the step added.
+    pub fn step_at(mut self, forge: F, path: impl AsRef<Path>) -> Self {
+        self.steps.push((forge, Some(path.as_ref().to_path_buf())));
+        self
+    }
+
+    /// Returns the number of steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the sequence has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl<F: Forge> FromIterator<F> for Sequence<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        Self {
+            steps: iter.into_iter().map(|forge| (forge, None)).collect(),
+        }
+    }
+}
+
+impl<F: Forge, P: AsRef<Path>> FromIterator<(F, P)> for Sequence<F> {
+    fn from_iter<I: IntoIterator<Item = (F, P)>>(iter: I) -> Self {
+        Self {
+            steps: iter
+                .into_iter()
+                .map(|(forge, path)| (forge, Some(path.as_ref().to_path_buf())))
+                .collect(),
+        }
+    }
+}
+
+impl<F: Forge> Forge for Sequence<F>
+where
+    F::Error: 'static,
+{
+    type Error = SequenceError<F::Error>;
+
+    /// Runs every step in order, stopping at the first that fails.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the steps run against
+    /// * `into` - Path where steps without their own path are performed, and that the
+    ///   paths of the other steps are resolved against
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), SequenceError<F::Error>>` - Ok if every step succeeds, or the error
+    ///   from the first step that failed, along with its position and path.
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let into = into.as_ref();
+        for (step, (forge, path)) in self.steps.iter().enumerate() {
+            let path = match path {
+                Some(path) => into.join(path),
+                None => into.to_path_buf(),
+            };
+            forge
+                .forge_in(fs, &path)
+                .map_err(|source| SequenceError { step, path, source })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        append::Append, fs::Memory, generate::Generate, transform::Transform, Anvil, ForgeExt,
+    };
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_then_runs_steps_in_order_and_reports_failed_step() {
+        let fs = Memory::new().with_file("src/models/mod.rs", "pub mod post;\n");
+
+        let model = Generate::new(mock("pub struct User;\n"))
+            .then(Transform::new(
+                |content| -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+                    Ok(content.replace("User", "Account"))
+                },
+            ))
+            .at("account.rs")
+            .then(Append::new(mock("pub mod account;\n")).at("mod.rs"));
+
+        // Each step's path is relative to the path the chain is forged into
+        model.forge_in(&fs, "src/models").unwrap();
+        assert_eq!(
+            fs.read_to_string(Path::new("src/models/account.rs"))
+                .unwrap(),
+            "pub struct Account;\n"
+        );
+        assert_eq!(
+            fs.read_to_string(Path::new("src/models/mod.rs")).unwrap(),
+            "pub mod post;\npub mod account;\n"
+        );
+
+        // Generating again fails in the very first step
+        let result = model.forge_in(&fs, "src/models");
+        assert!(matches!(result, Err(ThenError::First(ThenError::First(_)))));
+    }
+
+    #[test]
+    fn test_sequence_targets_each_step_path_and_reports_failed_step() {
+        let fs = Memory::new().with_file("out/b.txt", "exists");
+
+        let sequence: Sequence<_> = [("a.txt", "a"), ("b.txt", "b"), ("c.txt", "c")]
+            .into_iter()
+            .map(|(path, content)| (Generate::new(mock(content)), path))
+            .collect();
+        assert_eq!(sequence.len(), 3);
+
+        // Step paths are resolved against the path the sequence is forged into
+        let error = sequence.forge_in(&fs, "out").unwrap_err();
+        assert_eq!(error.step, 1);
+        assert_eq!(error.path, Path::new("out/b.txt"));
+
+        // Steps before the failure ran, and steps after it did not
+        assert!(fs.exists(Path::new("out/a.txt")));
+        assert!(!fs.exists(Path::new("out/c.txt")));
+
+        // Steps without their own path run at the path the sequence is forged into
+        Sequence::new()
+            .step(Append::new(mock("1")))
+            .step(Append::new(mock("2")))
+            .forge_in(&fs, "out/b.txt")
+            .unwrap();
+        assert_eq!(
+            fs.read_to_string(Path::new("out/b.txt")).unwrap(),
+            "exists12"
+        );
+    }
+}
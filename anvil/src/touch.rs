@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{
+    fs::{FileSystem, Kept},
+    Forge,
+};
+
+/// A struct that makes sure a file exists.
+///
+/// `Touch` creates an empty file at the path it is forged into, along with any missing
+/// parent directories. An existing file is left exactly as it is, which makes `Touch`
+/// useful for placeholder files such as `.gitkeep` or an empty `mod.rs` that later
+/// operations append to.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Forge, touch::Touch};
+///
+/// // Keep an otherwise empty directory in version control
+/// match Touch::new().forge("./my-app/storage/.gitkeep") {
+///     Ok(_) => println!("File ready"),
+///     Err(e) => eprintln!("Failed to create file: {}", e),
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Touch;
+
+/// Errors that can occur while creating an empty file.
+#[derive(Error, Debug)]
+pub enum TouchError {
+    /// Error that occurred during file IO operations.
+    #[error("failed to perform file I/O while creating a file: {0}")]
+    StdIo(#[from] std::io::Error),
+}
+
+impl Touch {
+    /// Creates a new `Touch` operation.
+    ///
+    /// # Returns
+    ///
+    /// A new `Touch` instance that creates whatever file it is forged into.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Forge for Touch {
+    type Error = TouchError;
+
+    /// Creates an empty file if nothing exists at the path yet.
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem in which the file is created
+    /// * `into` - The file to create
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), TouchError>` - Ok if the file exists afterwards, or an error if:
+    ///   - A directory exists at the path
+    ///   - Parent directories couldn't be created
+    ///   - File permissions don't allow the operation
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let path = into.as_ref();
+        match fs.metadata(path) {
+            Ok(metadata) if metadata.is_file() => {
+                fs.keep(path, Kept::Identical);
+                Ok(())
+            }
+            Ok(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::IsADirectory,
+                format!("is a directory: {}", path.display()),
+            )
+            .into()),
+            Err(_) => {
+                if let Some(parent) = path.parent() {
+                    fs.create_dir_all(parent)?;
+                }
+                Ok(fs.create_new(path, b"")?)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::fs::Memory;
+
+    #[test]
+    fn test_touch_creates_missing_file_and_keeps_existing_one() {
+        let fs = Memory::new().with_file("existing.txt", "content");
+
+        Touch::new().forge_in(&fs, "storage/.gitkeep").unwrap();
+        Touch::new().forge_in(&fs, "existing.txt").unwrap();
+
+        assert_eq!(fs.read(Path::new("storage/.gitkeep")).unwrap(), b"");
+        assert_eq!(fs.read(Path::new("existing.txt")).unwrap(), b"content");
+        assert!(matches!(
+            Touch::new().forge_in(&fs, "storage"),
+            Err(TouchError::StdIo(e)) if e.kind() == std::io::ErrorKind::IsADirectory
+        ));
+    }
+}
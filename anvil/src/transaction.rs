@@ -0,0 +1,555 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    fs::{FileSystem, Kept, Metadata, Savepoint},
+    Forge,
+};
+
+/// A type-erased transaction step: an operation paired with the path it targets.
+type Step<'a> = (
+    PathBuf,
+    Box<dyn Fn(&dyn FileSystem, &Path) -> Result<(), Box<dyn Error + Send + Sync>> + 'a>,
+);
+
+/// A struct that runs a group of operations as a single all-or-nothing unit.
+///
+/// `Transaction` runs its steps in order against a journal of the filesystem. Before each
+/// change is made, the journal records what it is about to overwrite: the original
+/// contents of a file, the fact that a file or directory did not exist yet, or the
+/// source of a move. If any step fails, the journal is replayed in reverse and the tree
+/// is restored to exactly the state it was in before the transaction started.
+///
+/// Each step targets its own path, which is resolved relative to the path the
+/// transaction itself is forged into, just like the paths of
+/// [`Sequence::step_at`](crate::sequence::Sequence::step_at) and
+/// [`At`](crate::sequence::At). An absolute step path is used as is. Only the path a step
+/// runs at is rebased: paths an operation holds itself, such as the source of a
+/// [`Move`](crate::mover::Move), are used as given, so they should be absolute or relative
+/// to the working directory the transaction is forged from.
+///
+/// The journal also supports [`Savepoint`]s, so an [`Either`](crate::either::Either)
+/// inside a transaction rolls back the partial effects of its failed left side before
+/// trying the right side.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use anvil::{Anvil, Forge, append::Append, generate::Generate, transaction::Transaction};
+/// use std::io::Write;
+///
+/// struct Line(&'static str);
+///
+/// impl Anvil for Line {
+///     type Error = std::io::Error;
+///
+///     fn anvil(&self, writer: &mut (impl Write + Sized)) -> Result<(), Self::Error> {
+///         writer.write_all(self.0.as_bytes())
+///     }
+/// }
+///
+/// let resource = Transaction::new()
+///     .step(Generate::new(Line("pub struct User;\n")), "src/models/user.rs")
+///     .step(Append::new(Line("pub mod user;\n")), "src/models/mod.rs");
+///
+/// // If appending to mod.rs fails, user.rs is removed again
+/// match resource.forge(".") {
+///     Ok(_) => println!("Resource generated"),
+///     Err(e) => eprintln!("Nothing was changed: {}", e),
+/// }
+/// ```
+#[derive(Default)]
+pub struct Transaction<'a> {
+    /// The steps to run, in order
+    steps: Vec<Step<'a>>,
+}
+
+/// Errors that can occur while running a [`Transaction`].
+#[derive(Error, Debug)]
+pub enum TransactionError {
+    /// A step failed and every change made by the transaction was rolled back.
+    #[error("transaction step {index} failed on {}: {source}", path.display())]
+    Step {
+        /// The zero-based index of the step that failed
+        index: usize,
+        /// The path the step was performed on
+        path: PathBuf,
+        /// The error the step failed with
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// A step failed and some changes could not be rolled back.
+    #[error(
+        "transaction step {index} failed on {}: {source}; rolling back also failed: {rollback}",
+        path.display()
+    )]
+    Rollback {
+        /// The zero-based index of the step that failed
+        index: usize,
+        /// The path the step was performed on
+        path: PathBuf,
+        /// The error the step failed with
+        source: Box<dyn std::error::Error + Send + Sync>,
+        /// The first error encountered while restoring the original tree
+        rollback: io::Error,
+    },
+}
+
+impl<'a> Transaction<'a> {
+    /// Creates a new, empty `Transaction`.
+    ///
+    /// # Returns
+    ///
+    /// A new `Transaction` with no steps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an operation to the end of the transaction.
+    ///
+    /// # Parameters
+    ///
+    /// * `forge` - The operation to run
+    /// * `path` - The path to run the operation on, relative to the path the transaction
+    ///   is forged into
+    ///
+    /// # Returns
+    ///
+    /// The transaction, so that steps can be added in a chain.
+    pub fn step<F>(mut self, forge: F, path: impl AsRef<Path>) -> Self
+    where
+        F: Forge + 'a,
+        F::Error: Send + Sync + 'static,
+    {
+        self.steps.push((
+            path.as_ref().to_path_buf(),
+            Box::new(move |fs, path| forge.forge_in(fs, path).map_err(|e| e.into())),
+        ));
+        self
+    }
+}
+
+impl Forge for Transaction<'_> {
+    type Error = TransactionError;
+
+    /// Runs every step, restoring the original tree if any of them fails.
+    ///
+    /// This method:
+    /// 1. Wraps the filesystem in a journal
+    /// 2. Runs each step on its path, resolved relative to `into`
+    /// 3. If a step fails, replays the journal in reverse to undo every change, then rolls
+    ///    the filesystem back to before the first step
+    ///
+    /// # Parameters
+    ///
+    /// * `fs` - The filesystem the steps run against
+    /// * `into` - The base path that step paths are resolved against
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), TransactionError>` - Ok if every step succeeded, or an error if:
+    ///   - A step failed (the tree has been restored)
+    ///   - A step failed and the tree could not be fully restored
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+        let base = into.as_ref();
+        let journal = Journal::new(fs);
+        let start = fs.savepoint();
+
+        for (index, (path, step)) in self.steps.iter().enumerate() {
+            let path = base.join(path);
+            if let Err(source) = step(&journal, &path) {
+                let restored = journal.restore_to(0);
+                fs.rollback_to(start, &*source);
+                return match restored {
+                    Ok(()) => Err(TransactionError::Step {
+                        index,
+                        path,
+                        source,
+                    }),
+                    Err(rollback) => Err(TransactionError::Rollback {
+                        index,
+                        path,
+                        source,
+                        rollback,
+                    }),
+                };
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What a [`Journal`] needs to do to undo a single change.
+#[derive(Debug)]
+enum Undo {
+    /// Restore a file to its original contents, or remove it if it did not exist
+    File {
+        path: PathBuf,
+        original: Option<Vec<u8>>,
+    },
+    /// Remove a directory that was created
+    CreatedDir { path: PathBuf },
+    /// Recreate a directory that was removed
+    RemovedDir { path: PathBuf },
+    /// Move an entry back to where it came from
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+/// A [`FileSystem`] wrapper that records how to undo every change it forwards.
+struct Journal<'a> {
+    inner: &'a dyn FileSystem,
+    entries: RefCell<Vec<Undo>>,
+    /// The inner savepoint and number of entries at each savepoint taken
+    savepoints: RefCell<Vec<(Savepoint, usize)>>,
+}
+
+impl<'a> Journal<'a> {
+    fn new(inner: &'a dyn FileSystem) -> Self {
+        Self {
+            inner,
+            entries: RefCell::new(Vec::new()),
+            savepoints: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn record_file(&self, path: &Path) -> io::Result<()> {
+        let original = match self.inner.metadata(path) {
+            Ok(metadata) if metadata.is_file() => Some(self.inner.read(path)?),
+            _ => None,
+        };
+        self.entries.borrow_mut().push(Undo::File {
+            path: path.to_path_buf(),
+            original,
+        });
+        Ok(())
+    }
+
+    /// Undoes every change recorded after the first `len` entries, most recent first.
+    ///
+    /// Every entry is attempted even if an earlier one fails; the first failure is returned.
+    fn restore_to(&self, len: usize) -> io::Result<()> {
+        let undone = self.entries.borrow_mut().split_off(len);
+        let mut result = Ok(());
+        for undo in undone.into_iter().rev() {
+            let outcome = match undo {
+                Undo::File {
+                    path,
+                    original: Some(contents),
+                } => self.inner.write(&path, &contents),
+                Undo::File {
+                    path,
+                    original: None,
+                } => match self.inner.remove_file(&path) {
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                    other => other,
+                },
+                Undo::CreatedDir { path } => self.inner.remove_dir(&path),
+                Undo::RemovedDir { path } => self.inner.create_dir_all(&path),
+                Undo::Rename { from, to } => self.inner.rename(&to, &from),
+            };
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+        result
+    }
+}
+
+impl FileSystem for Journal<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.record_file(path)?;
+        self.inner.write(path, contents)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.create_new(path, contents)?;
+        self.entries.borrow_mut().push(Undo::File {
+            path: path.to_path_buf(),
+            original: None,
+        });
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.record_file(path)?;
+        self.inner.append(path, contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.record_file(to)?;
+        self.inner.rename(from, to)?;
+        self.entries.borrow_mut().push(Undo::Rename {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut missing: Vec<PathBuf> = path
+            .ancestors()
+            .filter(|ancestor| ancestor.parent().is_some())
+            .take_while(|ancestor| !self.inner.exists(ancestor))
+            .map(Path::to_path_buf)
+            .collect();
+        self.inner.create_dir_all(path)?;
+        missing.reverse();
+        self.entries
+            .borrow_mut()
+            .extend(missing.into_iter().map(|path| Undo::CreatedDir { path }));
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.record_file(path)?;
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_dir(path)?;
+        self.entries.borrow_mut().push(Undo::RemovedDir {
+            path: path.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.inner.set_mode(path, mode)
+    }
+
+    fn keep(&self, path: &Path, kept: Kept) {
+        self.inner.keep(path, kept)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.inner.symlink(target, link)?;
+        self.entries.borrow_mut().push(Undo::File {
+            path: link.to_path_buf(),
+            original: None,
+        });
+        Ok(())
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        let inner = self.inner.savepoint();
+        let mut savepoints = self.savepoints.borrow_mut();
+        savepoints.push((inner, self.entries.borrow().len()));
+        Savepoint::new(savepoints.len() - 1)
+    }
+
+    fn rollback_to(&self, savepoint: Savepoint, cause: &dyn Error) {
+        let (inner, len) = {
+            let mut savepoints = self.savepoints.borrow_mut();
+            let Some(&taken) = savepoints.get(savepoint.position()) else {
+                return;
+            };
+            savepoints.truncate(savepoint.position());
+            taken
+        };
+        // Errors here surface when the transaction itself fails and restores the rest of
+        // the tree; a branch that cannot be undone leaves the fallback to fail visibly.
+        let _ = self.restore_to(len);
+        // Filesystems with their own history also discard the undone changes, so the
+        // branch leaves no trace in them at all
+        self.inner.rollback_to(inner, cause);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{append::Append, generate::Generate, Anvil};
+    #[cfg(feature = "memory")]
+    use crate::{either::either, fs::Memory, mover::Move, transform::Transform, ForgeExt};
+    #[cfg(feature = "memory")]
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    // Mock implementation for Anvil
+    struct MockAnvil {
+        content: String,
+    }
+
+    impl Anvil for MockAnvil {
+        type Error = std::io::Error;
+
+        fn anvil(&self, writer: &mut (impl std::io::Write + Sized)) -> Result<(), Self::Error> {
+            writer.write_all(self.content.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    fn mock(content: &str) -> MockAnvil {
+        MockAnvil {
+            content: content.to_string(),
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_transaction_runs_every_step() {
+        let fs = Memory::new().with_file("src/mod.rs", "pub mod a;\n");
+
+        let transaction = Transaction::new()
+            .step(Generate::new(mock("pub struct B;\n")), "src/b.rs")
+            .step(Append::new(mock("pub mod b;\n")), "src/mod.rs");
+
+        transaction.forge_in(&fs, "").unwrap();
+
+        let expected = BTreeMap::from([
+            (PathBuf::from("src/b.rs"), "pub struct B;\n".to_string()),
+            (
+                PathBuf::from("src/mod.rs"),
+                "pub mod a;\npub mod b;\n".to_string(),
+            ),
+        ]);
+        assert_eq!(fs.snapshot(), expected);
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_transaction_resolves_nested_step_paths_against_into() {
+        let fs = Memory::new().with_file("app/src/mod.rs", "pub mod a;\n");
+
+        // Paths given to At inside a step are relative to the step's own path
+        let module = Generate::new(mock("pub struct B;\n"))
+            .at("b.rs")
+            .then(Append::new(mock("pub mod b;\n")).at("mod.rs"));
+        Transaction::new()
+            .step(module, "src")
+            .forge_in(&fs, "app")
+            .unwrap();
+
+        assert_eq!(
+            fs.read_to_string(Path::new("app/src/b.rs")).unwrap(),
+            "pub struct B;\n"
+        );
+        assert_eq!(
+            fs.read_to_string(Path::new("app/src/mod.rs")).unwrap(),
+            "pub mod a;\npub mod b;\n"
+        );
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_transaction_restores_tree_when_step_fails() {
+        let fs = Memory::new()
+            .with_file("project/src/mod.rs", "pub mod a;\n")
+            .with_file("project/src/old.rs", "old");
+        let before = fs.snapshot();
+
+        let transaction = Transaction::new()
+            .step(Generate::new(mock("new")), "src/nested/new.rs")
+            .step(Append::new(mock("pub mod new;\n")), "src/mod.rs")
+            .step(Transform::new(|c| Ok(c.to_uppercase())), "src/mod.rs")
+            .step(Move::new("project/src/old.rs"), "src/renamed.rs")
+            .step(Append::new(mock("fails")), "src/missing.rs");
+
+        let result = transaction.forge_in(&fs, "project");
+
+        match result {
+            Err(TransactionError::Step { index, path, .. }) => {
+                assert_eq!(index, 4);
+                assert_eq!(path, PathBuf::from("project/src/missing.rs"));
+            }
+            other => unreachable!("Expected Step error but got: {:?}", other),
+        }
+
+        // Every file and directory is back to how it was
+        assert_eq!(fs.snapshot(), before);
+        assert!(!fs.exists(Path::new("project/src/nested")));
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_transaction_rolls_back_failed_either_branch() {
+        // An operation that changes the file before failing
+        struct PartialForge;
+
+        impl Forge for PartialForge {
+            type Error = std::io::Error;
+
+            fn forge_in(
+                &self,
+                fs: &dyn FileSystem,
+                into: impl AsRef<Path>,
+            ) -> Result<(), Self::Error> {
+                fs.append(into.as_ref(), b"partial")?;
+                Err(std::io::Error::other("Operation error"))
+            }
+        }
+
+        let fs = Memory::new().with_file("mod.rs", "existing\n");
+
+        // The left side appends partial content before failing, which must be undone
+        let transaction = Transaction::new().step(
+            either(PartialForge, Append::new(mock("fallback\n"))),
+            "mod.rs",
+        );
+
+        transaction.forge_in(&fs, "").unwrap();
+
+        assert_eq!(
+            fs.read_to_string(Path::new("mod.rs")).unwrap(),
+            "existing\nfallback\n"
+        );
+    }
+
+    #[test]
+    fn test_transaction_restores_tree_on_disk() {
+        // Create a temporary directory with an existing file
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("mod.rs"), "original").unwrap();
+
+        let transaction = Transaction::new()
+            .step(Generate::new(mock("created")), "nested/created.rs")
+            .step(Append::new(mock(" appended")), "mod.rs")
+            .step(Generate::new(mock("fails")), "mod.rs");
+
+        let result = transaction.forge(temp_dir.path());
+        assert!(result.is_err());
+
+        let content = std::fs::read_to_string(temp_dir.path().join("mod.rs")).unwrap();
+        assert_eq!(content, "original");
+        assert!(!temp_dir.path().join("nested").exists());
+    }
+}
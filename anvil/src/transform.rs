@@ -2,7 +2,7 @@ use std::{error::Error, path::Path};
 
 use thiserror::Error;
 
-use crate::Forge;
+use crate::{fs::FileSystem, Forge};
 
 /// A type alias for a boxed error that can be sent across threads.
 ///
@@ -133,13 +133,14 @@ impl Forge for Transform {
 
     /// Transforms the content of the file at the specified path.
     ///
-    /// This method:
+    /// This method performs all file access through the given [`FileSystem`]:
     /// 1. Reads the file content
     /// 2. Applies the transformation function
     /// 3. Writes the transformed content back to the file
     ///
     /// # Parameters
     ///
+    /// * `fs` - The filesystem containing the file
     /// * `into` - Path to the file to transform
     ///
     /// # Returns
@@ -153,11 +154,12 @@ impl Forge for Transform {
     ///
     /// Returns `TransformError::StdIo` if there's an IO error (like file not found),
     /// or `TransformError::Transform` if there's an error during the transformation.
-    fn forge(&self, into: impl AsRef<Path>) -> Result<(), Self::Error> {
+    fn forge_in(&self, fs: &dyn FileSystem, into: impl AsRef<Path>) -> Result<(), Self::Error> {
         let path = into.as_ref();
-        let content = std::fs::read_to_string(path).map_err(TransformError::StdIo)?;
+        let content = fs.read_to_string(path).map_err(TransformError::StdIo)?;
         let transformed = self.apply(&content).map_err(TransformError::Transform)?;
-        std::fs::write(path, transformed).map_err(TransformError::StdIo)?;
+        fs.write(path, transformed.as_bytes())
+            .map_err(TransformError::StdIo)?;
         Ok(())
     }
 }
@@ -0,0 +1,57 @@
+use anvil::{
+    inject::{Inject, Location},
+    Forge,
+};
+use askama::Template;
+
+use crate::Askama;
+
+pub trait AskamaInjectExt<'a, T: Template>: Forge {
+    fn askama(template: &'a T, location: Location) -> Self;
+}
+
+impl<'a, T: Template> AskamaInjectExt<'a, T> for Inject<Askama<'a, T>> {
+    fn askama(template: &'a T, location: Location) -> Self {
+        Self::new(Askama(template), location)
+    }
+}
+
+#[inline(always)]
+pub fn inject<T: Template>(template: &T, location: Location) -> Inject<Askama<'_, T>> {
+    Inject::askama(template, location)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[derive(Template)]
+    #[template(source = "Injected content.", ext = "txt")]
+    struct TestTemplate;
+
+    #[test]
+    fn it_fails_if_marker_is_missing() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate, location).forge(&file_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_injects_before_marker() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n// anvil:notes\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate, location).forge(&file_path);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            "Initial content.\nInjected content.\n// anvil:notes\n"
+        );
+    }
+}
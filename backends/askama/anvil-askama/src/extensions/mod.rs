@@ -1,3 +1,4 @@
 pub mod generate;
+pub mod inject;
 
 pub mod append;
@@ -19,6 +19,7 @@ pub mod prelude {
     pub use crate::extensions::{
         append::{append, AskamaAppendExt},
         generate::{generate, AskamaGenerateExt},
+        inject::{inject, AskamaInjectExt},
     };
     pub use crate::filters::*;
     pub use crate::Askama;
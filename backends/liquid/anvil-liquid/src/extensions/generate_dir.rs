@@ -0,0 +1,116 @@
+use anvil::{
+    embed::Source,
+    generate_dir::{GenerateDir, Renderer},
+    Forge,
+};
+use liquid::Parser;
+use serde::Serialize;
+
+/// Renders templates discovered at runtime with a liquid [`Parser`], so that filters and
+/// tags registered on it are available, against a serializable context.
+pub struct LiquidRenderer<'a, T: Serialize> {
+    parser: &'a Parser,
+    context: &'a T,
+}
+
+impl<'a, T: Serialize> LiquidRenderer<'a, T> {
+    /// Creates a renderer, for example to render a [`PathTemplate`] with the same context
+    /// and filters as the template contents.
+    ///
+    /// [`PathTemplate`]: anvil::path_template::PathTemplate
+    pub fn new(parser: &'a Parser, context: &'a T) -> Self {
+        Self { parser, context }
+    }
+}
+
+impl<T: Serialize> Renderer for LiquidRenderer<'_, T> {
+    type Error = liquid::Error;
+
+    fn render(&self, source: &str) -> Result<String, Self::Error> {
+        let globals = liquid::to_object(self.context)?;
+        self.parser.parse(source)?.render(&globals)
+    }
+}
+
+pub trait LiquidGenerateDirExt<'a, T: Serialize>: Forge {
+    fn liquid(source: impl Into<Source>, parser: &'a Parser, context: &'a T) -> Self;
+}
+
+impl<'a, T: Serialize> LiquidGenerateDirExt<'a, T> for GenerateDir<LiquidRenderer<'a, T>> {
+    fn liquid(source: impl Into<Source>, parser: &'a Parser, context: &'a T) -> Self {
+        Self::new(source, LiquidRenderer { parser, context })
+    }
+}
+
+#[inline(always)]
+pub fn generate_dir<'a, T: Serialize>(
+    source: impl Into<Source>,
+    parser: &'a Parser,
+    context: &'a T,
+) -> GenerateDir<LiquidRenderer<'a, T>> {
+    GenerateDir::liquid(source, parser, context)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anvil::path_template::PathTemplate;
+    use liquid::ParserBuilder;
+    use tempfile::tempdir;
+
+    #[derive(Serialize)]
+    struct Resource {
+        name: String,
+    }
+
+    #[test]
+    fn it_generates_directory_with_templated_names() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(templates.join("{{ name }}")).unwrap();
+        std::fs::write(
+            templates.join("{{ name }}/{{ name | upcase }}.txt"),
+            "Hello, {{ name }}!",
+        )
+        .unwrap();
+
+        let parser = ParserBuilder::with_stdlib().build().unwrap();
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &parser, &resource).forge(&output);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(output.join("user/USER.txt")).unwrap();
+        assert_eq!(content, "Hello, user!");
+    }
+
+    #[test]
+    fn it_fails_on_invalid_template() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(templates.join("broken.txt"), "{{ name").unwrap();
+
+        let parser = ParserBuilder::with_stdlib().build().unwrap();
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &parser, &resource).forge(&output);
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn it_renders_path_templates() {
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let parser = ParserBuilder::with_stdlib().build().unwrap();
+        let path = PathTemplate::new("src/{{ name }}/{{ name | upcase }}.rs")
+            .render(&LiquidRenderer::new(&parser, &resource))
+            .unwrap();
+        assert_eq!(path, std::path::Path::new("src/user/USER.rs"));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{Aqua, Water};
+use anvil::{
+    inject::{Inject, Location},
+    Forge,
+};
+
+pub trait LiquidInjectExt<'a, T: Water>: Forge {
+    fn liquid(template: &'a T, location: Location) -> Self;
+}
+
+impl<'a, T: Water> LiquidInjectExt<'a, T> for Inject<Aqua<'a, T>> {
+    fn liquid(template: &'a T, location: Location) -> Self {
+        Self::new(Aqua(template), location)
+    }
+}
+
+#[inline(always)]
+pub fn inject<T: Water>(template: &T, location: Location) -> Inject<Aqua<'_, T>> {
+    Inject::liquid(template, location)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use liquid::ParserBuilder;
+    use serde::Serialize;
+    use std::sync::LazyLock;
+    use tempfile::tempdir;
+
+    static PARSER: LazyLock<liquid::Parser> =
+        LazyLock::new(|| ParserBuilder::with_stdlib().build().unwrap());
+
+    #[derive(Serialize)]
+    struct TestTemplate {}
+
+    impl Water for TestTemplate {
+        fn liquid(&self, writer: &mut dyn std::io::Write) -> Result<(), liquid::Error> {
+            let object = liquid::to_object(self)?;
+            let template = PARSER.parse("Injected content.\n")?;
+            template.render_to(writer, &object)
+        }
+    }
+
+    #[test]
+    fn it_fails_if_marker_is_missing() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_injects_before_marker() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n// anvil:notes\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            "Initial content.\nInjected content.\n// anvil:notes\n"
+        );
+    }
+}
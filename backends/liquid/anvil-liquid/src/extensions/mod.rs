@@ -1,3 +1,5 @@
 pub mod generate;
+pub mod generate_dir;
+pub mod inject;
 
 pub mod append;
@@ -23,6 +23,8 @@ pub mod prelude {
     pub use crate::extensions::{
         append::{append, LiquidAppendExt},
         generate::{generate, LiquidGenerateExt},
+        generate_dir::{generate_dir, LiquidGenerateDirExt, LiquidRenderer},
+        inject::{inject, LiquidInjectExt},
     };
 
     pub use crate::Water;
@@ -0,0 +1,114 @@
+use anvil::{
+    embed::Source,
+    generate_dir::{GenerateDir, Renderer},
+    Forge,
+};
+use minijinja::Environment;
+use serde::Serialize;
+
+/// Renders templates discovered at runtime with a minijinja [`Environment`], so that
+/// filters and functions registered on it are available, against a serializable context.
+pub struct MinijinjaRenderer<'a, 'env, T: Serialize> {
+    env: &'a Environment<'env>,
+    context: &'a T,
+}
+
+impl<'a, 'env, T: Serialize> MinijinjaRenderer<'a, 'env, T> {
+    /// Creates a renderer, for example to render a [`PathTemplate`] with the same context
+    /// and filters as the template contents.
+    ///
+    /// [`PathTemplate`]: anvil::path_template::PathTemplate
+    pub fn new(env: &'a Environment<'env>, context: &'a T) -> Self {
+        Self { env, context }
+    }
+}
+
+impl<T: Serialize> Renderer for MinijinjaRenderer<'_, '_, T> {
+    type Error = minijinja::Error;
+
+    fn render(&self, source: &str) -> Result<String, Self::Error> {
+        self.env.render_str(source, self.context)
+    }
+}
+
+pub trait MinijinjaGenerateDirExt<'a, 'env, T: Serialize>: Forge {
+    fn minijinja(source: impl Into<Source>, env: &'a Environment<'env>, context: &'a T) -> Self;
+}
+
+impl<'a, 'env, T: Serialize> MinijinjaGenerateDirExt<'a, 'env, T>
+    for GenerateDir<MinijinjaRenderer<'a, 'env, T>>
+{
+    fn minijinja(source: impl Into<Source>, env: &'a Environment<'env>, context: &'a T) -> Self {
+        Self::new(source, MinijinjaRenderer { env, context })
+    }
+}
+
+#[inline(always)]
+pub fn generate_dir<'a, 'env, T: Serialize>(
+    source: impl Into<Source>,
+    env: &'a Environment<'env>,
+    context: &'a T,
+) -> GenerateDir<MinijinjaRenderer<'a, 'env, T>> {
+    GenerateDir::minijinja(source, env, context)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anvil::path_template::PathTemplate;
+    use tempfile::tempdir;
+
+    #[derive(Serialize)]
+    struct Resource {
+        name: String,
+    }
+
+    #[test]
+    fn it_generates_directory_with_templated_names() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(templates.join("{{ name }}")).unwrap();
+        std::fs::write(
+            templates.join("{{ name }}/{{ name | upper }}.txt"),
+            "Hello, {{ name }}!",
+        )
+        .unwrap();
+
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &Environment::new(), &resource).forge(&output);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(output.join("user/USER.txt")).unwrap();
+        assert_eq!(content, "Hello, user!");
+    }
+
+    #[test]
+    fn it_fails_on_invalid_template() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(templates.join("broken.txt"), "{{ name").unwrap();
+
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &Environment::new(), &resource).forge(&output);
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn it_renders_path_templates() {
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let env = Environment::new();
+        let path = PathTemplate::new("src/{{ name }}/{{ name | upper }}.rs")
+            .render(&MinijinjaRenderer::new(&env, &resource))
+            .unwrap();
+        assert_eq!(path, std::path::Path::new("src/user/USER.rs"));
+    }
+}
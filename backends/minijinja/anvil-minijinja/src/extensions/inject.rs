@@ -0,0 +1,70 @@
+use anvil::{
+    inject::{Inject, Location},
+    Forge,
+};
+
+use crate::{Katana, Shrine};
+
+pub trait MinijinjaInjectExt<'a, T: Shrine>: Forge {
+    fn minijinja(template: &'a T, location: Location) -> Self;
+}
+
+impl<'a, T: Shrine> MinijinjaInjectExt<'a, T> for Inject<Katana<'a, T>> {
+    fn minijinja(template: &'a T, location: Location) -> Self {
+        Self::new(Katana(template), location)
+    }
+}
+
+#[inline(always)]
+pub fn inject<T: Shrine>(template: &T, location: Location) -> Inject<Katana<'_, T>> {
+    Inject::minijinja(template, location)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use anvil::Forge;
+    use serde::Serialize;
+    use tempfile::tempdir;
+
+    use crate::extensions::inject::inject;
+
+    #[derive(Serialize)]
+    struct TestTemplate {}
+
+    impl Shrine for TestTemplate {
+        fn minijinja(&self, writer: &mut dyn std::io::Write) -> Result<(), minijinja::Error> {
+            let mut env = minijinja::Environment::new();
+            env.add_template("test", "Injected content.\n")?;
+            let tmpl = env.get_template("test")?;
+            tmpl.render_to_write(self, writer)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_fails_if_marker_is_missing() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_injects_before_marker() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n// anvil:notes\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            "Initial content.\nInjected content.\n// anvil:notes\n"
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod append;
 pub mod generate;
+pub mod generate_dir;
+pub mod inject;
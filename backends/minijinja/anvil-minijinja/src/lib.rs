@@ -22,6 +22,8 @@ pub mod prelude {
     pub use crate::extensions::{
         append::{append, MinijinjaAppendExt},
         generate::{generate, MinijinjaGenerateExt},
+        generate_dir::{generate_dir, MinijinjaGenerateDirExt, MinijinjaRenderer},
+        inject::{inject, MinijinjaInjectExt},
     };
     pub use crate::Shrine;
 }
@@ -0,0 +1,129 @@
+use anvil::{
+    embed::Source,
+    generate_dir::{GenerateDir, Renderer},
+    Forge,
+};
+use serde::Serialize;
+use std::cell::{OnceCell, RefCell};
+use tera::{Context, Tera};
+
+/// Renders templates discovered at runtime with a [`Tera`] instance, so that filters and
+/// functions registered on it are available, against a serializable context.
+///
+/// The instance is cloned once when the renderer is created, and the context is
+/// serialized once when the first template is rendered.
+pub struct TeraRenderer<'a, T: Serialize> {
+    tera: RefCell<Tera>,
+    data: &'a T,
+    context: OnceCell<Context>,
+}
+
+impl<'a, T: Serialize> TeraRenderer<'a, T> {
+    /// Creates a renderer, for example to render a [`PathTemplate`] with the same context
+    /// and filters as the template contents.
+    ///
+    /// [`PathTemplate`]: anvil::path_template::PathTemplate
+    pub fn new(tera: &Tera, context: &'a T) -> Self {
+        Self {
+            // `render_str` registers the source as a template, which needs a mutable instance
+            tera: RefCell::new(tera.clone()),
+            data: context,
+            context: OnceCell::new(),
+        }
+    }
+}
+
+impl<T: Serialize> Renderer for TeraRenderer<'_, T> {
+    type Error = tera::Error;
+
+    fn render(&self, source: &str) -> Result<String, Self::Error> {
+        let context = match self.context.get() {
+            Some(context) => context,
+            None => {
+                let context = Context::from_serialize(self.data)?;
+                self.context.get_or_init(|| context)
+            }
+        };
+        self.tera.borrow_mut().render_str(source, context)
+    }
+}
+
+pub trait TeraGenerateDirExt<'a, T: Serialize>: Forge {
+    fn tera(source: impl Into<Source>, tera: &'a Tera, context: &'a T) -> Self;
+}
+
+impl<'a, T: Serialize> TeraGenerateDirExt<'a, T> for GenerateDir<TeraRenderer<'a, T>> {
+    fn tera(source: impl Into<Source>, tera: &'a Tera, context: &'a T) -> Self {
+        Self::new(source, TeraRenderer::new(tera, context))
+    }
+}
+
+#[inline(always)]
+pub fn generate_dir<'a, T: Serialize>(
+    source: impl Into<Source>,
+    tera: &'a Tera,
+    context: &'a T,
+) -> GenerateDir<TeraRenderer<'a, T>> {
+    GenerateDir::tera(source, tera, context)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anvil::path_template::PathTemplate;
+    use tempfile::tempdir;
+
+    #[derive(Serialize)]
+    struct Resource {
+        name: String,
+    }
+
+    #[test]
+    fn it_generates_directory_with_templated_names() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(templates.join("{{ name }}")).unwrap();
+        std::fs::write(
+            templates.join("{{ name }}/{{ name | upper }}.txt"),
+            "Hello, {{ name }}!",
+        )
+        .unwrap();
+
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &Tera::default(), &resource).forge(&output);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(output.join("user/USER.txt")).unwrap();
+        assert_eq!(content, "Hello, user!");
+    }
+
+    #[test]
+    fn it_fails_on_invalid_template() {
+        let dir = tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(templates.join("broken.txt"), "{{ name").unwrap();
+
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let output = dir.path().join("output");
+        let result = generate_dir(&templates, &Tera::default(), &resource).forge(&output);
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn it_renders_path_templates() {
+        let resource = Resource {
+            name: "user".to_string(),
+        };
+        let tera = Tera::default();
+        let path = PathTemplate::new("src/{{ name }}/{{ name | upper }}.rs")
+            .render(&TeraRenderer::new(&tera, &resource))
+            .unwrap();
+        assert_eq!(path, std::path::Path::new("src/user/USER.rs"));
+    }
+}
@@ -0,0 +1,78 @@
+use anvil::{
+    inject::{Inject, Location},
+    Forge,
+};
+
+use crate::{Earth, Firma};
+
+pub trait TeraInjectExt<'a, T: Earth>: Forge {
+    fn tera(template: &'a T, location: Location) -> Self;
+}
+
+impl<'a, T: Earth> TeraInjectExt<'a, T> for Inject<Firma<'a, T>> {
+    fn tera(template: &'a T, location: Location) -> Self {
+        Self::new(Firma(template), location)
+    }
+}
+
+#[inline(always)]
+pub fn inject<T: Earth>(template: &T, location: Location) -> Inject<Firma<'_, T>> {
+    Inject::tera(template, location)
+}
+
+#[cfg(test)]
+mod test {
+    static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| {
+        let mut tera = match Tera::new("templates/**/*") {
+            Ok(t) => t,
+            Err(e) => {
+                println!("Parsing error(s): {}", e);
+                ::std::process::exit(1);
+            }
+        };
+        tera.add_raw_template("test", "Injected content.\n")
+            .unwrap();
+        tera
+    });
+
+    use super::*;
+    use serde::Serialize;
+    use std::sync::LazyLock;
+    use tempfile::tempdir;
+    use tera::Tera;
+
+    #[derive(Serialize)]
+    struct TestTemplate {}
+
+    impl Earth for TestTemplate {
+        fn tera(&self, writer: &mut (impl std::io::Write + ?Sized)) -> tera::Result<()> {
+            let context = ::tera::Context::from_serialize(self)?;
+            TEMPLATES.render_to("test", &context, writer)
+        }
+    }
+
+    #[test]
+    fn it_fails_if_marker_is_missing() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_injects_before_marker() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("my-temporary-note.txt");
+        std::fs::write(&file_path, "Initial content.\n// anvil:notes\n").unwrap();
+        let location = Location::Before("// anvil:notes".to_string());
+        let result = inject(&TestTemplate {}, location).forge(&file_path);
+        assert!(result.is_ok());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            "Initial content.\nInjected content.\n// anvil:notes\n"
+        );
+    }
+}
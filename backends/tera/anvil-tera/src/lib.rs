@@ -22,6 +22,8 @@ pub mod prelude {
     pub use crate::extensions::{
         append::{append, TeraAppendExt},
         generate::{generate, TeraGenerateExt},
+        generate_dir::{generate_dir, TeraGenerateDirExt, TeraRenderer},
+        inject::{inject, TeraInjectExt},
     };
     pub use crate::Earth;
 }
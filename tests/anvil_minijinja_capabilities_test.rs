@@ -1,4 +1,4 @@
-use anvil::{mover::Move, transform::Transform, Forge};
+use anvil::{either::either, fs::Memory, mover::Move, transform::Transform, Forge};
 use anvil_minijinja::prelude::*;
 use anvil_minijinja_derive::Template;
 use serde::Serialize;
@@ -391,3 +391,73 @@ fn test_transform_preserves_file_on_empty_result() {
     let content = fs::read_to_string(&file_path).unwrap();
     assert_eq!(content, "");
 }
+
+// IN-MEMORY TESTS: rendering through MiniJinja without touching the disk
+#[test]
+fn test_generate_in_memory_creates_parent_directories() {
+    let memory = Memory::new();
+
+    let template = GenerateTemplate {
+        name: "MemoryFile".to_string(),
+        id: "mem123".to_string(),
+    };
+
+    let result = generate(&template).forge_in(&memory, "nested/deep/generate_test.txt");
+
+    assert!(result.is_ok());
+    let snapshot = memory.snapshot();
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(
+        snapshot[std::path::Path::new("nested/deep/generate_test.txt")].trim(),
+        "Generated file with MemoryFile - mem123"
+    );
+}
+
+#[test]
+fn test_either_in_memory_falls_back_to_append() {
+    let memory = Memory::new().with_file("either_test.txt", "existing content\n");
+
+    let primary_template = EitherPrimaryTemplate {
+        message: "primary".to_string(),
+    };
+    let fallback_template = EitherFallbackTemplate {
+        message: "fallback".to_string(),
+    };
+
+    let result = either(generate(&primary_template), append(&fallback_template))
+        .forge_in(&memory, "either_test.txt");
+
+    assert!(result.is_ok());
+    let snapshot = memory.snapshot();
+    let content = &snapshot[std::path::Path::new("either_test.txt")];
+    assert!(content.starts_with("existing content\n"));
+    assert!(content.contains("Fallback: fallback"));
+    assert!(!content.contains("Primary: primary"));
+}
+
+#[test]
+fn test_generate_dir_in_memory_renders_names_and_contents_with_minijinja() {
+    #[derive(Serialize)]
+    struct Resource {
+        name: String,
+    }
+
+    // Templates are read from the same in-memory tree they are generated into
+    let memory = Memory::new().with_file(
+        "templates/{{ name }}/{{ name | upper }}.txt",
+        "Hello, {{ name | title }}!",
+    );
+    let resource = Resource {
+        name: "user".to_string(),
+    };
+    let env = minijinja::Environment::new();
+
+    let result = generate_dir("templates", &env, &resource).forge_in(&memory, "out");
+
+    assert!(result.is_ok());
+    let snapshot = memory.snapshot();
+    assert_eq!(
+        snapshot[std::path::Path::new("out/user/USER.txt")],
+        "Hello, User!"
+    );
+}